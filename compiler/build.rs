@@ -0,0 +1,11 @@
+fn main() {
+    println!("cargo:rerun-if-changed=runtime/runtime.c");
+
+    // The runtime is linked into the programs the compiler builds, not into
+    // the compiler itself.
+    cc::Build::new()
+        .file("runtime/runtime.c")
+        .opt_level(2)
+        .cargo_metadata(false)
+        .compile("coolrt");
+}
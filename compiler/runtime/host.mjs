@@ -0,0 +1,122 @@
+// Host for the WebAssembly backend, implementing the `io` interface modules
+// generated from `runtime.wat` import.
+//
+// `run` works in browsers as well as in Node. Run directly with Node, this
+// executes a compiled module with the standard streams:
+//
+//     node host.mjs program.wasm
+
+/** Thrown by `io.exit` to unwind the program. */
+class Exit extends Error {
+  constructor(code) {
+    super(`exited with ${code}`);
+    this.code = code;
+  }
+}
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+/**
+ * Parses a leading, optionally negative, integer, yielding 0 when there is
+ * none or it does not fit in an `Int`.
+ */
+function parseInt32(line) {
+  const match = /^[ \t]*(-?)([0-9]*)/.exec(decoder.decode(line));
+  const value = BigInt(match[1] + (match[2] || "0"));
+
+  return value < -(2n ** 31n) || value >= 2n ** 31n ? 0 : Number(value);
+}
+
+/**
+ * Runs the `main` export of a compiled module, returning its exit code.
+ *
+ * `io.write` and `io.writeError` take bytes, `io.readLine` returns the next
+ * line as bytes without its terminator, or `null` at the end of the input.
+ */
+export async function run(module, io) {
+  let memory;
+  let line = new Uint8Array();
+  const bytes = (address, length) => new Uint8Array(memory.buffer, address, length);
+
+  const imports = {
+    io: {
+      out_string: (address, length) => io.write(bytes(address, length).slice()),
+      out_int: (value) => io.write(encoder.encode(String(value))),
+      in_string: () => {
+        line = io.readLine() ?? new Uint8Array();
+        return line.length;
+      },
+      read_line: (address) => bytes(address, line.length).set(line),
+      in_int: () => parseInt32(io.readLine() ?? new Uint8Array()),
+      error: (address, length) => io.writeError(bytes(address, length).slice()),
+      exit: (code) => {
+        throw new Exit(code);
+      },
+    },
+  };
+
+  const { instance } = await WebAssembly.instantiate(module, imports);
+  memory = instance.exports.memory;
+  try {
+    instance.exports.main();
+  } catch (error) {
+    if (error instanceof Exit) {
+      return error.code;
+    }
+    throw error;
+  }
+  return 0;
+}
+
+/** Reads lines from a file descriptor, synchronously. */
+function lineReader(fs, fd) {
+  let buffer = new Uint8Array();
+  let done = false;
+
+  return () => {
+    for (;;) {
+      const end = buffer.indexOf(10);
+      if (end >= 0 || (done && buffer.length > 0)) {
+        const length = end >= 0 ? end : buffer.length;
+        let line = buffer.subarray(0, length);
+        buffer = buffer.subarray(end >= 0 ? end + 1 : length);
+
+        if (line.length > 0 && line[line.length - 1] === 13) {
+          line = line.subarray(0, line.length - 1);
+        }
+        return line;
+      }
+      if (done) {
+        return null;
+      }
+
+      const chunk = new Uint8Array(4096);
+      const read = fs.readSync(fd, chunk);
+      done = read === 0;
+      const joined = new Uint8Array(buffer.length + read);
+      joined.set(buffer);
+      joined.set(chunk.subarray(0, read), buffer.length);
+      buffer = joined;
+    }
+  };
+}
+
+if (typeof process !== "undefined" && process.argv?.length > 2) {
+  const { pathToFileURL } = await import("node:url");
+
+  if (import.meta.url === pathToFileURL(process.argv[1]).href) {
+    const fs = await import("node:fs");
+    const write = (fd) => (chunk) => {
+      for (let written = 0; written < chunk.length; ) {
+        written += fs.writeSync(fd, chunk, written);
+      }
+    };
+
+    process.exitCode = await run(fs.readFileSync(process.argv[2]), {
+      write: write(1),
+      writeError: write(2),
+      readLine: lineReader(fs, 0),
+    });
+  }
+}
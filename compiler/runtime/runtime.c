@@ -0,0 +1,266 @@
+/*
+ * Runtime for the x86-64 backend.
+ *
+ * Objects start with their class tag, their size in 8-byte words and a
+ * pointer to their dispatch table, followed by their attributes. `Int` and
+ * `Bool` objects hold their value in the first attribute, `String` objects
+ * their length and a pointer to their null-terminated characters.
+ *
+ * Built-in methods are defined under the same names generated methods use,
+ * e.g. `IO.out_string`, and follow the System V calling convention with
+ * `self` as the first argument.
+ */
+
+#define _POSIX_C_SOURCE 200809L
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/types.h>
+
+typedef struct Object {
+    int64_t tag;
+    int64_t size;
+    void **dispatch;
+} Object;
+
+typedef struct Int {
+    Object header;
+    int32_t value;
+} Int;
+
+typedef struct Bool {
+    Object header;
+    int64_t value;
+} Bool;
+
+typedef struct String {
+    Object header;
+    int64_t length;
+    char *chars;
+} String;
+
+/* Emitted by the code generator. */
+extern const int64_t _int_tag, _bool_tag, _string_tag;
+extern String *class_nameTab[];
+extern Object Main_protObj, Int_protObj, String_protObj;
+extern Object bool_const0, bool_const1;
+
+Object *Main_init(Object *self);
+Object *Main_main(Object *self) __asm__("Main.main");
+
+Object *Object_abort(Object *self) __asm__("Object.abort");
+Object *Object_type_name(Object *self) __asm__("Object.type_name");
+Object *Object_copy(Object *self) __asm__("Object.copy");
+Object *IO_out_string(Object *self, String *value) __asm__("IO.out_string");
+Object *IO_out_int(Object *self, Int *value) __asm__("IO.out_int");
+Object *IO_in_string(Object *self) __asm__("IO.in_string");
+Object *IO_in_int(Object *self) __asm__("IO.in_int");
+Object *String_length(String *self) __asm__("String.length");
+Object *String_concat(String *self, String *other) __asm__("String.concat");
+Object *String_substr(String *self, Int *start, Int *length) __asm__("String.substr");
+
+static _Noreturn void fail(void) {
+    fflush(stdout);
+    exit(1);
+}
+
+static Object *allocate(const Object *prototype) {
+    size_t bytes = (size_t)prototype->size * sizeof(int64_t);
+    Object *object = malloc(bytes);
+
+    if (object == NULL) {
+        fflush(stdout);
+        fputs("Out of memory.\n", stderr);
+        fail();
+    }
+    memcpy(object, prototype, bytes);
+
+    return object;
+}
+
+static Object *new_int(int32_t value) {
+    Int *object = (Int *)allocate(&Int_protObj);
+    object->value = value;
+
+    return &object->header;
+}
+
+/* Takes ownership of `chars`, which must be null-terminated. */
+static Object *new_string(char *chars, int64_t length) {
+    String *object = (String *)allocate(&String_protObj);
+    object->length = length;
+    object->chars = chars;
+
+    return &object->header;
+}
+
+/* Reads a line without its terminator, or NULL at the end of the input. */
+static char *read_line(int64_t *length) {
+    char *line = NULL;
+    size_t capacity = 0;
+
+    fflush(stdout);
+    ssize_t read = getline(&line, &capacity, stdin);
+    if (read < 0) {
+        free(line);
+        *length = 0;
+        return NULL;
+    }
+    if (read > 0 && line[read - 1] == '\n') {
+        line[--read] = '\0';
+    }
+    if (read > 0 && line[read - 1] == '\r') {
+        line[--read] = '\0';
+    }
+    *length = read;
+
+    return line;
+}
+
+Object *Object_abort(Object *self) {
+    fflush(stdout);
+    fprintf(stderr, "Abort called from class %s\n", class_nameTab[self->tag]->chars);
+    fail();
+}
+
+Object *Object_type_name(Object *self) {
+    return &class_nameTab[self->tag]->header;
+}
+
+Object *Object_copy(Object *self) {
+    return allocate(self);
+}
+
+Object *IO_out_string(Object *self, String *value) {
+    fwrite(value->chars, 1, (size_t)value->length, stdout);
+    return self;
+}
+
+Object *IO_out_int(Object *self, Int *value) {
+    printf("%d", value->value);
+    return self;
+}
+
+Object *IO_in_string(Object *self) {
+    (void)self;
+    int64_t length;
+    char *line = read_line(&length);
+
+    if (line == NULL) {
+        line = calloc(1, 1);
+    }
+    return new_string(line, length);
+}
+
+/* Parses a leading, optionally negative, integer, yielding 0 when there is
+ * none or it does not fit in an `Int`. */
+Object *IO_in_int(Object *self) {
+    (void)self;
+    int64_t length;
+    char *line = read_line(&length);
+    int64_t value = 0;
+
+    if (line != NULL) {
+        const char *digits = line;
+        while (*digits == ' ' || *digits == '\t') {
+            digits++;
+        }
+        int negative = *digits == '-';
+        digits += negative;
+
+        for (; *digits >= '0' && *digits <= '9'; digits++) {
+            value = value * 10 + (*digits - '0');
+            if (value > (int64_t)INT32_MAX + negative) {
+                value = 0;
+                break;
+            }
+        }
+        value = negative ? -value : value;
+        free(line);
+    }
+    return new_int((int32_t)value);
+}
+
+Object *String_length(String *self) {
+    return new_int((int32_t)self->length);
+}
+
+Object *String_concat(String *self, String *other) {
+    int64_t length = self->length + other->length;
+    char *chars = malloc((size_t)length + 1);
+
+    memcpy(chars, self->chars, (size_t)self->length);
+    memcpy(chars + self->length, other->chars, (size_t)other->length + 1);
+
+    return new_string(chars, length);
+}
+
+Object *String_substr(String *self, Int *start, Int *length) {
+    int64_t from = start->value, count = length->value;
+
+    if (from < 0 || count < 0 || from + count > self->length) {
+        fflush(stdout);
+        fputs("Substring out of range.\n", stderr);
+        fail();
+    }
+    char *chars = malloc((size_t)count + 1);
+    memcpy(chars, self->chars + from, (size_t)count);
+    chars[count] = '\0';
+
+    return new_string(chars, count);
+}
+
+/* Implements `=` for distinct references: basic values are compared by
+ * value, every other object by identity. */
+Object *cool_equals(Object *left, Object *right) {
+    if (left == NULL || right == NULL || left->tag != right->tag) {
+        return &bool_const0;
+    }
+
+    int equal = 0;
+    if (left->tag == _int_tag) {
+        equal = ((Int *)left)->value == ((Int *)right)->value;
+    } else if (left->tag == _bool_tag) {
+        equal = ((Bool *)left)->value == ((Bool *)right)->value;
+    } else if (left->tag == _string_tag) {
+        String *a = (String *)left, *b = (String *)right;
+        equal = a->length == b->length && memcmp(a->chars, b->chars, (size_t)a->length) == 0;
+    }
+    return equal ? &bool_const1 : &bool_const0;
+}
+
+void cool_dispatch_abort(const char *file, int64_t line) {
+    fflush(stdout);
+    fprintf(stderr, "%s:%ld: Dispatch to void.\n", file, (long)line);
+    fail();
+}
+
+void cool_case_abort(Object *value) {
+    fflush(stdout);
+    fprintf(stderr, "No match in case statement for Class %s\n",
+            class_nameTab[value->tag]->chars);
+    fail();
+}
+
+void cool_case_abort2(const char *file, int64_t line) {
+    fflush(stdout);
+    fprintf(stderr, "%s:%ld: Match on void in case statement.\n", file, (long)line);
+    fail();
+}
+
+void cool_division_by_zero(const char *file, int64_t line) {
+    fflush(stdout);
+    fprintf(stderr, "%s:%ld: Division by zero.\n", file, (long)line);
+    fail();
+}
+
+int main(void) {
+    Object *main = allocate(&Main_protObj);
+
+    Main_main(Main_init(main));
+    fflush(stdout);
+
+    return 0;
+}
@@ -0,0 +1,226 @@
+  ;; Runtime for the WebAssembly backend, spliced at the start of the modules
+  ;; it generates.
+  ;;
+  ;; Objects start with their class tag, their size in 4-byte words and the
+  ;; index of their dispatch table in the function table, followed by their
+  ;; attributes. `Int` and `Bool` objects hold their value in the first
+  ;; attribute, `String` objects their length and the address of their
+  ;; characters. Objects are bump-allocated and never freed.
+  ;;
+  ;; Built-in methods are defined under the same names generated methods use,
+  ;; e.g. `$IO.out_string`, and take `self` as their first argument.
+
+  ;; Interface implemented by the host, see `host.mjs`.
+  (import "io" "out_string" (func $host_out_string (param i32 i32)))
+  (import "io" "out_int" (func $host_out_int (param i32)))
+  ;; Reads a line without its terminator and returns its length in bytes, 0
+  ;; at the end of the input.
+  (import "io" "in_string" (func $host_in_string (result i32)))
+  ;; Copies the line read by `in_string` to the given address.
+  (import "io" "read_line" (func $host_read_line (param i32)))
+  (import "io" "in_int" (func $host_in_int (result i32)))
+  (import "io" "error" (func $host_error (param i32 i32)))
+  ;; Stops the program with the given exit code.
+  (import "io" "exit" (func $host_exit (param i32)))
+
+  ;; Messages of the runtime, below the data of the program.
+  (data (i32.const 16) "Abort called from class ")
+  (data (i32.const 40) "\n")
+  (data (i32.const 44) "No match in case statement for Class ")
+  (data (i32.const 84) "Substring out of range.\n")
+  (data (i32.const 108) "Out of memory.\n")
+
+  (func (export "main")
+    (drop
+      (call $Main.main
+        (call $Main_init (call $Object.copy (global.get $Main_protObj))))))
+
+  (func $alloc (param $bytes i32) (result i32)
+    (local $object i32)
+    (local $limit i32)
+    (local.set $object (global.get $heap))
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $object) (local.get $bytes)) (i32.const 3))
+        (i32.const -4)))
+
+    (local.set $limit (i32.shl (memory.size) (i32.const 16)))
+    (if (i32.gt_u (global.get $heap) (local.get $limit))
+      (then
+        (if (i32.eq
+              (memory.grow
+                (i32.add
+                  (i32.shr_u
+                    (i32.sub (global.get $heap) (local.get $limit))
+                    (i32.const 16))
+                  (i32.const 1)))
+              (i32.const -1))
+          (then (call $fail (i32.const 108) (i32.const 15))))))
+    (local.get $object))
+
+  ;; Reports `length` bytes at `message` as an error and stops the program.
+  (func $fail (param $message i32) (param $length i32)
+    (call $host_error (local.get $message) (local.get $length))
+    (call $host_exit (i32.const 1))
+    unreachable)
+
+  ;; Reports the `String` object `message` as an error and stops the program.
+  (func $runtime_error (param $message i32)
+    (call $fail
+      (i32.load offset=16 (local.get $message))
+      (i32.load offset=12 (local.get $message))))
+
+  (func $error_string (param $string i32)
+    (call $host_error
+      (i32.load offset=16 (local.get $string))
+      (i32.load offset=12 (local.get $string))))
+
+  (func $class_name (param $object i32) (result i32)
+    (i32.load
+      (i32.add
+        (global.get $class_nameTab)
+        (i32.shl (i32.load (local.get $object)) (i32.const 2)))))
+
+  (func $new_int (param $value i32) (result i32)
+    (local $object i32)
+    (local.set $object (call $Object.copy (global.get $Int_protObj)))
+    (i32.store offset=12 (local.get $object) (local.get $value))
+    (local.get $object))
+
+  (func $new_string (param $chars i32) (param $length i32) (result i32)
+    (local $object i32)
+    (local.set $object (call $Object.copy (global.get $String_protObj)))
+    (i32.store offset=12 (local.get $object) (local.get $length))
+    (i32.store offset=16 (local.get $object) (local.get $chars))
+    (local.get $object))
+
+  (func $Object.abort (param $self i32) (result i32)
+    (call $host_error (i32.const 16) (i32.const 24))
+    (call $error_string (call $class_name (local.get $self)))
+    (call $fail (i32.const 40) (i32.const 1))
+    unreachable)
+
+  (func $Object.type_name (param $self i32) (result i32)
+    (call $class_name (local.get $self)))
+
+  (func $Object.copy (param $self i32) (result i32)
+    (local $bytes i32)
+    (local $copy i32)
+    (local.set $bytes (i32.shl (i32.load offset=4 (local.get $self)) (i32.const 2)))
+    (local.set $copy (call $alloc (local.get $bytes)))
+    (memory.copy (local.get $copy) (local.get $self) (local.get $bytes))
+    (local.get $copy))
+
+  (func $IO.out_string (param $self i32) (param $value i32) (result i32)
+    (call $host_out_string
+      (i32.load offset=16 (local.get $value))
+      (i32.load offset=12 (local.get $value)))
+    (local.get $self))
+
+  (func $IO.out_int (param $self i32) (param $value i32) (result i32)
+    (call $host_out_int (i32.load offset=12 (local.get $value)))
+    (local.get $self))
+
+  (func $IO.in_string (param $self i32) (result i32)
+    (local $length i32)
+    (local $chars i32)
+    (local.set $length (call $host_in_string))
+    (local.set $chars (call $alloc (local.get $length)))
+    (call $host_read_line (local.get $chars))
+    (call $new_string (local.get $chars) (local.get $length)))
+
+  (func $IO.in_int (param $self i32) (result i32)
+    (call $new_int (call $host_in_int)))
+
+  (func $String.length (param $self i32) (result i32)
+    (call $new_int (i32.load offset=12 (local.get $self))))
+
+  (func $String.concat (param $self i32) (param $other i32) (result i32)
+    (local $length i32)
+    (local $chars i32)
+    (local.set $length
+      (i32.add
+        (i32.load offset=12 (local.get $self))
+        (i32.load offset=12 (local.get $other))))
+    (local.set $chars (call $alloc (local.get $length)))
+    (memory.copy
+      (local.get $chars)
+      (i32.load offset=16 (local.get $self))
+      (i32.load offset=12 (local.get $self)))
+    (memory.copy
+      (i32.add (local.get $chars) (i32.load offset=12 (local.get $self)))
+      (i32.load offset=16 (local.get $other))
+      (i32.load offset=12 (local.get $other)))
+    (call $new_string (local.get $chars) (local.get $length)))
+
+  (func $String.substr (param $self i32) (param $start i32) (param $length i32) (result i32)
+    (local $from i32)
+    (local $count i32)
+    (local $chars i32)
+    (local.set $from (i32.load offset=12 (local.get $start)))
+    (local.set $count (i32.load offset=12 (local.get $length)))
+    (if (i32.or
+          (i32.or
+            (i32.lt_s (local.get $from) (i32.const 0))
+            (i32.lt_s (local.get $count) (i32.const 0)))
+          (i32.gt_u
+            (i32.add (local.get $from) (local.get $count))
+            (i32.load offset=12 (local.get $self))))
+      (then (call $fail (i32.const 84) (i32.const 24))))
+    (local.set $chars (call $alloc (local.get $count)))
+    (memory.copy
+      (local.get $chars)
+      (i32.add (i32.load offset=16 (local.get $self)) (local.get $from))
+      (local.get $count))
+    (call $new_string (local.get $chars) (local.get $count)))
+
+  ;; Implements `=`: basic values are compared by value, every other object
+  ;; by identity.
+  (func $equals (param $left i32) (param $right i32) (result i32)
+    (local $tag i32)
+    (local $length i32)
+    (local $idx i32)
+    (if (i32.eq (local.get $left) (local.get $right))
+      (then (return (global.get $bool_const1))))
+    (if (i32.or
+          (i32.or (i32.eqz (local.get $left)) (i32.eqz (local.get $right)))
+          (i32.ne (i32.load (local.get $left)) (i32.load (local.get $right))))
+      (then (return (global.get $bool_const0))))
+
+    (local.set $tag (i32.load (local.get $left)))
+    (if (i32.or
+          (i32.eq (local.get $tag) (global.get $_int_tag))
+          (i32.eq (local.get $tag) (global.get $_bool_tag)))
+      (then
+        (return
+          (select
+            (global.get $bool_const1)
+            (global.get $bool_const0)
+            (i32.eq
+              (i32.load offset=12 (local.get $left))
+              (i32.load offset=12 (local.get $right)))))))
+    (if (i32.ne (local.get $tag) (global.get $_string_tag))
+      (then (return (global.get $bool_const0))))
+
+    (local.set $length (i32.load offset=12 (local.get $left)))
+    (if (i32.ne (local.get $length) (i32.load offset=12 (local.get $right)))
+      (then (return (global.get $bool_const0))))
+    (block $differ
+      (loop $next
+        (if (i32.lt_u (local.get $idx) (local.get $length))
+          (then
+            (br_if $differ
+              (i32.ne
+                (i32.load8_u
+                  (i32.add (i32.load offset=16 (local.get $left)) (local.get $idx)))
+                (i32.load8_u
+                  (i32.add (i32.load offset=16 (local.get $right)) (local.get $idx)))))
+            (local.set $idx (i32.add (local.get $idx) (i32.const 1)))
+            (br $next))))
+      (return (global.get $bool_const1)))
+    (global.get $bool_const0))
+
+  (func $case_abort (param $value i32)
+    (call $host_error (i32.const 44) (i32.const 37))
+    (call $error_string (call $class_name (local.get $value)))
+    (call $fail (i32.const 40) (i32.const 1)))
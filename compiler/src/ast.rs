@@ -1,9 +1,20 @@
-use crate::{ast::macros::ast_node, util::interner::InternedIdx};
+use sexp_derive::Sexp;
+
+use crate::{
+    ast::macros::ast_node,
+    util::{interner::InternedIdx, span::Span},
+};
 
 pub mod bindings;
 pub mod converter;
+pub mod lex;
+pub mod pretty;
+pub mod reference;
+pub mod sexp;
+pub mod visit;
 
 mod macros;
+mod syntax;
 
 ast_node! {
     #[derive(Debug)]
@@ -17,6 +28,7 @@ ast_node! {
         Params(Params),
         Param(Param),
         Expr(Expr),
+        Arguments(Arguments),
         Qualifier(Qualifier),
         LetBindings(LetBindings),
         LetBinding(LetBinding),
@@ -29,53 +41,95 @@ ast_node! {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Sexp)]
+#[sexp(rename = "program")]
 pub struct Program {
     pub classes: Vec<Class>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(rename = "class")]
 pub struct Class {
     pub name: Type,
     pub inherits: Option<Type>,
     pub features: Features,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
 pub struct Features(pub Vec<Feature>);
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
 pub enum Feature {
+    #[sexp(transparent)]
     Attribute(Attribute),
+    #[sexp(transparent)]
     Method(Method),
 }
 
-#[derive(Debug)]
+impl Feature {
+    pub fn span(&self) -> Span {
+        match self {
+            Feature::Attribute(attribute) => attribute.span,
+            Feature::Method(method) => method.span,
+        }
+    }
+}
+
+#[derive(Debug, Sexp)]
+#[sexp(rename = "attribute")]
 pub struct Attribute {
     pub name: Ident,
     pub ty: Type,
     pub initializer: Option<Expr>,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(rename = "method")]
 pub struct Method {
     pub name: Ident,
     pub params: Params,
     pub return_ty: Type,
     pub body: Expr,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
 pub struct Params(pub Vec<Param>);
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(rename = "param")]
 pub struct Param {
     pub name: Ident,
     pub ty: Type,
+    #[sexp(skip)]
+    pub span: Span,
+}
+
+/// Identifies an expression within its program, so that later passes can
+/// attach information to it without owning the tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(pub u32);
+
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
+pub struct Expr {
+    #[sexp(skip)]
+    pub id: ExprId,
+    pub kind: ExprKind,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
-pub enum Expr {
+#[derive(Debug, Sexp)]
+#[sexp(rename_all = "snake_case")]
+pub enum ExprKind {
     Assignment {
         name: Ident,
         right: Box<Expr>,
@@ -122,46 +176,64 @@ pub enum Expr {
     },
     Ident(Ident),
     String(Box<str>),
-    Int(u64),
+    /// A literal up to `i32::MAX`, the largest value an `Int` holds.
+    Int(i32),
     Bool(bool),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
+pub struct Arguments(pub Vec<Expr>);
+
+#[derive(Debug, Sexp)]
+#[sexp(rename = "qualifier")]
 pub struct Qualifier {
-    value: Box<Expr>,
-    parent: Option<Type>,
+    pub value: Box<Expr>,
+    pub parent: Option<Type>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
 pub struct LetBindings(pub Vec<LetBinding>);
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(rename = "let_binding")]
 pub struct LetBinding {
-    name: Ident,
-    ty: Type,
-    right: Option<Expr>,
+    pub name: Ident,
+    pub ty: Type,
+    pub right: Option<Expr>,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(rename = "case_arm")]
 pub struct CaseArm {
-    pat: CasePattern,
-    value: Box<Expr>,
+    pub pat: CasePattern,
+    pub value: Box<Expr>,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(rename = "case_pattern")]
 pub struct CasePattern {
-    name: Ident,
-    ty: Type,
+    pub name: Ident,
+    pub ty: Type,
+    #[sexp(skip)]
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Sexp)]
+#[sexp(rename_all = "snake_case")]
 pub enum UnaryOp {
     IsVoid,
     Complement,
     Not,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Sexp)]
+#[sexp(rename_all = "snake_case")]
 pub enum BinaryOp {
     Add,
     Sub,
@@ -172,10 +244,14 @@ pub enum BinaryOp {
     Eq,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
 pub struct Type(pub Ident);
 
-#[derive(Debug)]
+#[derive(Debug, Sexp)]
+#[sexp(transparent)]
 pub struct Ident {
     pub name: InternedIdx,
+    #[sexp(skip)]
+    pub span: Span,
 }
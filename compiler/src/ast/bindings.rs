@@ -1,4 +1,7 @@
-use crate::{language::Cool, util::span::Span};
+use crate::{
+    language::Cool,
+    util::span::{FileId, Span},
+};
 
 #[derive(Clone, Debug)]
 pub struct Tree(tree_sitter::Tree);
@@ -13,7 +16,7 @@ impl Tree {
         Self(parser.parse(code, None).unwrap())
     }
 
-    pub fn get_root(&self) -> Node {
+    pub fn get_root(&self) -> Node<'_> {
         Node(self.0.root_node())
     }
 }
@@ -21,6 +24,7 @@ impl Tree {
 #[derive(Clone)]
 pub struct Cursor<'a>(tree_sitter::TreeCursor<'a>);
 
+#[allow(dead_code)]
 impl<'a> Cursor<'a> {
     pub(crate) fn reset(&mut self, node: &Node<'a>) {
         self.0.reset(node.0);
@@ -42,6 +46,7 @@ impl<'a> Cursor<'a> {
 #[derive(Clone, Copy, Debug)]
 pub struct Node<'a>(tree_sitter::Node<'a>);
 
+#[allow(dead_code)]
 impl<'a> Node<'a> {
     pub(crate) fn has_error(&self) -> bool {
         self.0.has_error()
@@ -75,11 +80,8 @@ impl<'a> Node<'a> {
         self.0.end_byte()
     }
 
-    pub(crate) fn span(&self) -> Span {
-        let (srow, scol) = self.start_position();
-        let (erow, ecol) = self.end_position();
-
-        (srow + 1, scol + 1, erow + 1, ecol + 1)
+    pub(crate) fn span(&self, file: FileId) -> Span {
+        Span::new(file, self.start_byte(), self.end_byte())
     }
 
     pub(crate) fn start_position(&self) -> (usize, usize) {
@@ -136,7 +138,7 @@ impl<'a> Node<'a> {
         self.0.child_count()
     }
 
-    pub(crate) fn child_by_field_name(&self, name: &str) -> Option<Node> {
+    pub(crate) fn child_by_field_name(&self, name: &str) -> Option<Node<'a>> {
         self.0.child_by_field_name(name).map(Node)
     }
 
@@ -158,7 +160,6 @@ impl<'a> Node<'a> {
         Cursor(self.0.walk())
     }
 
-    #[allow(dead_code)]
     pub(crate) fn get_parent(&self, level: usize) -> Option<Node<'a>> {
         let mut level = level;
         let mut node = *self;
@@ -177,15 +178,15 @@ impl<'a> Node<'a> {
     pub(crate) fn has_ancestors(&self, typ: fn(&Node) -> bool, typs: fn(&Node) -> bool) -> bool {
         let mut res = false;
         let mut node = *self;
-        if let Some(parent) = node.parent() {
-            if typ(&parent) {
-                node = parent;
-            }
+        if let Some(parent) = node.parent()
+            && typ(&parent)
+        {
+            node = parent;
         }
-        if let Some(parent) = node.parent() {
-            if typs(&parent) {
-                res = true;
-            }
+        if let Some(parent) = node.parent()
+            && typs(&parent)
+        {
+            res = true;
         }
         res
     }
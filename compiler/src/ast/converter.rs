@@ -1,49 +1,106 @@
 use std::collections::VecDeque;
 
 use crate::{
+    ast::syntax,
     ast::{
         self, AstNode,
         bindings::{Cursor, Node, Tree},
     },
     language::Cool,
-    util::{interner::Interner, span::Spanned},
+    util::{
+        interner::Interner,
+        source::SourceMap,
+        span::{FileId, Spanned},
+    },
 };
 
 type Result<T, E = ()> = std::result::Result<T, E>;
 
 pub type ConversionResult<T> = Result<T, (T, Vec<Spanned<Error>>)>;
 
+/// Converts a program made of a single file.
 pub fn convert(src: &[u8], tree: &Tree, interner: &mut Interner) -> ConversionResult<ast::Program> {
-    let mut converter = Converter::new(src, tree, interner);
-    let program = converter.convert().unwrap_or_default();
+    let mut next_id = 0;
+    let (program, errors) = convert_file(FileId::default(), src, tree, interner, &mut next_id);
 
-    if converter.errors.is_empty() {
+    if errors.is_empty() {
         Ok(program)
     } else {
-        Err((program, converter.errors))
+        Err((program, errors))
+    }
+}
+
+/// Converts the files of a source map, which together form one program.
+pub fn convert_all(sources: &SourceMap, interner: &mut Interner) -> ConversionResult<ast::Program> {
+    let mut program = ast::Program::default();
+    let mut errors = Vec::new();
+    // Expression ids are unique across the files.
+    let mut next_id = 0;
+
+    for (file, source) in sources.files() {
+        let src = source.src.as_bytes();
+        let tree = Tree::new(src);
+        let (part, part_errors) = convert_file(file, src, &tree, interner, &mut next_id);
+
+        program.classes.extend(part.classes);
+        errors.extend(part_errors);
+    }
+
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        Err((program, errors))
+    }
+}
+
+fn convert_file(
+    file: FileId,
+    src: &[u8],
+    tree: &Tree,
+    interner: &mut Interner,
+    next_id: &mut u32,
+) -> (ast::Program, Vec<Spanned<Error>>) {
+    let syntax_errors = syntax::check(file, src, tree);
+
+    let mut converter = Converter::new(file, src, tree, interner, *next_id);
+    let program = converter.convert().unwrap_or_default();
+    *next_id = converter.next_id;
+
+    // Constructs the parser recovered are converted partially at best, and
+    // the errors that follow add nothing to those of the syntax.
+    if !syntax_errors.is_empty() {
+        (program, syntax_errors)
+    } else {
+        (program, converter.errors)
     }
 }
 
 struct Converter<'src, 'i> {
+    file: FileId,
     src: &'src [u8],
     interner: &'i mut Interner,
     cursor: Cursor<'src>,
     errors: Vec<Spanned<Error>>,
+    next_id: u32,
 }
 
 impl Converter<'_, '_> {
     fn new<'src, 'i>(
+        file: FileId,
         src: &'src [u8],
         tree: &'src Tree,
         interner: &'i mut Interner,
+        next_id: u32,
     ) -> Converter<'src, 'i> {
         let cursor = tree.get_root().cursor();
 
         Converter {
+            file,
             src,
             interner,
             cursor,
             errors: Vec::default(),
+            next_id,
         }
     }
 
@@ -91,25 +148,22 @@ impl Converter<'_, '_> {
     }
 
     fn convert_ident(&mut self, node: &Node) -> Option<ast::Ident> {
-        let name = extract::lexeme(self.src, node);
-        // If the tree-sitter parser is able to recover from erroneous text by
-        // inserting a missing token and then reducing, it will do so in the
-        // final tree, so long as it has the lowest error cost.
-        //
-        // The problem here is that a missing node doesn't have its own
-        // representation, such as `Cool::Error`, meaning that this redundant
-        // check must occur.
-        if name.is_empty() {
-            self.error(Error::InvalidSyntax);
+        // Identifiers the parser inserted to recover were reported by
+        // `syntax::check`.
+        if node.is_missing() {
             return None;
         }
+        let name = extract::lexeme(self.src, node);
         Some(ast::Ident {
             name: self.interner.intern(name),
+            span: node.span(self.file),
         })
     }
 
     fn convert_binop(&mut self, node: &Node) -> Option<ast::BinaryOp> {
-        let rule = node.child(1)?.rule();
+        // The left operand may be followed by an `ERROR`, such as the rest
+        // of a string ending with an escaped quote.
+        let rule = node.child_by_field_name("operator")?.rule();
 
         match rule {
             Cool::Plus => Some(ast::BinaryOp::Add),
@@ -119,16 +173,20 @@ impl Converter<'_, '_> {
             Cool::Lt => Some(ast::BinaryOp::Lt),
             Cool::Lte => Some(ast::BinaryOp::Lte),
             Cool::Eq => Some(ast::BinaryOp::Eq),
-            _ => {
-                self.error(Error::InvalidSyntax);
-                None
-            }
+            // The grammar has no other operator.
+            _ => None,
         }
     }
 }
 
 impl Converter<'_, '_> {
     fn try_cast(&mut self, node: &Node, mut children: VecDeque<AstNode>) -> Result<AstNode> {
+        // Literals standing for a missing expression were reported by
+        // `syntax::check`, and have no value to extract.
+        if node.has_error() && node.rule().is_expr() && node.child_count() <= 1 {
+            return Err(());
+        }
+
         match node.rule() {
             Cool::SourceFile => {
                 let program = ast::Program {
@@ -142,6 +200,7 @@ impl Converter<'_, '_> {
                     name: self.consume(&mut children)?,
                     inherits: self.consume_opt(&mut children),
                     features: self.consume(&mut children)?,
+                    span: node.span(self.file),
                 };
 
                 Ok(class.into())
@@ -156,6 +215,7 @@ impl Converter<'_, '_> {
                     name: self.consume(&mut children)?,
                     ty: self.consume(&mut children)?,
                     initializer: self.consume_opt(&mut children),
+                    span: node.span(self.file),
                 };
 
                 Ok(ast::Feature::Attribute(attribute).into())
@@ -166,6 +226,7 @@ impl Converter<'_, '_> {
                     params: self.consume(&mut children)?,
                     return_ty: self.consume(&mut children)?,
                     body: self.consume(&mut children)?,
+                    span: node.span(self.file),
                 };
 
                 Ok(ast::Feature::Method(method).into())
@@ -179,58 +240,68 @@ impl Converter<'_, '_> {
                 let param = ast::Param {
                     name: self.consume(&mut children)?,
                     ty: self.consume(&mut children)?,
+                    span: node.span(self.file),
                 };
 
                 Ok(param.into())
             }
             Cool::AssignmentExpression => {
-                let expr = ast::Expr::Assignment {
+                let kind = ast::ExprKind::Assignment {
                     name: self.consume(&mut children)?,
                     right: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::DispatchExpression => {
-                let expr = ast::Expr::Dispatch {
-                    qualifier: self.consume_opt(&mut children),
+                let qualifier = self.consume_opt(&mut children).map(|value| ast::Qualifier {
+                    value: Box::new(value),
+                    parent: self.consume_opt(&mut children),
+                });
+                let kind = ast::ExprKind::Dispatch {
+                    qualifier,
                     method: self.consume(&mut children)?,
-                    args: self.consume_all(children)?,
+                    args: self.consume::<ast::Arguments>(&mut children)?.0,
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
+            }
+            Cool::Arguments => {
+                let args = ast::Arguments(self.consume_all(children)?);
+
+                Ok(args.into())
             }
             Cool::IfExpression => {
-                let expr = ast::Expr::Conditional {
+                let kind = ast::ExprKind::Conditional {
                     condition: Box::new(self.consume(&mut children)?),
                     consequence: Box::new(self.consume(&mut children)?),
                     alternative: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::WhileExpression => {
-                let expr = ast::Expr::Repeat {
+                let kind = ast::ExprKind::Repeat {
                     condition: Box::new(self.consume(&mut children)?),
                     body: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::Block => {
-                let expr = ast::Expr::Block {
+                let kind = ast::ExprKind::Block {
                     body: self.consume_non_zero(children)?,
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::LetExpression => {
-                let expr = ast::Expr::Let {
+                let kind = ast::ExprKind::Let {
                     bindings: self.consume(&mut children)?,
                     body: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::Bindings => {
                 let bindings = ast::LetBindings(self.consume_non_zero(children)?);
@@ -242,22 +313,24 @@ impl Converter<'_, '_> {
                     name: self.consume(&mut children)?,
                     ty: self.consume(&mut children)?,
                     right: self.consume_opt(&mut children),
+                    span: node.span(self.file),
                 };
 
                 Ok(binding.into())
             }
             Cool::CaseExpression => {
-                let expr = ast::Expr::Case {
+                let kind = ast::ExprKind::Case {
                     value: Box::new(self.consume(&mut children)?),
                     body: self.consume_all(children)?,
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::CaseArm => {
                 let arm = ast::CaseArm {
                     pat: self.consume(&mut children)?,
                     value: Box::new(self.consume(&mut children)?),
+                    span: node.span(self.file),
                 };
 
                 Ok(arm.into())
@@ -266,73 +339,82 @@ impl Converter<'_, '_> {
                 let pat = ast::CasePattern {
                     name: self.consume(&mut children)?,
                     ty: self.consume(&mut children)?,
+                    span: node.span(self.file),
                 };
 
                 Ok(pat.into())
             }
             Cool::NewExpression => {
-                let expr = ast::Expr::New {
+                let kind = ast::ExprKind::New {
                     ty: self.consume(&mut children)?,
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::IsvoidExpression => {
-                let expr = ast::Expr::Unary {
+                let kind = ast::ExprKind::Unary {
                     op: ast::UnaryOp::IsVoid,
                     right: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::UnaryExpression => {
-                let expr = ast::Expr::Unary {
+                let kind = ast::ExprKind::Unary {
                     op: ast::UnaryOp::Complement,
                     right: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::NotExpression => {
-                let expr = ast::Expr::Unary {
+                let kind = ast::ExprKind::Unary {
                     op: ast::UnaryOp::Not,
                     right: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::BinaryExpression => {
                 let op = self.convert_binop(node).ok_or(())?;
 
-                let expr = ast::Expr::Binary {
+                let kind = ast::ExprKind::Binary {
                     op,
                     left: Box::new(self.consume(&mut children)?),
                     right: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::ParenthesizedExpression => {
-                let expr = ast::Expr::Paren {
+                let kind = ast::ExprKind::Paren {
                     value: Box::new(self.consume(&mut children)?),
                 };
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::StringLiteral => {
-                let expr = ast::Expr::String(extract::string(self.src, node));
+                // Strings that cannot be decoded are reported by
+                // `syntax::check`.
+                let value = extract::string(self.src, node).unwrap_or_default();
+                let mut expr = self.expr(node, ast::ExprKind::String(value));
+                if let Some(end) = extract::string_end(self.src, node) {
+                    expr.span.end = end as u32;
+                }
 
                 Ok(expr.into())
             }
             Cool::IntegerLiteral => {
-                let expr = ast::Expr::Int(extract::trivial(self.src, node));
+                // Literals out of range are reported by `syntax::check`.
+                let value = extract::int(self.src, node).unwrap_or_default();
+                let kind = ast::ExprKind::Int(value);
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::BooleanLiteral => {
-                let expr = ast::Expr::Bool(extract::trivial(self.src, node));
+                let kind = ast::ExprKind::Bool(extract::trivial(self.src, node));
 
-                Ok(expr.into())
+                Ok(self.expr(node, kind).into())
             }
             Cool::TypeIdentifier
             | Cool::Bool
@@ -346,28 +428,13 @@ impl Converter<'_, '_> {
                 Ok(ty.into())
             }
             Cool::Identifier | Cool::FieldIdentifier | Cool::SelfIdentifier => {
-                let to_ast = |ident| {
-                    if node.parent().unwrap().rule().is_expr() {
-                        ast::Expr::Ident(ident).into()
-                    } else {
-                        ident.into()
-                    }
-                };
-                self.convert_ident(node)
-                    .map(|ident| {
-                        if let Some(parent) = node.parent() {
-                            if parent.rule().is_expr() {
-                                ast::Expr::Ident(ident).into()
-                            } else {
-                                ident.into()
-                            }
-                        }
-                    })
-                    .ok_or(())
-            }
-            Cool::Error => {
-                self.error(Error::InvalidSyntax);
-                Err(())
+                let ident = self.convert_ident(node).ok_or(())?;
+
+                if is_declared_name(node) {
+                    Ok(ident.into())
+                } else {
+                    Ok(self.expr(node, ast::ExprKind::Ident(ident)).into())
+                }
             }
             _ => Err(()),
         }
@@ -391,7 +458,7 @@ impl Converter<'_, '_> {
         match T::try_from(child) {
             Ok(value) => Some(value),
             Err(Error::Unexpected(other)) => {
-                children.push_back(other);
+                children.push_front(*other);
                 None
             }
             Err(_) => unreachable!(),
@@ -422,19 +489,50 @@ impl Converter<'_, '_> {
         })
     }
 
+    fn expr(&mut self, node: &Node, kind: ast::ExprKind) -> ast::Expr {
+        let id = ast::ExprId(self.next_id);
+        self.next_id += 1;
+
+        ast::Expr {
+            id,
+            kind,
+            span: node.span(self.file),
+        }
+    }
+
     fn error(&mut self, error: Error) {
         self.errors.push(Spanned {
             inner: error,
-            span: self.cursor.node().span(),
+            span: self.cursor.node().span(self.file),
         });
     }
 }
 
+/// Whether an identifier names the construct it belongs to, such as a
+/// declaration, the target of an assignment or a dispatched method, instead of
+/// referring to a value.
+fn is_declared_name(node: &Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+
+    let field = match parent.rule() {
+        Cool::AssignmentExpression => "left",
+        Cool::DispatchExpression => "method",
+        _ => "name",
+    };
+
+    parent
+        .child_by_field_name(field)
+        .is_some_and(|child| child.id() == node.id())
+}
+
 struct ConversionState<'a> {
     node_stack: Vec<Node<'a>>,
     child_stack: Vec<VecDeque<AstNode>>,
 }
 
+#[allow(dead_code)]
 impl<'a> ConversionState<'a> {
     fn new() -> ConversionState<'a> {
         ConversionState {
@@ -476,8 +574,12 @@ impl<'a> ConversionState<'a> {
     }
 }
 
-mod extract {
-    use crate::ast::bindings::Node;
+pub(super) mod extract {
+    use super::Error;
+    use crate::ast::{
+        bindings::Node,
+        lex::{self, MAX_STRING_LEN},
+    };
 
     pub fn trivial<T>(src: &[u8], node: &Node) -> T
     where
@@ -487,13 +589,59 @@ mod extract {
         lexeme(src, node).parse::<T>().unwrap()
     }
 
-    pub fn string(src: &[u8], node: &Node) -> Box<str> {
-        let content = node.child(1);
+    /// Parses an integer literal, which the grammar makes of digits only, so
+    /// that it is never negative.
+    pub fn int(src: &[u8], node: &Node) -> Result<i32, Error> {
+        lexeme(src, node)
+            .parse()
+            .map_err(|_| Error::IntegerTooLarge)
+    }
+
+    /// The end of a string literal, past its closing quote, or `None` if
+    /// the line ends before the string does.
+    ///
+    /// The source is scanned as the reference lexer does, as the grammar
+    /// ends a string at the first quote, escaped or not: `"q\"z"` is parsed
+    /// as `"q\"` followed by an `ERROR` holding `z"`, the rest of the
+    /// string.
+    pub fn string_end(src: &[u8], node: &Node) -> Option<usize> {
+        let mut pos = node.start_byte() + 1;
+        loop {
+            match src.get(pos)? {
+                b'\n' => return None,
+                b'"' => return Some(pos + 1),
+                b'\\' => pos += 2,
+                _ => pos += 1,
+            }
+        }
+    }
+
+    /// Decodes the text between the quotes of a string literal.
+    pub fn string(src: &[u8], node: &Node) -> Result<Box<str>, Error> {
+        let end = string_end(src, node).ok_or(Error::UnterminatedString)?;
+        let raw = &src[node.start_byte() + 1..end - 1];
+
+        let mut value = Vec::with_capacity(raw.len());
+        let mut bytes = raw.iter().copied();
+        while let Some(c) = bytes.next() {
+            match c {
+                0 => return Err(Error::NullInString { escaped: false }),
+                b'\\' => match bytes.next().map(lex::unescape) {
+                    Some(Some(c)) => value.push(c),
+                    Some(None) => return Err(Error::NullInString { escaped: true }),
+                    None => {}
+                },
+                c => value.push(c),
+            }
+        }
 
-        match content {
-            Some(c) => lexeme(src, &c).to_string().into_boxed_str(),
-            _ => Box::from(""),
+        // Like the reference lexer, the length is counted in bytes.
+        if value.len() > MAX_STRING_LEN {
+            return Err(Error::StringTooLong);
         }
+        // Escapes only stand for ASCII characters, so the value is as valid
+        // UTF-8 as the source.
+        Ok(String::from_utf8_lossy(&value).into())
     }
 
     pub fn lexeme<'a>(src: &'a [u8], node: &'a Node) -> &'a str {
@@ -503,7 +651,66 @@ mod extract {
 
 #[derive(Debug)]
 pub enum Error {
-    Unexpected(AstNode),
+    Unexpected(Box<AstNode>),
     EmptyConstruct,
-    InvalidSyntax,
+    /// A closing keyword or delimiter missing at the end of a construct.
+    Unclosed {
+        token: &'static str,
+        construct: &'static str,
+    },
+    /// A `;` missing after a class, a feature or an expression of a block.
+    MissingSemicolon(&'static str),
+    /// Any other token missing from a construct.
+    MissingToken {
+        token: &'static str,
+        construct: &'static str,
+    },
+    /// An expression, identifier or type missing, possibly after a token.
+    MissingNode {
+        expected: &'static str,
+        after: Option<Box<str>>,
+    },
+    /// Text the parser had to skip, starting with `token`.
+    UnexpectedToken {
+        token: Box<str>,
+        construct: &'static str,
+    },
+    /// An `if` without an `else` branch, which COOL requires.
+    MissingElse,
+    /// A class named with an object identifier.
+    LowercaseClassName(Box<str>),
+    /// A string that an unescaped newline or the end of the file ends
+    /// before its closing quote.
+    UnterminatedString,
+    /// A null character in a string, escaped with `\` or not.
+    NullInString {
+        escaped: bool,
+    },
+    /// A string longer than [`MAX_STRING_LEN`](super::lex::MAX_STRING_LEN)
+    /// bytes.
+    StringTooLong,
+    /// An integer literal above `i32::MAX`.
+    IntegerTooLarge,
+}
+
+impl Error {
+    /// Stable identifier of the kind of error, that `cool --explain`
+    /// describes at length.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::EmptyConstruct => "C0001",
+            Error::Unclosed { .. } => "C0002",
+            Error::MissingSemicolon(_) => "C0003",
+            Error::MissingToken { .. } => "C0004",
+            Error::MissingNode { .. } => "C0005",
+            Error::UnexpectedToken { .. } => "C0006",
+            Error::MissingElse => "C0007",
+            Error::LowercaseClassName(_) => "C0008",
+            Error::Unexpected(_) => "C0009",
+            Error::UnterminatedString => "C0010",
+            Error::NullInString { .. } => "C0011",
+            Error::StringTooLong => "C0012",
+            Error::IntegerTooLarge => "C0013",
+        }
+    }
 }
@@ -0,0 +1,429 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    ast::{
+        bindings::{Node, Tree},
+        reference::escape,
+    },
+    language::Cool,
+    util::source::SourceFile,
+};
+
+/// The longest string constant once its escapes are decoded, in bytes as
+/// the reference lexer counts it.
+pub const MAX_STRING_LEN: usize = 1024;
+
+const KEYWORDS: &[(&str, TokenKind)] = &[
+    ("class", TokenKind::Class),
+    ("else", TokenKind::Else),
+    ("fi", TokenKind::Fi),
+    ("if", TokenKind::If),
+    ("in", TokenKind::In),
+    ("inherits", TokenKind::Inherits),
+    ("isvoid", TokenKind::Isvoid),
+    ("let", TokenKind::Let),
+    ("loop", TokenKind::Loop),
+    ("pool", TokenKind::Pool),
+    ("then", TokenKind::Then),
+    ("while", TokenKind::While),
+    ("case", TokenKind::Case),
+    ("esac", TokenKind::Esac),
+    ("new", TokenKind::New),
+    ("of", TokenKind::Of),
+    ("not", TokenKind::Not),
+];
+
+/// A token, as the reference COOL lexer reports it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    /// Line of the end of the token, from 1.
+    pub line: usize,
+    pub kind: TokenKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Class,
+    Else,
+    Fi,
+    If,
+    In,
+    Inherits,
+    Isvoid,
+    Let,
+    Loop,
+    Pool,
+    Then,
+    While,
+    Case,
+    Esac,
+    New,
+    Of,
+    Not,
+    /// `<-`
+    Assign,
+    /// `<=`
+    Le,
+    /// `=>`
+    Darrow,
+    /// Any other operator or delimiter, a single character.
+    Char(u8),
+    TypeId(Box<str>),
+    ObjectId(Box<str>),
+    /// An integer as written, leading zeros and all.
+    Int(Box<str>),
+    Bool(bool),
+    /// A string constant with its escapes decoded.
+    Str(Box<[u8]>),
+    /// Text that is not a token, described by a message or the rejected
+    /// character itself.
+    Error(Box<[u8]>),
+}
+
+impl TokenKind {
+    fn name(&self) -> &'static str {
+        match self {
+            TokenKind::Class => "CLASS",
+            TokenKind::Else => "ELSE",
+            TokenKind::Fi => "FI",
+            TokenKind::If => "IF",
+            TokenKind::In => "IN",
+            TokenKind::Inherits => "INHERITS",
+            TokenKind::Isvoid => "ISVOID",
+            TokenKind::Let => "LET",
+            TokenKind::Loop => "LOOP",
+            TokenKind::Pool => "POOL",
+            TokenKind::Then => "THEN",
+            TokenKind::While => "WHILE",
+            TokenKind::Case => "CASE",
+            TokenKind::Esac => "ESAC",
+            TokenKind::New => "NEW",
+            TokenKind::Of => "OF",
+            TokenKind::Not => "NOT",
+            TokenKind::Assign => "ASSIGN",
+            TokenKind::Le => "LE",
+            TokenKind::Darrow => "DARROW",
+            TokenKind::Char(_) => "",
+            TokenKind::TypeId(_) => "TYPEID",
+            TokenKind::ObjectId(_) => "OBJECTID",
+            TokenKind::Int(_) => "INT_CONST",
+            TokenKind::Bool(_) => "BOOL_CONST",
+            TokenKind::Str(_) => "STR_CONST",
+            TokenKind::Error(_) => "ERROR",
+        }
+    }
+}
+
+/// Writes a token the way the reference lexer does, e.g. `#3 OBJECTID x`.
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.line, self.kind.name())?;
+        match &self.kind {
+            TokenKind::Char(c) => write!(f, "'{}'", *c as char),
+            TokenKind::TypeId(name) | TokenKind::ObjectId(name) | TokenKind::Int(name) => {
+                write!(f, " {}", name)
+            }
+            TokenKind::Bool(value) => write!(f, " {}", value),
+            TokenKind::Str(value) => write!(f, " \"{}\"", escape(value)),
+            TokenKind::Error(message) => write!(f, " \"{}\"", escape(message)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Splits a file into tokens the way the reference lexer does, including
+/// `ERROR` tokens for text it rejects.
+///
+/// Tokens come from the leaves of the syntax tree. Where the parser gave up
+/// on the text, inside error nodes, unterminated comments and strings, the
+/// file is scanned directly instead.
+pub fn tokens(file: &SourceFile, tree: &Tree) -> Vec<Token> {
+    let mut lexer = Lexer {
+        file,
+        src: file.src.as_bytes(),
+        pos: 0,
+        tokens: Vec::new(),
+    };
+    lexer.node(&tree.get_root());
+    lexer.scan(lexer.src.len());
+    lexer.tokens
+}
+
+/// Prints the tokens of a file as the reference lexer does, after a
+/// `#name` line naming the file.
+pub fn print(file: &SourceFile, tokens: &[Token]) -> String {
+    let mut out = format!("#name \"{}\"\n", escape(file.name.as_bytes()));
+    for token in tokens {
+        out.push_str(&token.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+struct Lexer<'a> {
+    file: &'a SourceFile,
+    src: &'a [u8],
+    /// The first byte not turned into tokens yet.
+    pos: usize,
+    tokens: Vec<Token>,
+}
+
+impl Lexer<'_> {
+    fn node(&mut self, node: &Node) {
+        let (start, end) = (node.start_byte(), node.end_byte());
+        if end <= self.pos || node.is_missing() {
+            return;
+        }
+        // A token the scanner already went past part of.
+        if start < self.pos {
+            return self.scan(end);
+        }
+
+        match node.rule() {
+            Cool::InlineComment => self.pos = end,
+            Cool::BlockComment if !node.has_error() => self.pos = end,
+            Cool::StringLiteral | Cool::BlockComment | Cool::Error => self.scan(end),
+            _ if node.child_count() > 0 => {
+                for child in node.children() {
+                    self.node(&child);
+                }
+            }
+            rule => match leaf(rule, &self.src[start..end]) {
+                Some(kind) => {
+                    self.push(end, kind);
+                    self.pos = end;
+                }
+                None => self.scan(end),
+            },
+        }
+    }
+
+    fn push(&mut self, end: usize, kind: TokenKind) {
+        let line = self.file.line_index(end.saturating_sub(1)) + 1;
+        self.tokens.push(Token { line, kind });
+    }
+
+    /// Scans the text up to `end`, and on to the end of the token there.
+    fn scan(&mut self, end: usize) {
+        while self.pos < end {
+            let start = self.pos;
+            let rest = &self.src[start..];
+            let Some(&c) = rest.first() else { return };
+
+            match c {
+                b' ' | b'\n' | b'\r' | b'\t' | 0x0b | 0x0c => self.pos += 1,
+                _ if rest.starts_with(b"--") => {
+                    let len = rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+                    self.pos += len;
+                }
+                _ if rest.starts_with(b"(*") => self.comment(),
+                _ if rest.starts_with(b"*)") => {
+                    self.pos += 2;
+                    self.push(self.pos, error("Unmatched *)"));
+                }
+                b'"' => self.string(),
+                b'0'..=b'9' => {
+                    let len = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+                    self.pos += len;
+                    let text = String::from_utf8_lossy(&rest[..len]);
+                    self.push(self.pos, TokenKind::Int(text.into()));
+                }
+                b'a'..=b'z' | b'A'..=b'Z' => {
+                    let len = rest
+                        .iter()
+                        .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'_')
+                        .count();
+                    self.pos += len;
+                    self.push(self.pos, word(&rest[..len]));
+                }
+                _ => {
+                    let (len, kind) = operator(rest);
+                    self.pos += len;
+                    self.push(self.pos, kind);
+                }
+            }
+        }
+    }
+
+    /// Skips a comment, nested ones included.
+    fn comment(&mut self) {
+        let mut depth = 0;
+        while self.pos < self.src.len() {
+            let rest = &self.src[self.pos..];
+            if rest.starts_with(b"(*") {
+                depth += 1;
+                self.pos += 2;
+            } else if rest.starts_with(b"*)") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return;
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+        self.push(self.src.len() + 1, error("EOF in comment"));
+    }
+
+    /// Scans a string constant. After an error the rest of the string is
+    /// skipped, up to its closing quote or the end of its line.
+    fn string(&mut self) {
+        let mut value = Vec::new();
+        let mut failure = None;
+        self.pos += 1;
+
+        loop {
+            let Some(&c) = self.src.get(self.pos) else {
+                let message = failure.unwrap_or("EOF in string constant");
+                return self.push(self.src.len() + 1, error(message));
+            };
+            self.pos += 1;
+
+            match c {
+                b'"' => break,
+                b'\n' => {
+                    let message = failure.unwrap_or("Unterminated string constant");
+                    return self.push(self.pos, error(message));
+                }
+                0 => {
+                    failure.get_or_insert("String contains null character.");
+                }
+                b'\\' => {
+                    let Some(&escaped) = self.src.get(self.pos) else {
+                        continue;
+                    };
+                    self.pos += 1;
+                    match unescape(escaped) {
+                        Some(c) => value.push(c),
+                        None => {
+                            failure.get_or_insert("String contains escaped null character.");
+                        }
+                    }
+                }
+                c => value.push(c),
+            }
+
+            if value.len() > MAX_STRING_LEN {
+                failure.get_or_insert("String constant too long");
+            }
+        }
+
+        let kind = match failure {
+            Some(message) => error(message),
+            None => TokenKind::Str(value.into()),
+        };
+        self.push(self.pos, kind);
+    }
+}
+
+fn error(message: &str) -> TokenKind {
+    TokenKind::Error(message.as_bytes().into())
+}
+
+/// Decodes the character after a backslash in a string, `None` for a null
+/// character.
+pub fn unescape(c: u8) -> Option<u8> {
+    match c {
+        0 => None,
+        b'b' => Some(0x08),
+        b't' => Some(b'\t'),
+        b'n' => Some(b'\n'),
+        b'f' => Some(0x0c),
+        c => Some(c),
+    }
+}
+
+/// Classifies a leaf of the syntax tree, or `None` for text the scanner has
+/// to go through.
+fn leaf(rule: Cool, text: &[u8]) -> Option<TokenKind> {
+    let kind = match rule {
+        Cool::Class
+        | Cool::Inherits
+        | Cool::If
+        | Cool::Then
+        | Cool::Else
+        | Cool::Fi
+        | Cool::While
+        | Cool::Loop
+        | Cool::Pool
+        | Cool::Let
+        | Cool::In
+        | Cool::Case
+        | Cool::Of
+        | Cool::Esac
+        | Cool::New
+        | Cool::Isvoid
+        | Cool::Not
+        | Cool::True
+        | Cool::False
+        | Cool::Identifier
+        | Cool::FieldIdentifier
+        | Cool::SelfIdentifier
+        | Cool::TypeIdentifier
+        | Cool::Bool
+        | Cool::Int
+        | Cool::Io
+        | Cool::Object
+        | Cool::String
+        | Cool::SelfType => word(text),
+        Cool::IntegerLiteral => TokenKind::Int(String::from_utf8_lossy(text).into()),
+        Cool::Semi
+        | Cool::LBrace
+        | Cool::RBrace
+        | Cool::Colon
+        | Cool::LtDash
+        | Cool::LParen
+        | Cool::Comma
+        | Cool::RParen
+        | Cool::At
+        | Cool::Dot
+        | Cool::EqGt
+        | Cool::Tilde
+        | Cool::Lte
+        | Cool::Lt
+        | Cool::Eq
+        | Cool::Plus
+        | Cool::Dash
+        | Cool::Star
+        | Cool::Slash => operator(text).1,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Classifies a keyword or an identifier. Keywords are case insensitive,
+/// but `true` and `false` have to start with a lowercase letter.
+fn word(text: &[u8]) -> TokenKind {
+    let lowercase = text.to_ascii_lowercase();
+    if let Some((_, kind)) = KEYWORDS
+        .iter()
+        .find(|(keyword, _)| keyword.as_bytes() == lowercase)
+    {
+        return kind.clone();
+    }
+
+    let name = String::from_utf8_lossy(text);
+    match &*lowercase {
+        b"true" | b"false" if text[0].is_ascii_lowercase() => TokenKind::Bool(text[0] == b't'),
+        _ if text[0].is_ascii_uppercase() => TokenKind::TypeId(name.into()),
+        _ => TokenKind::ObjectId(name.into()),
+    }
+}
+
+/// Classifies the operator at the start of `text`, with its length. Any
+/// other character is an error.
+fn operator(text: &[u8]) -> (usize, TokenKind) {
+    match text {
+        [b'<', b'-', ..] => (2, TokenKind::Assign),
+        [b'<', b'=', ..] => (2, TokenKind::Le),
+        [b'=', b'>', ..] => (2, TokenKind::Darrow),
+        [
+            c @ (b'{' | b'}' | b'(' | b')' | b';' | b':' | b',' | b'.' | b'@' | b'+' | b'-' | b'*'
+            | b'/' | b'~' | b'<' | b'='),
+            ..,
+        ] => (1, TokenKind::Char(*c)),
+        [c, ..] => (1, TokenKind::Error(Box::new([*c]))),
+        [] => unreachable!("operators are at least a character long"),
+    }
+}
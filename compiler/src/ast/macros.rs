@@ -27,7 +27,7 @@ macro_rules! ast_node {
                     match value {
                         $name::$variant(v) => Ok(v),
                         other => Err(
-                            $crate::ast::converter::Error::Unexpected(other)
+                            $crate::ast::converter::Error::Unexpected(Box::new(other))
                         )
                     }
                 }
@@ -0,0 +1,441 @@
+use std::fmt::Write;
+
+use crate::{
+    ast::{
+        BinaryOp, CaseArm, Class, Expr, ExprKind, Feature, Ident, LetBinding, Program, Type,
+        UnaryOp,
+        bindings::{Node, Tree},
+    },
+    language::Cool,
+    util::interner::Interner,
+};
+
+const INDENT: &str = "    ";
+
+/// Prints a program back as COOL source, in a canonical layout.
+///
+/// Blocks and `case` branches take a line each, and so do the constructs
+/// containing them. Everything else stays on a single line. Comments are
+/// kept, on a line of their own before the item that follows them, or at
+/// the end of the line they ended.
+pub fn format(src: &[u8], tree: &Tree, program: &Program, interner: &Interner) -> String {
+    let mut comments = Vec::new();
+    collect_comments(src, &tree.get_root(), &mut comments);
+
+    let mut printer = Printer {
+        src,
+        interner,
+        out: String::new(),
+        depth: 0,
+        comments,
+        next: 0,
+    };
+
+    for (idx, class) in program.classes.iter().enumerate() {
+        if idx > 0 {
+            printer.out.push('\n');
+        }
+        printer.class(class);
+    }
+    printer.comments_before(usize::MAX);
+
+    let mut out = printer.out;
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+struct Comment<'a> {
+    text: &'a str,
+    /// Byte offset of the comment.
+    start: usize,
+    /// Whether the comment follows a token on the same line.
+    trailing: bool,
+}
+
+fn collect_comments<'a>(src: &'a [u8], node: &Node<'a>, comments: &mut Vec<Comment<'a>>) {
+    if matches!(node.rule(), Cool::InlineComment | Cool::BlockComment) {
+        let trailing =
+            previous_token(node).is_some_and(|token| token.end_row() == node.start_row());
+
+        comments.push(Comment {
+            text: node.utf8_text(src).unwrap_or_default().trim_end(),
+            start: node.start_byte(),
+            trailing,
+        });
+        return;
+    }
+
+    for child in node.children() {
+        collect_comments(src, &child, comments);
+    }
+}
+
+fn previous_token<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut node = *node;
+    loop {
+        if let Some(previous) = node.previous_sibling() {
+            return Some(previous);
+        }
+        node = node.parent()?;
+    }
+}
+
+struct Printer<'a> {
+    src: &'a [u8],
+    interner: &'a Interner,
+    out: String,
+    depth: usize,
+    comments: Vec<Comment<'a>>,
+    /// The first comment not printed yet.
+    next: usize,
+}
+
+impl Printer<'_> {
+    /// Starts a new line at the current depth.
+    fn line(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// Prints the comments before `offset`, at the start of a line.
+    fn comments_before(&mut self, offset: usize) {
+        while let Some(comment) = self.comments.get(self.next) {
+            if comment.start >= offset {
+                break;
+            }
+            self.next += 1;
+
+            if comment.trailing && !self.out.is_empty() {
+                // Back to the end of the previous line, and to this one after
+                // the comment.
+                let end = self.out.trim_end().len();
+                let rest = self.out.split_off(end);
+                self.out.push(' ');
+                self.out.push_str(comment.text);
+                self.out.push_str(&rest);
+            } else {
+                self.out.push_str(comment.text);
+                self.line();
+            }
+        }
+    }
+
+    fn class(&mut self, class: &Class) {
+        self.comments_before(start(&class.name.0));
+
+        self.out.push_str("class ");
+        self.ty(&class.name);
+        if let Some(parent) = &class.inherits {
+            self.out.push_str(" inherits ");
+            self.ty(parent);
+        }
+
+        let features = &class.features.0;
+        if features.is_empty() {
+            self.out.push_str(" {};");
+            self.line();
+            return;
+        }
+
+        self.out.push_str(" {");
+        self.depth += 1;
+        for (idx, feature) in features.iter().enumerate() {
+            let is_method = |feature: &Feature| matches!(feature, Feature::Method(_));
+            if idx > 0 && (is_method(feature) || is_method(&features[idx - 1])) {
+                self.out.push('\n');
+            }
+            self.line();
+            self.feature(feature);
+        }
+        self.depth -= 1;
+        self.line();
+        self.out.push_str("};");
+        self.line();
+    }
+
+    fn feature(&mut self, feature: &Feature) {
+        match feature {
+            Feature::Attribute(attribute) => {
+                self.comments_before(start(&attribute.name));
+                self.binding(
+                    &attribute.name,
+                    &attribute.ty,
+                    attribute.initializer.as_ref(),
+                );
+            }
+            Feature::Method(method) => {
+                self.comments_before(start(&method.name));
+                self.ident(&method.name);
+                self.out.push('(');
+                for (idx, param) in method.params.0.iter().enumerate() {
+                    if idx > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.ident(&param.name);
+                    self.out.push_str(" : ");
+                    self.ty(&param.ty);
+                }
+                self.out.push_str(") : ");
+                self.ty(&method.return_ty);
+
+                if is_multiline(&method.body) {
+                    self.out.push_str(" {");
+                    self.indented(&method.body);
+                    self.line();
+                    self.out.push('}');
+                } else {
+                    self.out.push_str(" { ");
+                    self.expr(&method.body);
+                    self.out.push_str(" }");
+                }
+            }
+        }
+        self.out.push(';');
+    }
+
+    fn binding(&mut self, name: &Ident, ty: &Type, initializer: Option<&Expr>) {
+        self.ident(name);
+        self.out.push_str(" : ");
+        self.ty(ty);
+        if let Some(initializer) = initializer {
+            self.out.push_str(" <- ");
+            self.expr(initializer);
+        }
+    }
+
+    /// Prints the body of a construct after the keyword that opens it. A
+    /// block opens on the same line, other bodies spanning several lines
+    /// start on their own.
+    fn body(&mut self, expr: &Expr) {
+        if matches!(expr.kind, ExprKind::Block { .. }) {
+            self.out.push(' ');
+            self.expr(expr);
+        } else {
+            self.indented(expr);
+        }
+    }
+
+    /// Prints the keyword following a body printed by [`Printer::body`].
+    fn after_body(&mut self, expr: &Expr, keyword: &str) {
+        if matches!(expr.kind, ExprKind::Block { .. }) {
+            self.out.push(' ');
+        } else {
+            self.line();
+        }
+        self.out.push_str(keyword);
+    }
+
+    /// Prints an expression on a line of its own, one level deeper.
+    fn indented(&mut self, expr: &Expr) {
+        self.depth += 1;
+        self.line();
+        self.comments_before(expr_start(expr));
+        self.expr(expr);
+        self.depth -= 1;
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Assignment { name, right } => {
+                self.ident(name);
+                self.out.push_str(" <- ");
+                self.expr(right);
+            }
+            ExprKind::Dispatch {
+                qualifier,
+                method,
+                args,
+            } => {
+                if let Some(qualifier) = qualifier {
+                    self.expr(&qualifier.value);
+                    if let Some(parent) = &qualifier.parent {
+                        self.out.push('@');
+                        self.ty(parent);
+                    }
+                    self.out.push('.');
+                }
+                self.ident(method);
+                self.out.push('(');
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.expr(arg);
+                }
+                self.out.push(')');
+            }
+            ExprKind::Conditional {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.out.push_str("if ");
+                self.expr(condition);
+                if is_multiline(consequence) || is_multiline(alternative) {
+                    self.out.push_str(" then");
+                    self.body(consequence);
+                    self.after_body(consequence, "else");
+                    self.body(alternative);
+                    self.after_body(alternative, "fi");
+                } else {
+                    self.out.push_str(" then ");
+                    self.expr(consequence);
+                    self.out.push_str(" else ");
+                    self.expr(alternative);
+                    self.out.push_str(" fi");
+                }
+            }
+            ExprKind::Repeat { condition, body } => {
+                self.out.push_str("while ");
+                self.expr(condition);
+                if is_multiline(body) {
+                    self.out.push_str(" loop");
+                    self.body(body);
+                    self.after_body(body, "pool");
+                } else {
+                    self.out.push_str(" loop ");
+                    self.expr(body);
+                    self.out.push_str(" pool");
+                }
+            }
+            ExprKind::Block { body } => {
+                self.out.push('{');
+                self.depth += 1;
+                for expr in body {
+                    self.line();
+                    self.comments_before(expr_start(expr));
+                    self.expr(expr);
+                    self.out.push(';');
+                }
+                // Comments after the last expression stay inside the block.
+                self.line();
+                self.comments_before(expr_end(expr));
+                self.out.truncate(self.out.trim_end().len());
+                self.depth -= 1;
+                self.line();
+                self.out.push('}');
+            }
+            ExprKind::Let { bindings, body } => {
+                self.out.push_str("let ");
+                for (
+                    idx,
+                    LetBinding {
+                        name, ty, right, ..
+                    },
+                ) in bindings.0.iter().enumerate()
+                {
+                    if idx > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.binding(name, ty, right.as_ref());
+                }
+                if is_multiline(body) {
+                    self.out.push_str(" in");
+                    self.body(body);
+                } else {
+                    self.out.push_str(" in ");
+                    self.expr(body);
+                }
+            }
+            ExprKind::Case { value, body } => {
+                self.out.push_str("case ");
+                self.expr(value);
+                self.out.push_str(" of");
+                self.depth += 1;
+                for CaseArm { pat, value, .. } in body {
+                    self.line();
+                    self.comments_before(start(&pat.name));
+                    self.ident(&pat.name);
+                    self.out.push_str(" : ");
+                    self.ty(&pat.ty);
+                    self.out.push_str(" => ");
+                    self.expr(value);
+                    self.out.push(';');
+                }
+                self.depth -= 1;
+                self.line();
+                self.out.push_str("esac");
+            }
+            ExprKind::New { ty } => {
+                self.out.push_str("new ");
+                self.ty(ty);
+            }
+            ExprKind::Unary { op, right } => {
+                self.out.push_str(match op {
+                    UnaryOp::IsVoid => "isvoid ",
+                    UnaryOp::Complement => "~",
+                    UnaryOp::Not => "not ",
+                });
+                self.expr(right);
+            }
+            ExprKind::Binary { op, left, right } => {
+                self.expr(left);
+                self.out.push_str(match op {
+                    BinaryOp::Add => " + ",
+                    BinaryOp::Sub => " - ",
+                    BinaryOp::Mul => " * ",
+                    BinaryOp::Div => " / ",
+                    BinaryOp::Lt => " < ",
+                    BinaryOp::Lte => " <= ",
+                    BinaryOp::Eq => " = ",
+                });
+                self.expr(right);
+            }
+            ExprKind::Paren { value } => {
+                self.out.push('(');
+                self.expr(value);
+                self.out.push(')');
+            }
+            ExprKind::Ident(ident) => self.ident(ident),
+            // Strings are printed as written, escapes and all.
+            ExprKind::String(_) => {
+                let text = source_text(self.src, expr);
+                self.out.push_str(text);
+            }
+            ExprKind::Int(value) => write!(self.out, "{}", value).unwrap(),
+            ExprKind::Bool(value) => write!(self.out, "{}", value).unwrap(),
+        }
+    }
+
+    fn ident(&mut self, ident: &Ident) {
+        self.out.push_str(self.interner.lookup(&ident.name));
+    }
+
+    fn ty(&mut self, ty: &Type) {
+        self.ident(&ty.0);
+    }
+}
+
+/// Whether an expression is laid out over several lines, i.e. it is or
+/// ends with a block or a `case`.
+fn is_multiline(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Block { .. } | ExprKind::Case { .. } => true,
+        ExprKind::Conditional {
+            consequence,
+            alternative,
+            ..
+        } => is_multiline(consequence) || is_multiline(alternative),
+        ExprKind::Repeat { body, .. } | ExprKind::Let { body, .. } => is_multiline(body),
+        _ => false,
+    }
+}
+
+fn start(ident: &Ident) -> usize {
+    ident.span.start as usize
+}
+
+fn expr_start(expr: &Expr) -> usize {
+    expr.span.start as usize
+}
+
+fn expr_end(expr: &Expr) -> usize {
+    expr.span.end as usize
+}
+
+fn source_text<'a>(src: &'a [u8], expr: &Expr) -> &'a str {
+    std::str::from_utf8(&src[expr.span.range()]).unwrap_or_default()
+}
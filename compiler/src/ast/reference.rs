@@ -0,0 +1,285 @@
+use std::fmt::Write;
+
+use crate::{
+    ast::{
+        Attribute, BinaryOp, CaseArm, Class, Expr, ExprKind, Feature, Ident, LetBinding, Method,
+        Program, Type, UnaryOp,
+    },
+    util::{interner::Interner, source::SourceMap, span::Span},
+};
+
+/// Prints a program the way the reference COOL parser dumps its tree, so
+/// that the output can be compared against `lexer | parser`.
+///
+/// Every node starts with `#<line>` and its name, with its children two
+/// spaces deeper. Expressions end with their type, always `_no_type` as the
+/// tree is not checked yet.
+pub fn print(program: &Program, sources: &SourceMap, interner: &Interner) -> String {
+    let mut printer = Printer {
+        sources,
+        interner,
+        out: String::new(),
+    };
+
+    let span = program
+        .classes
+        .first()
+        .map_or_else(Span::default, |class| class.span);
+    printer.node(0, span, "_program");
+    for class in &program.classes {
+        printer.class(2, class);
+    }
+    printer.out
+}
+
+struct Printer<'a> {
+    sources: &'a SourceMap,
+    interner: &'a Interner,
+    out: String,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, depth: usize, text: &str) {
+        writeln!(self.out, "{:depth$}{}", "", text).unwrap();
+    }
+
+    fn node(&mut self, depth: usize, span: Span, name: &str) {
+        let line = self.sources.line(span);
+        self.line(depth, &format!("#{}", line));
+        self.line(depth, name);
+    }
+
+    fn ident(&mut self, depth: usize, ident: &Ident) {
+        self.line(depth, self.interner.lookup(&ident.name));
+    }
+
+    fn ty(&mut self, depth: usize, ty: &Type) {
+        self.ident(depth, &ty.0);
+    }
+
+    fn no_type(&mut self, depth: usize) {
+        self.line(depth, ": _no_type");
+    }
+
+    fn class(&mut self, depth: usize, class: &Class) {
+        self.node(depth, class.span, "_class");
+        self.ty(depth + 2, &class.name);
+        match &class.inherits {
+            Some(parent) => self.ty(depth + 2, parent),
+            None => self.line(depth + 2, "Object"),
+        }
+
+        let file = &self.sources.get(class.span.file).name;
+        self.line(depth + 2, &format!("\"{}\"", escape(file.as_bytes())));
+        self.line(depth + 2, "(");
+        for feature in &class.features.0 {
+            match feature {
+                Feature::Attribute(attribute) => self.attribute(depth + 2, attribute),
+                Feature::Method(method) => self.method(depth + 2, method),
+            }
+        }
+        self.line(depth + 2, ")");
+    }
+
+    fn attribute(&mut self, depth: usize, attribute: &Attribute) {
+        self.node(depth, attribute.span, "_attr");
+        self.ident(depth + 2, &attribute.name);
+        self.ty(depth + 2, &attribute.ty);
+        self.optional(depth + 2, attribute.initializer.as_ref(), attribute.span);
+    }
+
+    fn method(&mut self, depth: usize, method: &Method) {
+        self.node(depth, method.span, "_method");
+        self.ident(depth + 2, &method.name);
+        for param in &method.params.0 {
+            self.node(depth + 2, param.span, "_formal");
+            self.ident(depth + 4, &param.name);
+            self.ty(depth + 4, &param.ty);
+        }
+        self.ty(depth + 2, &method.return_ty);
+        self.expr(depth + 2, &method.body);
+    }
+
+    /// Prints an expression that may be left out, as `_no_expr` at the line
+    /// of the construct around it.
+    fn optional(&mut self, depth: usize, expr: Option<&Expr>, span: Span) {
+        match expr {
+            Some(expr) => self.expr(depth, expr),
+            None => {
+                self.node(depth, span, "_no_expr");
+                self.no_type(depth);
+            }
+        }
+    }
+
+    fn expr(&mut self, depth: usize, expr: &Expr) {
+        let inner = depth + 2;
+
+        match &expr.kind {
+            // The reference tree has no node for parentheses.
+            ExprKind::Paren { value } => return self.expr(depth, value),
+            ExprKind::Let { bindings, body } => {
+                return self.bindings(depth, &bindings.0, body, expr.span);
+            }
+            _ => {}
+        }
+
+        match &expr.kind {
+            ExprKind::Assignment { name, right } => {
+                self.node(depth, expr.span, "_assign");
+                self.ident(inner, name);
+                self.expr(inner, right);
+            }
+            ExprKind::Dispatch {
+                qualifier,
+                method,
+                args,
+            } => {
+                match qualifier {
+                    Some(qualifier) if qualifier.parent.is_some() => {
+                        self.node(depth, expr.span, "_static_dispatch");
+                        self.expr(inner, &qualifier.value);
+                        self.ty(inner, qualifier.parent.as_ref().unwrap());
+                    }
+                    Some(qualifier) => {
+                        self.node(depth, expr.span, "_dispatch");
+                        self.expr(inner, &qualifier.value);
+                    }
+                    // A dispatch without a receiver is a dispatch on `self`.
+                    None => {
+                        self.node(depth, expr.span, "_dispatch");
+                        self.node(inner, expr.span, "_object");
+                        self.line(inner + 2, "self");
+                        self.no_type(inner);
+                    }
+                }
+                self.ident(inner, method);
+                self.line(inner, "(");
+                for arg in args {
+                    self.expr(inner, arg);
+                }
+                self.line(inner, ")");
+            }
+            ExprKind::Conditional {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.node(depth, expr.span, "_cond");
+                self.expr(inner, condition);
+                self.expr(inner, consequence);
+                self.expr(inner, alternative);
+            }
+            ExprKind::Repeat { condition, body } => {
+                self.node(depth, expr.span, "_loop");
+                self.expr(inner, condition);
+                self.expr(inner, body);
+            }
+            ExprKind::Block { body } => {
+                self.node(depth, expr.span, "_block");
+                for expr in body {
+                    self.expr(inner, expr);
+                }
+            }
+            ExprKind::Case { value, body } => {
+                self.node(depth, expr.span, "_typcase");
+                self.expr(inner, value);
+                for arm in body {
+                    self.arm(inner, arm);
+                }
+            }
+            ExprKind::New { ty } => {
+                self.node(depth, expr.span, "_new");
+                self.ty(inner, ty);
+            }
+            ExprKind::Unary { op, right } => {
+                let name = match op {
+                    UnaryOp::IsVoid => "_isvoid",
+                    UnaryOp::Complement => "_neg",
+                    UnaryOp::Not => "_comp",
+                };
+                self.node(depth, expr.span, name);
+                self.expr(inner, right);
+            }
+            ExprKind::Binary { op, left, right } => {
+                let name = match op {
+                    BinaryOp::Add => "_plus",
+                    BinaryOp::Sub => "_sub",
+                    BinaryOp::Mul => "_mul",
+                    BinaryOp::Div => "_divide",
+                    BinaryOp::Lt => "_lt",
+                    BinaryOp::Lte => "_leq",
+                    BinaryOp::Eq => "_eq",
+                };
+                self.node(depth, expr.span, name);
+                self.expr(inner, left);
+                self.expr(inner, right);
+            }
+            ExprKind::Ident(ident) => {
+                self.node(depth, expr.span, "_object");
+                self.ident(inner, ident);
+            }
+            ExprKind::String(value) => {
+                self.node(depth, expr.span, "_string");
+                self.line(inner, &format!("\"{}\"", escape(value.as_bytes())));
+            }
+            // Integers are printed as written, leading zeros and all.
+            ExprKind::Int(_) => {
+                let text = self.sources.get(expr.span.file).text(expr.span);
+                self.node(depth, expr.span, "_int");
+                self.line(inner, text);
+            }
+            ExprKind::Bool(value) => {
+                self.node(depth, expr.span, "_bool");
+                self.line(inner, if *value { "1" } else { "0" });
+            }
+            ExprKind::Paren { .. } | ExprKind::Let { .. } => unreachable!(),
+        }
+        self.no_type(depth);
+    }
+
+    /// Prints the bindings of a `let` as one `_let` per binding, each the
+    /// body of the one before.
+    fn bindings(&mut self, depth: usize, bindings: &[LetBinding], body: &Expr, span: Span) {
+        let Some((binding, rest)) = bindings.split_first() else {
+            return self.expr(depth, body);
+        };
+
+        self.node(depth, span, "_let");
+        self.ident(depth + 2, &binding.name);
+        self.ty(depth + 2, &binding.ty);
+        self.optional(depth + 2, binding.right.as_ref(), binding.span);
+        match rest.first() {
+            Some(next) => self.bindings(depth + 2, rest, body, next.span),
+            None => self.expr(depth + 2, body),
+        }
+        self.no_type(depth);
+    }
+
+    fn arm(&mut self, depth: usize, arm: &CaseArm) {
+        self.node(depth, arm.span, "_branch");
+        self.ident(depth + 2, &arm.pat.name);
+        self.ty(depth + 2, &arm.pat.ty);
+        self.expr(depth + 2, &arm.value);
+    }
+}
+
+/// Escapes a string the way the reference tools do: backslashes, quotes
+/// and the characters COOL has escapes for are written with a backslash,
+/// other bytes that are not printable ASCII in octal.
+pub(crate) fn escape(value: &[u8]) -> String {
+    let mut out = String::with_capacity(value.len());
+    for &byte in value {
+        match byte {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\t' => out.push_str("\\t"),
+            0x08 => out.push_str("\\b"),
+            0x0c => out.push_str("\\f"),
+            b' '..=b'~' => out.push(byte as char),
+            _ => write!(out, "\\{:03o}", byte).unwrap(),
+        }
+    }
+    out
+}
@@ -0,0 +1,16 @@
+use sexp_core::{SexpSerializerImpl, ToSexp};
+
+use crate::{ast::Program, util::interner::Interner};
+
+/// Writes a program as an s-expression, a cell per node with names resolved
+/// through `interner`. Spans and expression ids are left out.
+pub fn print(program: &Program, interner: &Interner) -> String {
+    let mut out = Vec::new();
+    let mut serializer = SexpSerializerImpl::with_context(&mut out, interner);
+    program
+        .to_sexp(&mut serializer)
+        .expect("writing to a vector cannot fail");
+
+    out.push(b'\n');
+    String::from_utf8(out).expect("s-expressions are written as UTF-8")
+}
@@ -0,0 +1,308 @@
+use crate::{
+    ast::{
+        bindings::{Node, Tree},
+        converter::{Error, extract},
+    },
+    language::Cool,
+    util::span::{FileId, Span, Spanned},
+};
+
+/// Reports the syntax errors tree-sitter recovered from.
+///
+/// The parser either skips the text it cannot make sense of, leaving an
+/// `ERROR` node, or inserts the token it expected, leaving a node that is
+/// missing from the source. Both are described from the construct they
+/// appear in, so that each mistake gets its own message.
+pub fn check(file: FileId, src: &[u8], tree: &Tree) -> Vec<Spanned<Error>> {
+    let mut errors = Vec::new();
+    let mut pending = vec![tree.get_root()];
+    // The end of the last string, as the grammar may end one early. The
+    // `ERROR` nodes before it hold the rest of the string.
+    let mut string_end = 0;
+
+    while let Some(node) = pending.pop() {
+        if node.rule() == Cool::Error && node.end_byte() <= string_end {
+            continue;
+        }
+        if node.rule() == Cool::Error {
+            // Whatever the skipped text contains only follows from the
+            // mistake that made the parser skip it.
+            errors.push(skipped(file, src, &node));
+            continue;
+        }
+        if node.is_missing() {
+            errors.push(Spanned {
+                inner: missing(src, &node),
+                span: node.span(file),
+            });
+            continue;
+        }
+        if node.rule() == Cool::StringLiteral {
+            let end = extract::string_end(src, &node);
+            string_end = end.unwrap_or_else(|| line_end(src, node.start_byte()));
+
+            match extract::string(src, &node) {
+                Ok(_) => {}
+                Err(Error::UnterminatedString) => {
+                    errors.push(unterminated(file, src, node.start_byte()));
+                }
+                Err(inner) => errors.push(Spanned {
+                    inner,
+                    span: Span::new(file, node.start_byte(), string_end),
+                }),
+            }
+            continue;
+        }
+        if node.rule() == Cool::IntegerLiteral
+            && let Err(inner) = extract::int(src, &node)
+        {
+            errors.push(Spanned {
+                inner,
+                span: node.span(file),
+            });
+        }
+        if node.rule() == Cool::IfExpression
+            && node.child_by_field_name("alternative").is_none()
+            && !node.has_error()
+        {
+            errors.push(Spanned {
+                inner: Error::MissingElse,
+                span: node.span(file),
+            });
+        }
+
+        let mut children: Vec<_> = node.children().collect();
+        children.reverse();
+        pending.extend(children);
+    }
+
+    errors.sort_by_key(|error| error.span);
+    errors
+}
+
+/// Tokens an expression follows.
+const OPERATORS: &[&str] = &[
+    "<-", "+", "-", "*", "/", "<", "<=", "=", "=>", "~", "not", "isvoid", "if", "then", "else",
+    "while", "loop", "in", "case", "of",
+];
+
+/// Describes a token the parser inserted.
+fn missing(src: &[u8], node: &Node) -> Error {
+    let parent = node.parent();
+    let construct = parent.as_ref().map_or("program", describe);
+
+    match node.kind() {
+        ";" if parent.is_some_and(|parent| parent.rule() == Cool::ClassItem) => {
+            Error::MissingSemicolon("class")
+        }
+        ";" => {
+            let previous = node.previous_sibling();
+            Error::MissingSemicolon(previous.as_ref().map_or(construct, describe))
+        }
+        token @ ("fi" | "pool" | "esac" | ")" | "}") => Error::Unclosed {
+            token,
+            construct: parent.as_ref().map_or("program", opened_by),
+        },
+        // Expressions are recovered with the cheapest literal there is.
+        "true" | "false" | "integer_literal" | "identifier" | "self" => {
+            let expr = match parent {
+                Some(parent) if parent.rule() == Cool::BooleanLiteral => parent,
+                _ => *node,
+            };
+            let is_name = expr.parent().is_some_and(|parent| {
+                ["name", "method", "left"]
+                    .iter()
+                    .any(|field| is_field(&parent, field, &expr))
+            });
+
+            if is_name {
+                Error::MissingNode {
+                    expected: "identifier",
+                    after: token_before(src, &expr),
+                }
+            } else {
+                Error::MissingNode {
+                    expected: "expression",
+                    after: token_before(src, &expr),
+                }
+            }
+        }
+        "type_identifier" | "primitive_type" => Error::MissingNode {
+            expected: "type",
+            after: token_before(src, node),
+        },
+        token => Error::MissingToken { token, construct },
+    }
+}
+
+/// Describes text the parser skipped.
+fn skipped(file: FileId, src: &[u8], node: &Node) -> Spanned<Error> {
+    let span = node.span(file);
+    let parent = node.parent();
+    let construct = parent.as_ref().map_or("program", describe);
+    let first = node.child(0);
+
+    // A quote that is never closed ends the string at the end of its line.
+    if let Some(quote) = node.children().find(|child| child.kind() == "\"") {
+        return unterminated(file, src, quote.start_byte());
+    }
+
+    // `class main` is lexed as an identifier the class keyword cannot take.
+    if first.as_ref().is_some_and(|first| first.kind() == "class") {
+        let text = text(src, node);
+        let name = text["class".len()..]
+            .trim_start()
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+
+        if name.starts_with(|c: char| c.is_lowercase() || c == '_') {
+            return Spanned {
+                inner: Error::LowercaseClassName(name.into()),
+                span,
+            };
+        }
+    }
+
+    // A `let` binding is followed by something else than `in` or `,`.
+    let children: Vec<_> = node.children().collect();
+    for pair in children.windows(2) {
+        if pair[0].rule() == Cool::Binding && !matches!(pair[1].kind(), "in" | ",") {
+            return Spanned {
+                inner: Error::MissingToken {
+                    token: "in",
+                    construct: "`let`",
+                },
+                span: pair[1].span(file),
+            };
+        }
+    }
+
+    let inner = match (
+        parent.as_ref().map(Node::rule),
+        first.as_ref().map(Node::kind),
+    ) {
+        // `x : Int` followed by anything but `;` is taken for the start of
+        // a method.
+        (Some(Cool::MethodDeclaration), Some(":")) => {
+            Error::MissingSemicolon("attribute declaration")
+        }
+        // A lone token that needs something after it.
+        (_, Some(token @ ("inherits" | "@" | "new" | ":"))) if node.child_count() == 1 => {
+            Error::MissingNode {
+                expected: "type",
+                after: Some(token.into()),
+            }
+        }
+        (_, Some(token)) if node.child_count() == 1 && OPERATORS.contains(&token) => {
+            Error::MissingNode {
+                expected: "expression",
+                after: Some(token.into()),
+            }
+        }
+        // An expression following another one in a block.
+        (Some(Cool::Block), _)
+            if node.child_count() == 1 && first.is_some_and(|first| first.rule().is_expr()) =>
+        {
+            Error::MissingSemicolon("expression")
+        }
+        _ => {
+            let token = first_token(node);
+            Error::UnexpectedToken {
+                token: text(src, &token).into(),
+                construct,
+            }
+        }
+    };
+    Spanned { inner, span }
+}
+
+/// Reports a string starting at `start` and ending with its line.
+fn unterminated(file: FileId, src: &[u8], start: usize) -> Spanned<Error> {
+    Spanned {
+        inner: Error::UnterminatedString,
+        span: Span::new(file, start, line_end(src, start)),
+    }
+}
+
+fn line_end(src: &[u8], start: usize) -> usize {
+    src[start..]
+        .iter()
+        .position(|&c| c == b'\n')
+        .map_or(src.len(), |len| start + len)
+}
+
+/// Describes a node for messages, as the construct it stands for.
+fn describe(node: &Node) -> &'static str {
+    match node.rule() {
+        Cool::SourceFile => "program",
+        Cool::ClassItem => "class",
+        Cool::FieldDeclarationList => "class body",
+        Cool::AttributeDeclaration => "attribute declaration",
+        Cool::MethodDeclaration => "method declaration",
+        Cool::Parameters => "parameter list",
+        Cool::Parameter => "parameter",
+        Cool::Arguments => "argument list",
+        Cool::IfExpression => "`if`",
+        Cool::WhileExpression => "`while`",
+        Cool::Block => "block",
+        Cool::LetExpression => "`let`",
+        Cool::Bindings | Cool::Binding => "`let` binding",
+        Cool::CaseExpression => "`case`",
+        Cool::CaseArm | Cool::CasePattern => "`case` branch",
+        Cool::NewExpression => "`new`",
+        Cool::DispatchExpression => "dispatch",
+        Cool::AssignmentExpression => "assignment",
+        Cool::ParenthesizedExpression => "parenthesized expression",
+        Cool::StringLiteral => "string",
+        rule if rule.is_expr() => "expression",
+        _ => "program",
+    }
+}
+
+/// Describes the construct a closing token ends.
+fn opened_by(node: &Node) -> &'static str {
+    match node.rule() {
+        Cool::MethodDeclaration => "method body",
+        Cool::ParenthesizedExpression => "`(`",
+        _ => describe(node),
+    }
+}
+
+fn is_field(parent: &Node, field: &str, node: &Node) -> bool {
+    parent
+        .child_by_field_name(field)
+        .is_some_and(|child| child.id() == node.id())
+}
+
+/// The token right before a node, if any, to point out what the node was
+/// expected to follow.
+fn token_before(src: &[u8], node: &Node) -> Option<Box<str>> {
+    let previous = node.previous_sibling()?;
+    let previous = text(src, &last_token(&previous));
+    (!previous.is_empty()).then(|| previous.into())
+}
+
+fn first_token<'a>(node: &Node<'a>) -> Node<'a> {
+    let mut node = *node;
+    while let Some(child) = node.child(0) {
+        node = child;
+    }
+    node
+}
+
+fn last_token<'a>(node: &Node<'a>) -> Node<'a> {
+    let mut node = *node;
+    while let Some(child) = node
+        .child_count()
+        .checked_sub(1)
+        .and_then(|idx| node.child(idx))
+    {
+        node = child;
+    }
+    node
+}
+
+fn text<'a>(src: &'a [u8], node: &Node<'a>) -> &'a str {
+    node.utf8_text(src).unwrap_or_default()
+}
@@ -0,0 +1,386 @@
+//! Traversals of the syntax tree.
+//!
+//! A [`Visitor`] sees every node of a tree by reference, and a [`VisitorMut`]
+//! by mutable reference. Each `visit_*` method defaults to the `walk_*`
+//! function of its node, which visits the children of the node in source
+//! order. Passes override the methods of the nodes they care about, and call
+//! the `walk_*` function from them to keep descending.
+
+use crate::ast::{
+    Attribute, CaseArm, CasePattern, Class, Expr, ExprKind, Feature, Ident, LetBinding, Method,
+    Param, Program, Qualifier, Type,
+};
+
+pub trait Visitor<'ast>: Sized {
+    fn visit_program(&mut self, program: &'ast Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_class(&mut self, class: &'ast Class) {
+        walk_class(self, class);
+    }
+
+    fn visit_feature(&mut self, feature: &'ast Feature) {
+        walk_feature(self, feature);
+    }
+
+    fn visit_attribute(&mut self, attribute: &'ast Attribute) {
+        walk_attribute(self, attribute);
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        walk_method(self, method);
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        walk_param(self, param);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_qualifier(&mut self, qualifier: &'ast Qualifier) {
+        walk_qualifier(self, qualifier);
+    }
+
+    fn visit_let_binding(&mut self, binding: &'ast LetBinding) {
+        walk_let_binding(self, binding);
+    }
+
+    fn visit_case_arm(&mut self, arm: &'ast CaseArm) {
+        walk_case_arm(self, arm);
+    }
+
+    fn visit_case_pattern(&mut self, pat: &'ast CasePattern) {
+        walk_case_pattern(self, pat);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        walk_type(self, ty);
+    }
+
+    fn visit_ident(&mut self, _ident: &'ast Ident) {}
+}
+
+pub fn walk_program<'ast, V: Visitor<'ast>>(visitor: &mut V, program: &'ast Program) {
+    for class in &program.classes {
+        visitor.visit_class(class);
+    }
+}
+
+pub fn walk_class<'ast, V: Visitor<'ast>>(visitor: &mut V, class: &'ast Class) {
+    visitor.visit_type(&class.name);
+    if let Some(parent) = &class.inherits {
+        visitor.visit_type(parent);
+    }
+    for feature in &class.features.0 {
+        visitor.visit_feature(feature);
+    }
+}
+
+pub fn walk_feature<'ast, V: Visitor<'ast>>(visitor: &mut V, feature: &'ast Feature) {
+    match feature {
+        Feature::Attribute(attribute) => visitor.visit_attribute(attribute),
+        Feature::Method(method) => visitor.visit_method(method),
+    }
+}
+
+pub fn walk_attribute<'ast, V: Visitor<'ast>>(visitor: &mut V, attribute: &'ast Attribute) {
+    visitor.visit_ident(&attribute.name);
+    visitor.visit_type(&attribute.ty);
+    if let Some(initializer) = &attribute.initializer {
+        visitor.visit_expr(initializer);
+    }
+}
+
+pub fn walk_method<'ast, V: Visitor<'ast>>(visitor: &mut V, method: &'ast Method) {
+    visitor.visit_ident(&method.name);
+    for param in &method.params.0 {
+        visitor.visit_param(param);
+    }
+    visitor.visit_type(&method.return_ty);
+    visitor.visit_expr(&method.body);
+}
+
+pub fn walk_param<'ast, V: Visitor<'ast>>(visitor: &mut V, param: &'ast Param) {
+    visitor.visit_ident(&param.name);
+    visitor.visit_type(&param.ty);
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast>>(visitor: &mut V, expr: &'ast Expr) {
+    match &expr.kind {
+        ExprKind::Assignment { name, right } => {
+            visitor.visit_ident(name);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Dispatch {
+            qualifier,
+            method,
+            args,
+        } => {
+            if let Some(qualifier) = qualifier {
+                visitor.visit_qualifier(qualifier);
+            }
+            visitor.visit_ident(method);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprKind::Conditional {
+            condition,
+            consequence,
+            alternative,
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(consequence);
+            visitor.visit_expr(alternative);
+        }
+        ExprKind::Repeat { condition, body } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(body);
+        }
+        ExprKind::Block { body } => {
+            for expr in body {
+                visitor.visit_expr(expr);
+            }
+        }
+        ExprKind::Let { bindings, body } => {
+            for binding in &bindings.0 {
+                visitor.visit_let_binding(binding);
+            }
+            visitor.visit_expr(body);
+        }
+        ExprKind::Case { value, body } => {
+            visitor.visit_expr(value);
+            for arm in body {
+                visitor.visit_case_arm(arm);
+            }
+        }
+        ExprKind::New { ty } => visitor.visit_type(ty),
+        ExprKind::Unary { right, .. } => visitor.visit_expr(right),
+        ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Paren { value } => visitor.visit_expr(value),
+        ExprKind::Ident(ident) => visitor.visit_ident(ident),
+        ExprKind::String(_) | ExprKind::Int(_) | ExprKind::Bool(_) => {}
+    }
+}
+
+pub fn walk_qualifier<'ast, V: Visitor<'ast>>(visitor: &mut V, qualifier: &'ast Qualifier) {
+    visitor.visit_expr(&qualifier.value);
+    if let Some(parent) = &qualifier.parent {
+        visitor.visit_type(parent);
+    }
+}
+
+pub fn walk_let_binding<'ast, V: Visitor<'ast>>(visitor: &mut V, binding: &'ast LetBinding) {
+    visitor.visit_ident(&binding.name);
+    visitor.visit_type(&binding.ty);
+    if let Some(right) = &binding.right {
+        visitor.visit_expr(right);
+    }
+}
+
+pub fn walk_case_arm<'ast, V: Visitor<'ast>>(visitor: &mut V, arm: &'ast CaseArm) {
+    visitor.visit_case_pattern(&arm.pat);
+    visitor.visit_expr(&arm.value);
+}
+
+pub fn walk_case_pattern<'ast, V: Visitor<'ast>>(visitor: &mut V, pat: &'ast CasePattern) {
+    visitor.visit_ident(&pat.name);
+    visitor.visit_type(&pat.ty);
+}
+
+pub fn walk_type<'ast, V: Visitor<'ast>>(visitor: &mut V, ty: &'ast Type) {
+    visitor.visit_ident(&ty.0);
+}
+
+pub trait VisitorMut: Sized {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_class_mut(&mut self, class: &mut Class) {
+        walk_class_mut(self, class);
+    }
+
+    fn visit_feature_mut(&mut self, feature: &mut Feature) {
+        walk_feature_mut(self, feature);
+    }
+
+    fn visit_attribute_mut(&mut self, attribute: &mut Attribute) {
+        walk_attribute_mut(self, attribute);
+    }
+
+    fn visit_method_mut(&mut self, method: &mut Method) {
+        walk_method_mut(self, method);
+    }
+
+    fn visit_param_mut(&mut self, param: &mut Param) {
+        walk_param_mut(self, param);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_qualifier_mut(&mut self, qualifier: &mut Qualifier) {
+        walk_qualifier_mut(self, qualifier);
+    }
+
+    fn visit_let_binding_mut(&mut self, binding: &mut LetBinding) {
+        walk_let_binding_mut(self, binding);
+    }
+
+    fn visit_case_arm_mut(&mut self, arm: &mut CaseArm) {
+        walk_case_arm_mut(self, arm);
+    }
+
+    fn visit_case_pattern_mut(&mut self, pat: &mut CasePattern) {
+        walk_case_pattern_mut(self, pat);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        walk_type_mut(self, ty);
+    }
+
+    fn visit_ident_mut(&mut self, _ident: &mut Ident) {}
+}
+
+pub fn walk_program_mut<V: VisitorMut>(visitor: &mut V, program: &mut Program) {
+    for class in &mut program.classes {
+        visitor.visit_class_mut(class);
+    }
+}
+
+pub fn walk_class_mut<V: VisitorMut>(visitor: &mut V, class: &mut Class) {
+    visitor.visit_type_mut(&mut class.name);
+    if let Some(parent) = &mut class.inherits {
+        visitor.visit_type_mut(parent);
+    }
+    for feature in &mut class.features.0 {
+        visitor.visit_feature_mut(feature);
+    }
+}
+
+pub fn walk_feature_mut<V: VisitorMut>(visitor: &mut V, feature: &mut Feature) {
+    match feature {
+        Feature::Attribute(attribute) => visitor.visit_attribute_mut(attribute),
+        Feature::Method(method) => visitor.visit_method_mut(method),
+    }
+}
+
+pub fn walk_attribute_mut<V: VisitorMut>(visitor: &mut V, attribute: &mut Attribute) {
+    visitor.visit_ident_mut(&mut attribute.name);
+    visitor.visit_type_mut(&mut attribute.ty);
+    if let Some(initializer) = &mut attribute.initializer {
+        visitor.visit_expr_mut(initializer);
+    }
+}
+
+pub fn walk_method_mut<V: VisitorMut>(visitor: &mut V, method: &mut Method) {
+    visitor.visit_ident_mut(&mut method.name);
+    for param in &mut method.params.0 {
+        visitor.visit_param_mut(param);
+    }
+    visitor.visit_type_mut(&mut method.return_ty);
+    visitor.visit_expr_mut(&mut method.body);
+}
+
+pub fn walk_param_mut<V: VisitorMut>(visitor: &mut V, param: &mut Param) {
+    visitor.visit_ident_mut(&mut param.name);
+    visitor.visit_type_mut(&mut param.ty);
+}
+
+pub fn walk_expr_mut<V: VisitorMut>(visitor: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Assignment { name, right } => {
+            visitor.visit_ident_mut(name);
+            visitor.visit_expr_mut(right);
+        }
+        ExprKind::Dispatch {
+            qualifier,
+            method,
+            args,
+        } => {
+            if let Some(qualifier) = qualifier {
+                visitor.visit_qualifier_mut(qualifier);
+            }
+            visitor.visit_ident_mut(method);
+            for arg in args {
+                visitor.visit_expr_mut(arg);
+            }
+        }
+        ExprKind::Conditional {
+            condition,
+            consequence,
+            alternative,
+        } => {
+            visitor.visit_expr_mut(condition);
+            visitor.visit_expr_mut(consequence);
+            visitor.visit_expr_mut(alternative);
+        }
+        ExprKind::Repeat { condition, body } => {
+            visitor.visit_expr_mut(condition);
+            visitor.visit_expr_mut(body);
+        }
+        ExprKind::Block { body } => {
+            for expr in body {
+                visitor.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::Let { bindings, body } => {
+            for binding in &mut bindings.0 {
+                visitor.visit_let_binding_mut(binding);
+            }
+            visitor.visit_expr_mut(body);
+        }
+        ExprKind::Case { value, body } => {
+            visitor.visit_expr_mut(value);
+            for arm in body {
+                visitor.visit_case_arm_mut(arm);
+            }
+        }
+        ExprKind::New { ty } => visitor.visit_type_mut(ty),
+        ExprKind::Unary { right, .. } => visitor.visit_expr_mut(right),
+        ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr_mut(left);
+            visitor.visit_expr_mut(right);
+        }
+        ExprKind::Paren { value } => visitor.visit_expr_mut(value),
+        ExprKind::Ident(ident) => visitor.visit_ident_mut(ident),
+        ExprKind::String(_) | ExprKind::Int(_) | ExprKind::Bool(_) => {}
+    }
+}
+
+pub fn walk_qualifier_mut<V: VisitorMut>(visitor: &mut V, qualifier: &mut Qualifier) {
+    visitor.visit_expr_mut(&mut qualifier.value);
+    if let Some(parent) = &mut qualifier.parent {
+        visitor.visit_type_mut(parent);
+    }
+}
+
+pub fn walk_let_binding_mut<V: VisitorMut>(visitor: &mut V, binding: &mut LetBinding) {
+    visitor.visit_ident_mut(&mut binding.name);
+    visitor.visit_type_mut(&mut binding.ty);
+    if let Some(right) = &mut binding.right {
+        visitor.visit_expr_mut(right);
+    }
+}
+
+pub fn walk_case_arm_mut<V: VisitorMut>(visitor: &mut V, arm: &mut CaseArm) {
+    visitor.visit_case_pattern_mut(&mut arm.pat);
+    visitor.visit_expr_mut(&mut arm.value);
+}
+
+pub fn walk_case_pattern_mut<V: VisitorMut>(visitor: &mut V, pat: &mut CasePattern) {
+    visitor.visit_ident_mut(&mut pat.name);
+    visitor.visit_type_mut(&mut pat.ty);
+}
+
+pub fn walk_type_mut<V: VisitorMut>(visitor: &mut V, ty: &mut Type) {
+    visitor.visit_ident_mut(&mut ty.0);
+}
@@ -0,0 +1,8 @@
+pub use self::{constants::Constants, layout::Layout};
+
+pub mod constants;
+pub mod layout;
+pub mod llvm;
+pub mod mips;
+pub mod wasm;
+pub mod x86;
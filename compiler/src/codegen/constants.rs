@@ -0,0 +1,43 @@
+use rustc_hash::FxHashMap;
+
+/// The `String` and `Int` constants a program refers to, each identified by
+/// the index its label is derived from.
+#[derive(Debug, Default)]
+pub struct Constants {
+    strings: Vec<String>,
+    string_indices: FxHashMap<String, usize>,
+    ints: Vec<i32>,
+}
+
+impl Constants {
+    pub fn string(&mut self, value: &str) -> usize {
+        if let Some(&idx) = self.string_indices.get(value) {
+            return idx;
+        }
+        self.strings.push(value.to_owned());
+        self.string_indices
+            .insert(value.to_owned(), self.strings.len() - 1);
+
+        self.strings.len() - 1
+    }
+
+    pub fn int(&mut self, value: i32) -> usize {
+        match self.ints.iter().position(|&int| int == value) {
+            Some(idx) => idx,
+            None => {
+                self.ints.push(value);
+                self.ints.len() - 1
+            }
+        }
+    }
+
+    /// String constants, in the order their indices were handed out.
+    pub fn strings(&self) -> &[String] {
+        &self.strings
+    }
+
+    /// Int constants, in the order their indices were handed out.
+    pub fn ints(&self) -> &[i32] {
+        &self.ints
+    }
+}
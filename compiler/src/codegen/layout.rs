@@ -0,0 +1,88 @@
+use std::ops::RangeInclusive;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast,
+    semantic::{ClassTable, Hierarchy, MethodSig},
+    util::interner::InternedIdx,
+};
+
+/// Runtime representation of the classes of a program, shared by the
+/// backends.
+///
+/// Tags and dispatch tables are those of the [`Hierarchy`] of the classes,
+/// objects add the attributes of each class after those of its ancestors.
+#[derive(Debug)]
+pub struct Layout<'c, 'a> {
+    hierarchy: Hierarchy<'c, 'a>,
+    /// Attributes of each class and its ancestors, inherited ones first.
+    attributes: FxHashMap<InternedIdx, Vec<&'a ast::Attribute>>,
+}
+
+impl<'c, 'a> Layout<'c, 'a> {
+    pub fn new(classes: &'c ClassTable<'a>) -> Layout<'c, 'a> {
+        let hierarchy = Hierarchy::new(classes);
+
+        // Parents come before their children in tag order.
+        let mut attributes: FxHashMap<InternedIdx, Vec<&'a ast::Attribute>> = FxHashMap::default();
+        for &class in hierarchy.order() {
+            let info = classes.get(class).unwrap();
+            let mut own = match info.parent {
+                Some(parent) => attributes[&parent].clone(),
+                None => Vec::new(),
+            };
+            own.extend(info.attributes.iter().copied());
+            attributes.insert(class, own);
+        }
+
+        Layout {
+            hierarchy,
+            attributes,
+        }
+    }
+
+    pub fn hierarchy(&self) -> &Hierarchy<'c, 'a> {
+        &self.hierarchy
+    }
+
+    pub fn classes(&self) -> &'c ClassTable<'a> {
+        self.hierarchy.classes()
+    }
+
+    /// Every class, in tag order.
+    pub fn order(&self) -> &[InternedIdx] {
+        self.hierarchy.order()
+    }
+
+    pub fn tag(&self, class: InternedIdx) -> usize {
+        self.hierarchy.tag(class)
+    }
+
+    /// Tags of the class and all of its descendants.
+    pub fn tag_range(&self, class: InternedIdx) -> RangeInclusive<usize> {
+        self.hierarchy.tag_range(class)
+    }
+
+    pub fn depth(&self, class: InternedIdx) -> usize {
+        self.hierarchy.depth(class)
+    }
+
+    pub fn attributes(&self, class: InternedIdx) -> &[&'a ast::Attribute] {
+        &self.attributes[&class]
+    }
+
+    pub fn attribute_index(&self, class: InternedIdx, name: InternedIdx) -> Option<usize> {
+        self.attributes(class)
+            .iter()
+            .position(|attribute| attribute.name.name == name)
+    }
+
+    pub fn methods(&self, class: InternedIdx) -> &[&'c MethodSig<'a>] {
+        self.hierarchy.methods(class)
+    }
+
+    pub fn method_index(&self, class: InternedIdx, name: InternedIdx) -> Option<usize> {
+        self.hierarchy.method_index(class, name)
+    }
+}
@@ -0,0 +1,864 @@
+//! Code generation for LLVM, as textual IR with opaque pointers.
+//!
+//! Objects share the layout of the x86-64 backend, so that the output can be
+//! compiled with `llc` and linked with the same runtime: every class is a
+//! named struct made of its tag, its size in words, a pointer to its dispatch
+//! table and its attributes. Dispatch tables are constant arrays of function
+//! pointers, and `case` is lowered to range checks on the tag of the value.
+
+use std::fmt::Write;
+
+use crate::{
+    ast::BinaryOp,
+    codegen::{Constants, Layout},
+    ir::{self, BlockId, Function, FunctionKind, Inst, Operand, Terminator, UnaryOp},
+    semantic::{ClassTable, MethodSig},
+    util::{
+        interner::{InternedIdx, Interner, sym},
+        source::SourceMap,
+        span::Span,
+    },
+};
+
+macro_rules! emit {
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, $($arg)*).unwrap()
+    };
+}
+
+/// Fields before the attributes of an object: tag, size and dispatch table.
+const HEADER_FIELDS: usize = 3;
+
+/// Routines of the runtime reporting errors, as `(name, parameters)`.
+const RUNTIME_ERRORS: [(&str, &str); 4] = [
+    ("cool_dispatch_abort", "ptr, i64"),
+    ("cool_case_abort", "ptr"),
+    ("cool_case_abort2", "ptr, i64"),
+    ("cool_division_by_zero", "ptr, i64"),
+];
+
+/// Generates LLVM IR for the IR of a program.
+///
+/// The name of the file of an expression, without its directories, is
+/// reported by the runtime along with the line of a runtime error.
+pub fn generate(
+    program: &ir::Program,
+    classes: &ClassTable,
+    interner: &Interner,
+    sources: &SourceMap,
+) -> String {
+    let layout = Layout::new(classes);
+    let mut generator = Generator {
+        layout: &layout,
+        interner,
+        out: String::new(),
+        constants: Constants::default(),
+        body: String::new(),
+        allocas: String::new(),
+        temps: 0,
+        labels: 0,
+        class: sym::OBJECT,
+        sources,
+        filenames: Vec::new(),
+    };
+    generator.filenames = sources
+        .files()
+        .map(|(_, file)| generator.constants.string(file.file_name()))
+        .collect();
+
+    generator.functions(program);
+    let functions = std::mem::take(&mut generator.out);
+    generator.globals();
+
+    generator.out + &functions
+}
+
+struct Generator<'g, 'c, 'a> {
+    layout: &'g Layout<'c, 'a>,
+    interner: &'g Interner,
+    out: String,
+    constants: Constants,
+    /// Instructions of the function being generated.
+    body: String,
+    /// Stack slots of the function being generated, hoisted to its entry
+    /// block.
+    allocas: String,
+    temps: usize,
+    labels: usize,
+    /// The class of the function being generated.
+    class: InternedIdx,
+    sources: &'g SourceMap,
+    /// String constants holding the names of the source files.
+    filenames: Vec<usize>,
+}
+
+impl Generator<'_, '_, '_> {
+    fn globals(&mut self) {
+        let layout = self.layout;
+        let classes = layout.order();
+
+        // Every constant must be known before the tables are written out.
+        let interner = self.interner;
+        let names: Vec<_> = classes
+            .iter()
+            .map(|class| self.constants.string(interner.lookup(class)))
+            .collect();
+        let empty = self.constants.string("");
+
+        for &class in classes {
+            let fields = match class {
+                sym::INT | sym::BOOL => vec!["i64"],
+                sym::STRING => vec!["i64", "ptr"],
+                _ => vec!["ptr"; layout.attributes(class).len()],
+            };
+            let fields: Vec<_> = ["i64", "i64", "ptr"].into_iter().chain(fields).collect();
+            emit!(
+                self.out,
+                "%class.{} = type {{ {} }}",
+                self.interner.lookup(&class),
+                fields.join(", ")
+            );
+        }
+        self.out.push('\n');
+
+        for (label, class) in [
+            ("_int_tag", sym::INT),
+            ("_bool_tag", sym::BOOL),
+            ("_string_tag", sym::STRING),
+        ] {
+            emit!(self.out, "@{} = constant i64 {}", label, layout.tag(class));
+        }
+        self.constants_data();
+
+        let names: Vec<_> = names
+            .iter()
+            .map(|idx| format!("ptr @str_const{}", idx))
+            .collect();
+        emit!(
+            self.out,
+            "@class_nameTab = constant [{} x ptr] [{}]",
+            names.len(),
+            names.join(", ")
+        );
+        let entries: Vec<_> = classes
+            .iter()
+            .flat_map(|class| {
+                let name = self.interner.lookup(class);
+                [
+                    format!("ptr @{}_protObj", name),
+                    format!("ptr @{}_init", name),
+                ]
+            })
+            .collect();
+        emit!(
+            self.out,
+            "@class_objTab = constant [{} x ptr] [{}]",
+            entries.len(),
+            entries.join(", ")
+        );
+
+        for &class in classes {
+            let methods: Vec<_> = layout
+                .methods(class)
+                .iter()
+                .map(|method| format!("ptr {}", self.method_name(method)))
+                .collect();
+            emit!(
+                self.out,
+                "@{}_dispTab = constant [{} x ptr] [{}]",
+                self.interner.lookup(&class),
+                methods.len(),
+                methods.join(", ")
+            );
+        }
+        for &class in classes {
+            self.prototype(class, empty);
+        }
+        self.out.push('\n');
+
+        self.declarations();
+    }
+
+    fn constants_data(&mut self) {
+        let string_tag = self.layout.tag(sym::STRING);
+        let int_tag = self.layout.tag(sym::INT);
+        let bool_tag = self.layout.tag(sym::BOOL);
+
+        for (idx, value) in self.constants.strings().iter().enumerate() {
+            emit!(
+                self.out,
+                "@str_const{} = constant %class.String {{ i64 {}, i64 {}, ptr @String_dispTab, i64 {}, ptr @str_chars{} }}",
+                idx,
+                string_tag,
+                HEADER_FIELDS + 2,
+                value.len(),
+                idx
+            );
+            emit!(
+                self.out,
+                "@str_chars{} = private unnamed_addr constant [{} x i8] c\"{}\\00\"",
+                idx,
+                value.len() + 1,
+                escape(value.as_bytes())
+            );
+        }
+        for (idx, value) in self.constants.ints().iter().enumerate() {
+            emit!(
+                self.out,
+                "@int_const{} = constant %class.Int {{ i64 {}, i64 {}, ptr @Int_dispTab, i64 {} }}",
+                idx,
+                int_tag,
+                HEADER_FIELDS + 1,
+                value
+            );
+        }
+        for value in 0..2 {
+            emit!(
+                self.out,
+                "@bool_const{} = constant %class.Bool {{ i64 {}, i64 {}, ptr @Bool_dispTab, i64 {} }}",
+                value,
+                bool_tag,
+                HEADER_FIELDS + 1,
+                value
+            );
+        }
+    }
+
+    /// The object `new` copies before running the initializer of the class.
+    fn prototype(&mut self, class: InternedIdx, empty: usize) {
+        let layout = self.layout;
+        let fields: Vec<String> = match class {
+            sym::INT | sym::BOOL => vec!["i64 0".to_owned()],
+            sym::STRING => vec!["i64 0".to_owned(), format!("ptr @str_chars{}", empty)],
+            _ => layout
+                .attributes(class)
+                .iter()
+                .map(|attribute| format!("ptr {}", self.default_value(attribute.ty.0.name)))
+                .collect(),
+        };
+        let name = self.interner.lookup(&class);
+
+        emit!(
+            self.out,
+            "@{}_protObj = constant %class.{} {{ i64 {}, i64 {}, ptr @{}_dispTab{}{} }}",
+            name,
+            name,
+            layout.tag(class),
+            HEADER_FIELDS + fields.len(),
+            name,
+            if fields.is_empty() { "" } else { ", " },
+            fields.join(", ")
+        );
+    }
+
+    /// Declares the built-in methods and the error routines of the runtime.
+    fn declarations(&mut self) {
+        let layout = self.layout;
+
+        for &class in layout.order() {
+            let methods = &layout.classes().get(class).unwrap().methods;
+
+            for method in methods.iter().filter(|method| method.decl.is_none()) {
+                let params = vec!["ptr"; 1 + method.params.len()];
+                emit!(
+                    self.out,
+                    "declare ptr {}({})",
+                    self.method_name(method),
+                    params.join(", ")
+                );
+            }
+        }
+        emit!(self.out, "declare ptr @cool_equals(ptr, ptr)");
+        for (name, params) in RUNTIME_ERRORS {
+            emit!(self.out, "declare void @{}({}) noreturn", name, params);
+        }
+        self.out.push('\n');
+    }
+
+    fn functions(&mut self, program: &ir::Program) {
+        let layout = self.layout;
+
+        for &class in layout.order() {
+            let empty = Function::empty_init(class);
+            let function = program.init(class).unwrap_or(&empty);
+            let name = format!("@{}_init", self.interner.lookup(&class));
+
+            // The attributes of the ancestors are initialized first.
+            let parent = layout.classes().get(class).unwrap().parent;
+            self.function(&name, function, |this| {
+                if let Some(parent) = parent {
+                    let parent = this.interner.lookup(&parent);
+                    emit!(this.body, "  call ptr @{}_init(ptr %self)", parent);
+                }
+            });
+        }
+        for function in &program.functions {
+            if let FunctionKind::Method(name) = function.kind {
+                let name = format!(
+                    "@\"{}.{}\"",
+                    self.interner.lookup(&function.class),
+                    self.interner.lookup(&name)
+                );
+                self.function(&name, function, |_| {});
+            }
+        }
+    }
+
+    /// Emits a function taking `self` and its parameters, running `prologue`
+    /// in its entry block. Every temporary has a stack slot, so that it can
+    /// be assigned more than once.
+    fn function(&mut self, name: &str, function: &Function, prologue: impl FnOnce(&mut Self)) {
+        self.temps = 0;
+        self.class = function.class;
+
+        let mut args = vec!["ptr %self".to_owned()];
+        for temp in 0..function.temps {
+            emit!(self.allocas, "  %slot{} = alloca ptr", temp);
+        }
+        for (idx, param) in function.params.iter().enumerate() {
+            args.push(format!("ptr %arg{}", idx));
+            emit!(
+                self.allocas,
+                "  store ptr %arg{}, ptr %slot{}",
+                idx,
+                param.0
+            );
+        }
+        prologue(self);
+
+        // The entry block cannot be branched to, unlike the first block of
+        // the function.
+        emit!(self.body, "  br label %b0");
+        for (idx, block) in function.blocks.iter().enumerate() {
+            self.start_block(&format!("b{}", idx));
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            self.terminator(&block.terminator);
+        }
+
+        emit!(self.out, "define ptr {}({}) {{", name, args.join(", "));
+        emit!(self.out, "entry:");
+        self.out.push_str(&std::mem::take(&mut self.allocas));
+        self.out.push_str(&std::mem::take(&mut self.body));
+        emit!(self.out, "}}");
+        self.out.push('\n');
+    }
+
+    fn inst(&mut self, inst: &Inst) {
+        let value = match inst {
+            Inst::Copy { value, .. } => self.operand(value),
+            Inst::Unary { op, value, .. } => {
+                let value = self.operand(value);
+
+                match op {
+                    UnaryOp::Complement => {
+                        let int = self.int_value(&value);
+                        let negated = self.temp();
+                        emit!(self.body, "  {} = sub i32 0, {}", negated, int);
+                        self.new_int(&value, &negated)
+                    }
+                    UnaryOp::Not => {
+                        let truth = self.bool_value(&value);
+                        let negated = self.temp();
+                        emit!(self.body, "  {} = xor i1 {}, true", negated, truth);
+                        self.select_bool(&negated)
+                    }
+                }
+            }
+            Inst::IsVoid { value, .. } => {
+                let value = self.operand(value);
+                let void = self.temp();
+                emit!(self.body, "  {} = icmp eq ptr {}, null", void, value);
+                self.select_bool(&void)
+            }
+            Inst::Binary {
+                op,
+                left,
+                right,
+                span,
+                ..
+            } => {
+                let left = self.operand(left);
+                let right = self.operand(right);
+
+                self.binary(*op, &left, &right, *span)
+            }
+            Inst::GetAttr { name, .. } => {
+                let field = self.attribute_field(*name);
+                let value = self.temp();
+                emit!(self.body, "  {} = load ptr, ptr {}", value, field);
+                value
+            }
+            Inst::SetAttr { name, value } => {
+                let value = self.operand(value);
+                let field = self.attribute_field(*name);
+                emit!(self.body, "  store ptr {}, ptr {}", value, field);
+                return;
+            }
+            Inst::Dispatch {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                let receiver = self.receiver(receiver, *span);
+                let table = self.dispatch_table(&receiver);
+                let idx = self.layout.method_index(*class, *method).unwrap();
+                let slot = self.temp();
+                let function = self.temp();
+                emit!(
+                    self.body,
+                    "  {} = getelementptr inbounds ptr, ptr {}, i64 {}",
+                    slot,
+                    table,
+                    idx
+                );
+                emit!(self.body, "  {} = load ptr, ptr {}", function, slot);
+
+                self.call(&function, &receiver, args)
+            }
+            Inst::Call {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                let receiver = self.receiver(receiver, *span);
+                let target = self.layout.classes().lookup_method(*class, *method);
+                let function = self.method_name(target.unwrap());
+
+                self.call(&function, &receiver, args)
+            }
+            Inst::New { class, .. } => self.new_object(*class),
+        };
+
+        if let Some(dest) = inst.dest() {
+            emit!(self.body, "  store ptr {}, ptr %slot{}", value, dest.0);
+        }
+    }
+
+    /// The value of the receiver of a call, checked not to be void unless
+    /// it is `self`.
+    fn receiver(&mut self, receiver: &Operand, span: Span) -> String {
+        let value = self.operand(receiver);
+        if *receiver != Operand::SelfObject {
+            self.check_void(&value, "cool_dispatch_abort", span);
+        }
+        value
+    }
+
+    fn call(&mut self, function: &str, receiver: &str, args: &[Operand]) -> String {
+        let args: Vec<_> = args
+            .iter()
+            .map(|arg| format!("ptr {}", self.operand(arg)))
+            .collect();
+        let value = self.temp();
+        emit!(
+            self.body,
+            "  {} = call ptr {}(ptr {}{}{})",
+            value,
+            function,
+            receiver,
+            if args.is_empty() { "" } else { ", " },
+            args.join(", ")
+        );
+
+        value
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: &str, right: &str, span: Span) -> String {
+        if let BinaryOp::Eq = op {
+            let same = self.temp();
+            let equal = self.temp();
+            emit!(self.body, "  {} = icmp eq ptr {}, {}", same, left, right);
+            emit!(
+                self.body,
+                "  {} = call ptr @cool_equals(ptr {}, ptr {})",
+                equal,
+                left,
+                right
+            );
+
+            let value = self.temp();
+            emit!(
+                self.body,
+                "  {} = select i1 {}, ptr @bool_const1, ptr {}",
+                value,
+                same,
+                equal
+            );
+            return value;
+        }
+
+        let left_value = self.int_value(left);
+        let right_value = self.int_value(right);
+        let result = self.temp();
+
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                let inst = match op {
+                    BinaryOp::Add => "add",
+                    BinaryOp::Sub => "sub",
+                    _ => "mul",
+                };
+                emit!(
+                    self.body,
+                    "  {} = {} i32 {}, {}",
+                    result,
+                    inst,
+                    left_value,
+                    right_value
+                );
+                // The result is a fresh copy of the right operand.
+                self.new_int(right, &result)
+            }
+            BinaryOp::Div => {
+                let zero = self.temp();
+                emit!(self.body, "  {} = icmp eq i32 {}, 0", zero, right_value);
+                self.check(&zero, "cool_division_by_zero", span);
+
+                // `sdiv` is undefined for INT_MIN / -1, so a divisor of -1
+                // negates instead, which wraps like the interpreter.
+                let (minus_one, divisor, quotient, negated) =
+                    (self.temp(), self.temp(), self.temp(), self.temp());
+                emit!(
+                    self.body,
+                    "  {} = icmp eq i32 {}, -1",
+                    minus_one,
+                    right_value
+                );
+                emit!(
+                    self.body,
+                    "  {} = select i1 {}, i32 1, i32 {}",
+                    divisor,
+                    minus_one,
+                    right_value
+                );
+                emit!(
+                    self.body,
+                    "  {} = sdiv i32 {}, {}",
+                    quotient,
+                    left_value,
+                    divisor
+                );
+                emit!(self.body, "  {} = sub i32 0, {}", negated, left_value);
+                emit!(
+                    self.body,
+                    "  {} = select i1 {}, i32 {}, i32 {}",
+                    result,
+                    minus_one,
+                    negated,
+                    quotient
+                );
+                self.new_int(right, &result)
+            }
+            _ => {
+                let predicate = if let BinaryOp::Lt = op { "slt" } else { "sle" };
+                emit!(
+                    self.body,
+                    "  {} = icmp {} i32 {}, {}",
+                    result,
+                    predicate,
+                    left_value,
+                    right_value
+                );
+                self.select_bool(&result)
+            }
+        }
+    }
+
+    fn terminator(&mut self, terminator: &Terminator) {
+        match terminator {
+            Terminator::Jump(target) => emit!(self.body, "  br label %b{}", target.0),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let condition = self.operand(condition);
+                let condition = self.bool_value(&condition);
+                emit!(
+                    self.body,
+                    "  br i1 {}, label %b{}, label %b{}",
+                    condition,
+                    then.0,
+                    otherwise.0
+                );
+            }
+            Terminator::TypeCase { value, arms, span } => self.case(value, arms, *span),
+            Terminator::Return(value) => {
+                let value = self.operand(value);
+                emit!(self.body, "  ret ptr {}", value);
+            }
+        }
+    }
+
+    /// Branches to the arm whose class is the closest ancestor of the
+    /// dynamic type of the value, by trying the most specific classes first.
+    fn case(&mut self, value: &Operand, arms: &[(InternedIdx, BlockId)], span: Span) {
+        let value = self.operand(value);
+        self.check_void(&value, "cool_case_abort2", span);
+
+        let tag = self.tag(&value);
+
+        let mut arms = arms.to_vec();
+        arms.sort_by_key(|&(class, _)| std::cmp::Reverse(self.layout.depth(class)));
+
+        for (class, block) in arms {
+            let next = self.label();
+            let tags = self.layout.tag_range(class);
+
+            let (above, below, within) = (self.temp(), self.temp(), self.temp());
+            emit!(
+                self.body,
+                "  {} = icmp sge i64 {}, {}",
+                above,
+                tag,
+                tags.start()
+            );
+            emit!(
+                self.body,
+                "  {} = icmp sle i64 {}, {}",
+                below,
+                tag,
+                tags.end()
+            );
+            emit!(self.body, "  {} = and i1 {}, {}", within, above, below);
+            emit!(
+                self.body,
+                "  br i1 {}, label %b{}, label %{}",
+                within,
+                block.0,
+                next
+            );
+
+            self.start_block(&next);
+        }
+        emit!(self.body, "  call void @cool_case_abort(ptr {})", value);
+        emit!(self.body, "  unreachable");
+    }
+
+    fn new_object(&mut self, class: InternedIdx) -> String {
+        let (prototype, init) = if class == sym::SELF_TYPE {
+            // Prototypes and initializers are indexed by tag in
+            // `class_objTab`.
+            let tag = self.tag("%self");
+            let idx = self.temp();
+            emit!(self.body, "  {} = mul i64 {}, 2", idx, tag);
+
+            let [prototype, init] = [0, 1].map(|offset| {
+                let slot = self.temp();
+                let value = self.temp();
+                emit!(
+                    self.body,
+                    "  {} = getelementptr inbounds ptr, ptr @class_objTab, i64 {}",
+                    slot,
+                    idx
+                );
+                if offset == 1 {
+                    // Skip to the initializer following the prototype.
+                    let next = self.temp();
+                    emit!(
+                        self.body,
+                        "  {} = getelementptr inbounds ptr, ptr {}, i64 1",
+                        next,
+                        slot
+                    );
+                    emit!(self.body, "  {} = load ptr, ptr {}", value, next);
+                } else {
+                    emit!(self.body, "  {} = load ptr, ptr {}", value, slot);
+                }
+                value
+            });
+            (prototype, init)
+        } else {
+            let name = self.interner.lookup(&class);
+            (format!("@{}_protObj", name), format!("@{}_init", name))
+        };
+
+        let object = self.temp();
+        let value = self.temp();
+        emit!(
+            self.body,
+            "  {} = call ptr @\"Object.copy\"(ptr {})",
+            object,
+            prototype
+        );
+        emit!(self.body, "  {} = call ptr {}(ptr {})", value, init, object);
+
+        value
+    }
+
+    /// Copies the `Int` object `template` with `value` as its value.
+    fn new_int(&mut self, template: &str, value: &str) -> String {
+        let object = self.temp();
+        emit!(
+            self.body,
+            "  {} = call ptr @\"Object.copy\"(ptr {})",
+            object,
+            template
+        );
+        let field = self.field("Int", &object, HEADER_FIELDS);
+        emit!(self.body, "  store i32 {}, ptr {}", value, field);
+
+        object
+    }
+
+    fn int_value(&mut self, object: &str) -> String {
+        let field = self.field("Int", object, HEADER_FIELDS);
+        let value = self.temp();
+        emit!(self.body, "  {} = load i32, ptr {}", value, field);
+        value
+    }
+
+    fn bool_value(&mut self, object: &str) -> String {
+        let field = self.field("Bool", object, HEADER_FIELDS);
+        let (value, truth) = (self.temp(), self.temp());
+        emit!(self.body, "  {} = load i64, ptr {}", value, field);
+        emit!(self.body, "  {} = icmp ne i64 {}, 0", truth, value);
+        truth
+    }
+
+    fn tag(&mut self, object: &str) -> String {
+        let field = self.field("Object", object, 0);
+        let tag = self.temp();
+        emit!(self.body, "  {} = load i64, ptr {}", tag, field);
+        tag
+    }
+
+    fn dispatch_table(&mut self, object: &str) -> String {
+        let field = self.field("Object", object, 2);
+        let table = self.temp();
+        emit!(self.body, "  {} = load ptr, ptr {}", table, field);
+        table
+    }
+
+    fn field(&mut self, class: &str, object: &str, idx: usize) -> String {
+        let field = self.temp();
+        emit!(
+            self.body,
+            "  {} = getelementptr inbounds %class.{}, ptr {}, i32 0, i32 {}",
+            field,
+            class,
+            object,
+            idx
+        );
+        field
+    }
+
+    fn select_bool(&mut self, condition: &str) -> String {
+        let value = self.temp();
+        emit!(
+            self.body,
+            "  {} = select i1 {}, ptr @bool_const1, ptr @bool_const0",
+            value,
+            condition
+        );
+        value
+    }
+
+    fn check_void(&mut self, value: &str, routine: &str, span: Span) {
+        let void = self.temp();
+        emit!(self.body, "  {} = icmp eq ptr {}, null", void, value);
+        self.check(&void, routine, span);
+    }
+
+    /// Calls a runtime routine taking the file name and the line of `span`
+    /// when `failed` holds.
+    fn check(&mut self, failed: &str, routine: &str, span: Span) {
+        let (error, ok) = (self.label(), self.label());
+        emit!(
+            self.body,
+            "  br i1 {}, label %{}, label %{}",
+            failed,
+            error,
+            ok
+        );
+
+        self.start_block(&error);
+        emit!(
+            self.body,
+            "  call void @{}(ptr @str_chars{}, i64 {})",
+            routine,
+            self.filenames[span.file.index()],
+            self.sources.line(span)
+        );
+        emit!(self.body, "  unreachable");
+
+        self.start_block(&ok);
+    }
+
+    /// The value attributes of a type start with.
+    fn default_value(&mut self, ty: InternedIdx) -> String {
+        match ty {
+            sym::INT => format!("@int_const{}", self.constants.int(0)),
+            sym::STRING => format!("@str_const{}", self.constants.string("")),
+            sym::BOOL => "@bool_const0".to_owned(),
+            _ => "null".to_owned(),
+        }
+    }
+
+    fn operand(&mut self, operand: &Operand) -> String {
+        match operand {
+            Operand::Temp(temp) => {
+                let value = self.temp();
+                emit!(self.body, "  {} = load ptr, ptr %slot{}", value, temp.0);
+                value
+            }
+            Operand::SelfObject => "%self".to_owned(),
+            Operand::Int(value) => format!("@int_const{}", self.constants.int(*value)),
+            Operand::Bool(value) => format!("@bool_const{}", *value as u8),
+            Operand::String(value) => format!("@str_const{}", self.constants.string(value)),
+            Operand::Void => "null".to_owned(),
+        }
+    }
+
+    fn attribute_field(&mut self, name: InternedIdx) -> String {
+        let idx = self.layout.attribute_index(self.class, name).unwrap();
+        let class = self.interner.lookup(&self.class);
+
+        self.field(class, "%self", HEADER_FIELDS + idx)
+    }
+
+    fn temp(&mut self) -> String {
+        self.temps += 1;
+        format!("%t{}", self.temps - 1)
+    }
+
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!("L{}", self.labels - 1)
+    }
+
+    fn start_block(&mut self, label: &str) {
+        emit!(self.body, "{}:", label);
+    }
+
+    fn method_name(&self, method: &MethodSig) -> String {
+        format!(
+            "@\"{}.{}\"",
+            self.interner.lookup(&method.class),
+            self.interner.lookup(&method.name)
+        )
+    }
+}
+
+/// Escapes bytes for a `c"..."` string, writing anything but printable
+/// characters as hexadecimal escapes.
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+
+    for &byte in bytes {
+        if (b' '..=b'~').contains(&byte) && !matches!(byte, b'"' | b'\\') {
+            escaped.push(byte as char);
+        } else {
+            write!(escaped, "\\{:02X}", byte).unwrap();
+        }
+    }
+    escaped
+}
@@ -0,0 +1,653 @@
+//! Code generation for MIPS, targeting the SPIM simulator and the standard
+//! COOL runtime (`trap.handler`).
+//!
+//! Objects are laid out as the runtime expects: a `-1` eye catcher precedes
+//! every object, followed by its class tag, its size in words, a pointer to
+//! its dispatch table and its attributes. Methods receive `self` in `$a0`
+//! and their arguments on the stack, in order, and return their result in
+//! `$a0`. Every method saves `$fp`, `$s0` and `$ra` and pops its own
+//! arguments.
+
+use std::fmt::Write;
+
+use crate::{
+    ast::BinaryOp,
+    codegen::{Constants, Layout},
+    ir::{self, BlockId, Function, FunctionKind, Inst, Operand, Terminator, UnaryOp},
+    semantic::{ClassTable, MethodSig},
+    util::{
+        interner::{InternedIdx, Interner, sym},
+        source::SourceMap,
+        span::Span,
+    },
+};
+
+macro_rules! emit {
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, $($arg)*).unwrap()
+    };
+}
+
+const WORD: i32 = 4;
+
+/// Words before the attributes of an object: tag, size and dispatch table.
+const HEADER_WORDS: i32 = 3;
+
+const TAG_OFFSET: i32 = 0;
+const DISPATCH_OFFSET: i32 = 8;
+/// Offset of the value of `Int` and `Bool` objects, and of the length of
+/// `String` objects.
+const VALUE_OFFSET: i32 = 12;
+
+/// Generates SPIM assembly for the IR of a program.
+///
+/// The name of the file of an expression, without its directories, is
+/// reported by the runtime along with the line of a dispatch or `case` on
+/// void.
+pub fn generate(
+    program: &ir::Program,
+    classes: &ClassTable,
+    interner: &Interner,
+    sources: &SourceMap,
+) -> String {
+    let layout = Layout::new(classes);
+    let mut generator = Generator {
+        layout: &layout,
+        interner,
+        out: String::new(),
+        constants: Constants::default(),
+        labels: 0,
+        class: sym::OBJECT,
+        params: 0,
+        slots: Vec::new(),
+        blocks: Vec::new(),
+        sources,
+        filenames: Vec::new(),
+    };
+    generator.filenames = sources
+        .files()
+        .map(|(_, file)| generator.string_const(file.file_name()))
+        .collect();
+
+    generator.text(program);
+    let text = std::mem::take(&mut generator.out);
+    generator.data();
+
+    generator.out + &text
+}
+
+struct Generator<'g, 'c, 'a> {
+    layout: &'g Layout<'c, 'a>,
+    interner: &'g Interner,
+    out: String,
+    constants: Constants,
+    labels: usize,
+    /// The class of the function being generated.
+    class: InternedIdx,
+    /// Parameters of the function being generated, popped on return.
+    params: usize,
+    /// Offset from `$fp` of every temporary of the function being
+    /// generated.
+    slots: Vec<i32>,
+    /// Label of every block of the function being generated.
+    blocks: Vec<usize>,
+    sources: &'g SourceMap,
+    /// String constants holding the names of the source files.
+    filenames: Vec<usize>,
+}
+
+impl Generator<'_, '_, '_> {
+    fn data(&mut self) {
+        let layout = self.layout;
+        let classes = layout.order();
+
+        // Every constant must be known before the tables are written out.
+        let interner = self.interner;
+        let names: Vec<_> = classes
+            .iter()
+            .map(|class| self.string_const(interner.lookup(class)))
+            .collect();
+        self.string_const("");
+        self.int_const(0);
+        for idx in 0..self.constants.strings().len() {
+            self.int_const(self.constants.strings()[idx].len() as i32);
+        }
+
+        emit!(self.out, "\t.data");
+        emit!(self.out, "\t.align\t2");
+        for global in [
+            "class_nameTab",
+            "Main_protObj",
+            "Int_protObj",
+            "String_protObj",
+            "bool_const0",
+            "bool_const1",
+            "_int_tag",
+            "_bool_tag",
+            "_string_tag",
+        ] {
+            emit!(self.out, "\t.globl\t{}", global);
+        }
+        for (label, class) in [
+            ("_int_tag", sym::INT),
+            ("_bool_tag", sym::BOOL),
+            ("_string_tag", sym::STRING),
+        ] {
+            emit!(self.out, "{}:", label);
+            emit!(self.out, "\t.word\t{}", layout.tag(class));
+        }
+        for (label, value) in [
+            ("_MemMgr_INITIALIZER", "_NoGC_Init"),
+            ("_MemMgr_COLLECTOR", "_NoGC_Collect"),
+            ("_MemMgr_TEST", "0"),
+        ] {
+            emit!(self.out, "\t.globl\t{}", label);
+            emit!(self.out, "{}:", label);
+            emit!(self.out, "\t.word\t{}", value);
+        }
+
+        self.constants();
+
+        emit!(self.out, "class_nameTab:");
+        for name in names {
+            emit!(self.out, "\t.word\tstr_const{}", name);
+        }
+        emit!(self.out, "class_objTab:");
+        for &class in classes {
+            let name = self.interner.lookup(&class);
+            emit!(self.out, "\t.word\t{}_protObj", name);
+            emit!(self.out, "\t.word\t{}_init", name);
+        }
+
+        for &class in classes {
+            emit!(self.out, "{}_dispTab:", self.interner.lookup(&class));
+            for method in layout.methods(class) {
+                let label = self.method_label(method);
+                emit!(self.out, "\t.word\t{}", label);
+            }
+        }
+        for &class in classes {
+            self.prototype(class);
+        }
+
+        emit!(self.out, "\t.globl\theap_start");
+        emit!(self.out, "heap_start:");
+        emit!(self.out, "\t.word\t0");
+    }
+
+    fn constants(&mut self) {
+        let string_tag = self.layout.tag(sym::STRING);
+        let int_tag = self.layout.tag(sym::INT);
+        let bool_tag = self.layout.tag(sym::BOOL);
+
+        for (idx, value) in self.constants.strings().iter().enumerate() {
+            let len = self
+                .constants
+                .ints()
+                .iter()
+                .position(|&int| int == value.len() as i32);
+
+            emit!(self.out, "\t.word\t-1");
+            emit!(self.out, "str_const{}:", idx);
+            emit!(self.out, "\t.word\t{}", string_tag);
+            emit!(
+                self.out,
+                "\t.word\t{}",
+                HEADER_WORDS + 1 + (value.len() as i32 + 4) / 4
+            );
+            emit!(self.out, "\t.word\tString_dispTab");
+            emit!(self.out, "\t.word\tint_const{}", len.unwrap());
+            emit_ascii(&mut self.out, value.as_bytes());
+            emit!(self.out, "\t.byte\t0");
+            emit!(self.out, "\t.align\t2");
+        }
+        for (idx, value) in self.constants.ints().iter().enumerate() {
+            emit!(self.out, "\t.word\t-1");
+            emit!(self.out, "int_const{}:", idx);
+            emit!(self.out, "\t.word\t{}", int_tag);
+            emit!(self.out, "\t.word\t{}", HEADER_WORDS + 1);
+            emit!(self.out, "\t.word\tInt_dispTab");
+            emit!(self.out, "\t.word\t{}", value);
+        }
+        for value in 0..2 {
+            emit!(self.out, "\t.word\t-1");
+            emit!(self.out, "bool_const{}:", value);
+            emit!(self.out, "\t.word\t{}", bool_tag);
+            emit!(self.out, "\t.word\t{}", HEADER_WORDS + 1);
+            emit!(self.out, "\t.word\tBool_dispTab");
+            emit!(self.out, "\t.word\t{}", value);
+        }
+    }
+
+    /// The object `new` copies before running the initializer of the class.
+    fn prototype(&mut self, class: InternedIdx) {
+        let layout = self.layout;
+        let fields: Vec<String> = match class {
+            sym::INT | sym::BOOL => vec!["0".to_owned()],
+            sym::STRING => vec![format!("int_const{}", self.int_const(0)), "0".to_owned()],
+            _ => layout
+                .attributes(class)
+                .iter()
+                .map(|attribute| self.default_value(attribute.ty.0.name))
+                .collect(),
+        };
+
+        emit!(self.out, "\t.word\t-1");
+        emit!(self.out, "{}_protObj:", self.interner.lookup(&class));
+        emit!(self.out, "\t.word\t{}", self.layout.tag(class));
+        emit!(self.out, "\t.word\t{}", HEADER_WORDS + fields.len() as i32);
+        emit!(
+            self.out,
+            "\t.word\t{}_dispTab",
+            self.interner.lookup(&class)
+        );
+        for field in fields {
+            emit!(self.out, "\t.word\t{}", field);
+        }
+    }
+
+    fn text(&mut self, program: &ir::Program) {
+        emit!(self.out, "\t.globl\tMain_init");
+        emit!(self.out, "\t.globl\tInt_init");
+        emit!(self.out, "\t.globl\tString_init");
+        emit!(self.out, "\t.globl\tBool_init");
+        emit!(self.out, "\t.globl\tMain.main");
+        emit!(self.out, "\t.text");
+
+        let layout = self.layout;
+        for &class in layout.order() {
+            let empty = Function::empty_init(class);
+            let function = program.init(class).unwrap_or(&empty);
+            let label = format!("{}_init", self.interner.lookup(&class));
+
+            // The attributes of the ancestors are initialized first.
+            let parent = layout.classes().get(class).unwrap().parent;
+            self.function(&label, function, |this| {
+                if let Some(parent) = parent {
+                    emit!(this.out, "\tjal\t{}_init", this.interner.lookup(&parent));
+                }
+            });
+        }
+        for function in &program.functions {
+            if let FunctionKind::Method(name) = function.kind {
+                let label = format!(
+                    "{}.{}",
+                    self.interner.lookup(&function.class),
+                    self.interner.lookup(&name)
+                );
+                self.function(&label, function, |_| {});
+            }
+        }
+    }
+
+    /// Generates a function, running `prologue` once its frame is set up.
+    ///
+    /// `$fp` points to the saved `$ra`, with the saved `$s0` and `$fp` above
+    /// it, followed by the arguments. Temporaries other than the parameters
+    /// have a slot right below.
+    fn function(&mut self, label: &str, function: &Function, prologue: impl FnOnce(&mut Self)) {
+        let params = function.params.len();
+        let mut locals = 0;
+        self.class = function.class;
+        self.params = params;
+        self.slots = (0..function.temps)
+            .map(
+                |temp| match function.params.iter().position(|param| param.0 == temp) {
+                    // The first argument is pushed first, so it is the furthest
+                    // from the frame pointer.
+                    Some(idx) => 3 * WORD + WORD * (params - 1 - idx) as i32,
+                    None => {
+                        locals += 1;
+                        -WORD * locals
+                    }
+                },
+            )
+            .collect();
+        self.blocks = (0..function.blocks.len()).map(|_| self.label()).collect();
+
+        emit!(self.out, "{}:", label);
+        emit!(self.out, "\taddiu\t$sp $sp -12");
+        emit!(self.out, "\tsw\t$fp 12($sp)");
+        emit!(self.out, "\tsw\t$s0 8($sp)");
+        emit!(self.out, "\tsw\t$ra 4($sp)");
+        emit!(self.out, "\taddiu\t$fp $sp 4");
+        emit!(self.out, "\tmove\t$s0 $a0");
+        if locals > 0 {
+            emit!(self.out, "\taddiu\t$sp $sp {}", -WORD * locals);
+        }
+        prologue(self);
+
+        for (idx, block) in function.blocks.iter().enumerate() {
+            emit!(self.out, "label{}:", self.blocks[idx]);
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            self.terminator(&block.terminator, idx + 1);
+        }
+    }
+
+    /// Generates an instruction, storing the value it assigns from `$a0`.
+    fn inst(&mut self, inst: &Inst) {
+        match inst {
+            Inst::Copy { value, .. } => self.load("$a0", value),
+            Inst::Unary { op, value, .. } => {
+                self.load("$a0", value);
+
+                match op {
+                    UnaryOp::Complement => {
+                        emit!(self.out, "\tjal\tObject.copy");
+                        emit!(self.out, "\tlw\t$t1 {}($a0)", VALUE_OFFSET);
+                        emit!(self.out, "\tneg\t$t1 $t1");
+                        emit!(self.out, "\tsw\t$t1 {}($a0)", VALUE_OFFSET);
+                    }
+                    UnaryOp::Not => {
+                        emit!(self.out, "\tlw\t$t1 {}($a0)", VALUE_OFFSET);
+                        self.select_bool("beqz\t$t1");
+                    }
+                }
+            }
+            Inst::IsVoid { value, .. } => {
+                self.load("$t1", value);
+                self.select_bool("beqz\t$t1");
+            }
+            Inst::Binary {
+                op, left, right, ..
+            } => self.binary(*op, left, right),
+            Inst::GetAttr { name, .. } => {
+                let offset = self.attribute_offset(*name);
+                emit!(self.out, "\tlw\t$a0 {}($s0)", offset);
+            }
+            Inst::SetAttr { name, value } => {
+                self.load("$a0", value);
+                let offset = self.attribute_offset(*name);
+                emit!(self.out, "\tsw\t$a0 {}($s0)", offset);
+            }
+            Inst::Dispatch {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                self.receiver(receiver, args, *span);
+
+                let idx = self.layout.method_index(*class, *method).unwrap();
+                emit!(self.out, "\tlw\t$t1 {}($a0)", DISPATCH_OFFSET);
+                emit!(self.out, "\tlw\t$t1 {}($t1)", WORD * idx as i32);
+                emit!(self.out, "\tjalr\t$t1");
+            }
+            Inst::Call {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                self.receiver(receiver, args, *span);
+
+                let target = self.layout.classes().lookup_method(*class, *method);
+                emit!(self.out, "\tjal\t{}", self.method_label(target.unwrap()));
+            }
+            Inst::New { class, .. } => self.new_object(*class),
+        }
+
+        if let Some(dest) = inst.dest() {
+            emit!(self.out, "\tsw\t$a0 {}($fp)", self.slots[dest.0 as usize]);
+        }
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: &Operand, right: &Operand) {
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => {
+                let inst = match op {
+                    BinaryOp::Add => "add",
+                    BinaryOp::Sub => "sub",
+                    BinaryOp::Mul => "mul",
+                    _ => "div",
+                };
+                // The result is a fresh copy of the right operand.
+                self.load("$a0", right);
+                emit!(self.out, "\tjal\tObject.copy");
+                self.load("$t1", left);
+                emit!(self.out, "\tlw\t$t1 {}($t1)", VALUE_OFFSET);
+                emit!(self.out, "\tlw\t$t2 {}($a0)", VALUE_OFFSET);
+                emit!(self.out, "\t{}\t$t1 $t1 $t2", inst);
+                emit!(self.out, "\tsw\t$t1 {}($a0)", VALUE_OFFSET);
+            }
+            BinaryOp::Lt | BinaryOp::Lte => {
+                self.load("$t1", left);
+                self.load("$t2", right);
+                emit!(self.out, "\tlw\t$t1 {}($t1)", VALUE_OFFSET);
+                emit!(self.out, "\tlw\t$t2 {}($t2)", VALUE_OFFSET);
+                let branch = match op {
+                    BinaryOp::Lt => "blt\t$t1 $t2",
+                    _ => "ble\t$t1 $t2",
+                };
+                self.select_bool(branch);
+            }
+            BinaryOp::Eq => {
+                let end = self.label();
+
+                self.load("$t1", left);
+                self.load("$t2", right);
+                emit!(self.out, "\tla\t$a0 bool_const1");
+                emit!(self.out, "\tla\t$a1 bool_const0");
+                emit!(self.out, "\tbeq\t$t1 $t2 label{}", end);
+                emit!(self.out, "\tjal\tequality_test");
+                emit!(self.out, "label{}:", end);
+            }
+        }
+    }
+
+    /// Pushes the arguments of a call, then loads the receiver into `$a0`,
+    /// checking it is not void unless it is `self`.
+    fn receiver(&mut self, receiver: &Operand, args: &[Operand], span: Span) {
+        for arg in args {
+            self.load("$a0", arg);
+            self.push();
+        }
+        self.load("$a0", receiver);
+
+        if *receiver != Operand::SelfObject {
+            let ok = self.label();
+            emit!(self.out, "\tbne\t$a0 $zero label{}", ok);
+            self.runtime_error("_dispatch_abort", span);
+            emit!(self.out, "label{}:", ok);
+        }
+    }
+
+    /// Generates a terminator, falling through when it jumps to the block
+    /// at index `next`.
+    fn terminator(&mut self, terminator: &Terminator, next: usize) {
+        match terminator {
+            Terminator::Jump(target) => self.jump(*target, next),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.load("$a0", condition);
+                emit!(self.out, "\tlw\t$t1 {}($a0)", VALUE_OFFSET);
+                let label = self.blocks[otherwise.0 as usize];
+                emit!(self.out, "\tbeqz\t$t1 label{}", label);
+                self.jump(*then, next);
+            }
+            Terminator::TypeCase { value, arms, span } => self.case(value, arms, *span),
+            Terminator::Return(value) => {
+                self.load("$a0", value);
+                emit!(self.out, "\tlw\t$ra 0($fp)");
+                emit!(self.out, "\tlw\t$s0 4($fp)");
+                emit!(
+                    self.out,
+                    "\taddiu\t$sp $fp {}",
+                    2 * WORD + WORD * self.params as i32
+                );
+                emit!(self.out, "\tlw\t$fp 8($fp)");
+                emit!(self.out, "\tjr\t$ra");
+            }
+        }
+    }
+
+    fn jump(&mut self, target: BlockId, next: usize) {
+        if target.0 as usize != next {
+            emit!(self.out, "\tb\tlabel{}", self.blocks[target.0 as usize]);
+        }
+    }
+
+    /// Jumps to the arm whose class is the closest ancestor of the dynamic
+    /// type of the value, by trying the most specific classes first.
+    fn case(&mut self, value: &Operand, arms: &[(InternedIdx, BlockId)], span: Span) {
+        let ok = self.label();
+
+        self.load("$a0", value);
+        emit!(self.out, "\tbne\t$a0 $zero label{}", ok);
+        self.runtime_error("_case_abort2", span);
+        emit!(self.out, "label{}:", ok);
+        emit!(self.out, "\tlw\t$t2 {}($a0)", TAG_OFFSET);
+
+        let mut arms = arms.to_vec();
+        arms.sort_by_key(|&(class, _)| std::cmp::Reverse(self.layout.depth(class)));
+
+        for (class, block) in arms {
+            let next = self.label();
+            let tags = self.layout.tag_range(class);
+
+            emit!(self.out, "\tblt\t$t2 {} label{}", tags.start(), next);
+            emit!(self.out, "\tbgt\t$t2 {} label{}", tags.end(), next);
+            emit!(self.out, "\tb\tlabel{}", self.blocks[block.0 as usize]);
+            emit!(self.out, "label{}:", next);
+        }
+        emit!(self.out, "\tjal\t_case_abort");
+    }
+
+    fn new_object(&mut self, class: InternedIdx) {
+        if class == sym::SELF_TYPE {
+            // Prototypes and initializers are indexed by tag in
+            // `class_objTab`.
+            emit!(self.out, "\tla\t$t1 class_objTab");
+            emit!(self.out, "\tlw\t$t2 {}($s0)", TAG_OFFSET);
+            emit!(self.out, "\tsll\t$t2 $t2 3");
+            emit!(self.out, "\taddu\t$t1 $t1 $t2");
+            emit!(self.out, "\tsw\t$t1 0($sp)");
+            emit!(self.out, "\taddiu\t$sp $sp -4");
+            emit!(self.out, "\tlw\t$a0 0($t1)");
+            emit!(self.out, "\tjal\tObject.copy");
+            self.pop("$t1");
+            emit!(self.out, "\tlw\t$t1 4($t1)");
+            emit!(self.out, "\tjalr\t$t1");
+        } else {
+            let name = self.interner.lookup(&class);
+            emit!(self.out, "\tla\t$a0 {}_protObj", name);
+            emit!(self.out, "\tjal\tObject.copy");
+            emit!(self.out, "\tjal\t{}_init", name);
+        }
+    }
+
+    /// Loads `bool_const1` into `$a0` if the branch is taken, `bool_const0`
+    /// otherwise.
+    fn select_bool(&mut self, branch: &str) {
+        let end = self.label();
+
+        emit!(self.out, "\tla\t$a0 bool_const1");
+        emit!(self.out, "\t{} label{}", branch, end);
+        emit!(self.out, "\tla\t$a0 bool_const0");
+        emit!(self.out, "label{}:", end);
+    }
+
+    /// Calls a runtime routine expecting the file name in `$a0` and the line
+    /// of `span` in `$t1`.
+    fn runtime_error(&mut self, routine: &str, span: Span) {
+        let filename = self.filenames[span.file.index()];
+        emit!(self.out, "\tla\t$a0 str_const{}", filename);
+        emit!(self.out, "\tli\t$t1 {}", self.sources.line(span));
+        emit!(self.out, "\tjal\t{}", routine);
+    }
+
+    /// Loads an operand into a register.
+    fn load(&mut self, register: &str, operand: &Operand) {
+        match operand {
+            Operand::Temp(temp) => {
+                let offset = self.slots[temp.0 as usize];
+                emit!(self.out, "\tlw\t{} {}($fp)", register, offset);
+            }
+            Operand::SelfObject => emit!(self.out, "\tmove\t{} $s0", register),
+            Operand::Int(value) => {
+                let idx = self.int_const(*value);
+                emit!(self.out, "\tla\t{} int_const{}", register, idx);
+            }
+            Operand::Bool(value) => {
+                emit!(self.out, "\tla\t{} bool_const{}", register, *value as u8);
+            }
+            Operand::String(value) => {
+                let idx = self.string_const(value);
+                emit!(self.out, "\tla\t{} str_const{}", register, idx);
+            }
+            Operand::Void => emit!(self.out, "\tmove\t{} $zero", register),
+        }
+    }
+
+    /// The value attributes of a type start with, as a
+    /// label or `0` for void.
+    fn default_value(&mut self, ty: InternedIdx) -> String {
+        match ty {
+            sym::INT => format!("int_const{}", self.int_const(0)),
+            sym::STRING => format!("str_const{}", self.string_const("")),
+            sym::BOOL => "bool_const0".to_owned(),
+            _ => "0".to_owned(),
+        }
+    }
+
+    fn attribute_offset(&self, name: InternedIdx) -> i32 {
+        let idx = self.layout.attribute_index(self.class, name).unwrap();
+        WORD * (HEADER_WORDS + idx as i32)
+    }
+
+    fn push(&mut self) {
+        emit!(self.out, "\tsw\t$a0 0($sp)");
+        emit!(self.out, "\taddiu\t$sp $sp -4");
+    }
+
+    fn pop(&mut self, register: &str) {
+        emit!(self.out, "\tlw\t{} 4($sp)", register);
+        emit!(self.out, "\taddiu\t$sp $sp 4");
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels += 1;
+        self.labels - 1
+    }
+
+    fn method_label(&self, method: &MethodSig) -> String {
+        format!(
+            "{}.{}",
+            self.interner.lookup(&method.class),
+            self.interner.lookup(&method.name)
+        )
+    }
+
+    fn string_const(&mut self, value: &str) -> usize {
+        self.constants.string(value)
+    }
+
+    fn int_const(&mut self, value: i32) -> usize {
+        self.constants.int(value)
+    }
+}
+
+/// Writes printable characters with `.ascii` and everything else, including
+/// characters SPIM would interpret as escapes, with `.byte`.
+fn emit_ascii(out: &mut String, bytes: &[u8]) {
+    let printable = |byte: &u8| (b' '..=b'~').contains(byte) && !matches!(byte, b'"' | b'\\');
+
+    for chunk in bytes.chunk_by(|a, b| printable(a) == printable(b)) {
+        if printable(&chunk[0]) {
+            emit!(out, "\t.ascii\t\"{}\"", std::str::from_utf8(chunk).unwrap());
+        } else {
+            for byte in chunk {
+                emit!(out, "\t.byte\t{}", byte);
+            }
+        }
+    }
+}
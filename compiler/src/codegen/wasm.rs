@@ -0,0 +1,778 @@
+//! Code generation for WebAssembly, in the text format, with the runtime in
+//! `runtime/runtime.wat`.
+//!
+//! Objects live in linear memory and are made of 4-byte words: their class
+//! tag, their size in words, the index of their dispatch table in the
+//! function table and their attributes. Dynamic dispatch goes through
+//! `call_indirect`, and input and output through the `io` interface the host
+//! provides, see `runtime/host.mjs`.
+
+use std::fmt::Write;
+
+use crate::{
+    ast::BinaryOp,
+    codegen::{Constants, Layout},
+    ir::{self, BlockId, Function, FunctionKind, Inst, Operand, Temp, Terminator, UnaryOp},
+    semantic::{ClassTable, MethodSig},
+    util::{
+        interner::{InternedIdx, Interner, sym},
+        source::SourceMap,
+        span::Span,
+    },
+};
+
+macro_rules! emit {
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, $($arg)*).unwrap()
+    };
+}
+
+/// Emits an instruction at the indentation of the current block.
+macro_rules! inst {
+    ($generator:expr, $($arg:tt)*) => {{
+        let indent = $generator.indent;
+        write!($generator.out, "{:1$}", "", indent).unwrap();
+        writeln!($generator.out, $($arg)*).unwrap()
+    }};
+}
+
+/// The runtime, as module fields to be spliced into generated modules.
+pub const RUNTIME: &str = include_str!("../../runtime/runtime.wat");
+
+/// Host implementing the interface the runtime imports, for Node and
+/// browsers.
+pub const HOST: &str = include_str!("../../runtime/host.mjs");
+
+const WORD: usize = 4;
+
+/// Words before the attributes of an object: tag, size and dispatch table.
+const HEADER_WORDS: usize = 3;
+
+const DISPATCH_OFFSET: usize = 8;
+/// Offset of the value of `Int` and `Bool` objects, and of the length of
+/// `String` objects.
+const VALUE_OFFSET: usize = 12;
+
+/// Address of the data of the program, after the messages of the runtime.
+const DATA_START: usize = 128;
+
+const PAGE_SIZE: usize = 65_536;
+
+/// Generates a WebAssembly module for the IR of a program.
+///
+/// The name of the file of an expression, without its directories, is
+/// reported along with the line of a runtime error.
+pub fn generate(
+    program: &ir::Program,
+    classes: &ClassTable,
+    interner: &Interner,
+    sources: &SourceMap,
+) -> String {
+    let layout = Layout::new(classes);
+    let mut generator = Generator {
+        layout: &layout,
+        interner,
+        out: String::new(),
+        constants: Constants::default(),
+        class: sym::OBJECT,
+        temps: Vec::new(),
+        locals: 0,
+        max_locals: 0,
+        indent: 0,
+        sources,
+    };
+
+    generator.functions(program);
+    let functions = std::mem::take(&mut generator.out);
+    generator.tables();
+    generator.data();
+
+    format!("(module\n{}\n{}\n{})\n", RUNTIME, generator.out, functions)
+}
+
+/// Static data, laid out from [`DATA_START`].
+struct Data {
+    bytes: Vec<u8>,
+}
+
+impl Data {
+    fn address(&self) -> usize {
+        DATA_START + self.bytes.len()
+    }
+
+    /// Appends words, returning the address of the first one.
+    fn words(&mut self, words: &[usize]) -> usize {
+        let address = self.address();
+        for &word in words {
+            self.bytes.extend((word as u32).to_le_bytes());
+        }
+        address
+    }
+
+    /// Appends bytes aligned to a word, returning the address of the first
+    /// one.
+    fn bytes(&mut self, bytes: &[u8]) -> usize {
+        let address = self.address();
+        self.bytes.extend(bytes);
+        self.bytes
+            .resize(self.bytes.len().next_multiple_of(WORD), 0);
+        address
+    }
+}
+
+struct Generator<'g, 'c, 'a> {
+    layout: &'g Layout<'c, 'a>,
+    interner: &'g Interner,
+    out: String,
+    constants: Constants,
+    /// The class of the function being generated.
+    class: InternedIdx,
+    /// Local or parameter of every temporary of the function being
+    /// generated.
+    temps: Vec<String>,
+    /// Scratch locals in use by the instruction being generated.
+    locals: usize,
+    /// Scratch locals the function being generated needs.
+    max_locals: usize,
+    indent: usize,
+    sources: &'g SourceMap,
+}
+
+impl Generator<'_, '_, '_> {
+    /// Types for `call_indirect`, and the function table holding the
+    /// dispatch tables followed by the initializers.
+    fn tables(&mut self) {
+        let layout = self.layout;
+        let classes = layout.order();
+
+        let arity = classes
+            .iter()
+            .flat_map(|&class| layout.methods(class))
+            .map(|method| method.params.len())
+            .max()
+            .unwrap_or(0);
+        for params in 0..=arity {
+            emit!(
+                self.out,
+                "  (type $method{} (func{} (result i32)))",
+                params,
+                " (param i32)".repeat(params + 1)
+            );
+        }
+
+        let mut functions = Vec::new();
+        for &class in classes {
+            for method in layout.methods(class) {
+                functions.push(self.method_label(method));
+            }
+        }
+        for &class in classes {
+            functions.push(format!("${}_init", self.interner.lookup(&class)));
+        }
+        emit!(self.out, "  (table {} funcref)", functions.len());
+        emit!(
+            self.out,
+            "  (elem (i32.const 0) func {})",
+            functions.join(" ")
+        );
+    }
+
+    fn data(&mut self) {
+        let layout = self.layout;
+        let classes = layout.order();
+
+        // Every constant must be known before the data is laid out.
+        let interner = self.interner;
+        let names: Vec<_> = classes
+            .iter()
+            .map(|class| self.constants.string(interner.lookup(class)))
+            .collect();
+        let empty = self.constants.string("");
+        let zero = self.constants.int(0);
+
+        let mut dispatch_tables = Vec::with_capacity(classes.len());
+        let mut functions = 0;
+        for &class in classes {
+            dispatch_tables.push(functions);
+            functions += layout.methods(class).len();
+        }
+        let dispatch_table = |class| dispatch_tables[layout.tag(class)];
+
+        let mut data = Data { bytes: Vec::new() };
+        let mut globals = Vec::new();
+
+        for (label, class) in [
+            ("_int_tag", sym::INT),
+            ("_bool_tag", sym::BOOL),
+            ("_string_tag", sym::STRING),
+        ] {
+            globals.push((label.to_owned(), layout.tag(class)));
+        }
+
+        let chars: Vec<_> = self
+            .constants
+            .strings()
+            .iter()
+            .map(|value| data.bytes(value.as_bytes()))
+            .collect();
+        let strings: Vec<_> = self
+            .constants
+            .strings()
+            .iter()
+            .zip(&chars)
+            .map(|(value, &chars)| {
+                data.words(&[
+                    layout.tag(sym::STRING),
+                    HEADER_WORDS + 2,
+                    dispatch_table(sym::STRING),
+                    value.len(),
+                    chars,
+                ])
+            })
+            .collect();
+        let ints: Vec<_> = self
+            .constants
+            .ints()
+            .iter()
+            .map(|&value| {
+                data.words(&[
+                    layout.tag(sym::INT),
+                    HEADER_WORDS + 1,
+                    dispatch_table(sym::INT),
+                    value as u32 as usize,
+                ])
+            })
+            .collect();
+        let bools = [0, 1].map(|value| {
+            data.words(&[
+                layout.tag(sym::BOOL),
+                HEADER_WORDS + 1,
+                dispatch_table(sym::BOOL),
+                value,
+            ])
+        });
+        for (idx, address) in strings.iter().enumerate() {
+            globals.push((format!("str_const{}", idx), *address));
+        }
+        for (idx, address) in ints.iter().enumerate() {
+            globals.push((format!("int_const{}", idx), *address));
+        }
+        for (value, address) in bools.iter().enumerate() {
+            globals.push((format!("bool_const{}", value), *address));
+        }
+
+        // The objects `new` copies before running the initializer of the
+        // class.
+        let mut prototypes = Vec::with_capacity(classes.len());
+        for &class in classes {
+            let fields: Vec<_> = match class {
+                sym::INT | sym::BOOL => vec![0],
+                sym::STRING => vec![0, chars[empty]],
+                _ => layout
+                    .attributes(class)
+                    .iter()
+                    .map(|attribute| match attribute.ty.0.name {
+                        sym::INT => ints[zero],
+                        sym::STRING => strings[empty],
+                        sym::BOOL => bools[0],
+                        _ => 0,
+                    })
+                    .collect(),
+            };
+            let header = [
+                layout.tag(class),
+                HEADER_WORDS + fields.len(),
+                dispatch_table(class),
+            ];
+            let address = data.words(&[&header[..], &fields].concat());
+
+            prototypes.push(address);
+            globals.push((format!("{}_protObj", interner.lookup(&class)), address));
+        }
+
+        let name_table: Vec<_> = names.iter().map(|&name| strings[name]).collect();
+        let name_table = data.words(&name_table);
+        globals.push(("class_nameTab".to_owned(), name_table));
+
+        // Prototypes and the index of initializers in the function table,
+        // for `new SELF_TYPE`.
+        let object_table: Vec<_> = prototypes
+            .iter()
+            .enumerate()
+            .flat_map(|(tag, &prototype)| [prototype, functions + tag])
+            .collect();
+        let object_table = data.words(&object_table);
+        globals.push(("class_objTab".to_owned(), object_table));
+
+        let heap = data.address();
+        emit!(
+            self.out,
+            "  (memory (export \"memory\") {})",
+            heap.div_ceil(PAGE_SIZE) + 1
+        );
+        for (label, value) in globals {
+            emit!(self.out, "  (global ${} i32 (i32.const {}))", label, value);
+        }
+        emit!(self.out, "  (global $heap (mut i32) (i32.const {}))", heap);
+        emit!(
+            self.out,
+            "  (data (i32.const {}) \"{}\")",
+            DATA_START,
+            escape(&data.bytes)
+        );
+    }
+
+    fn functions(&mut self, program: &ir::Program) {
+        let layout = self.layout;
+
+        for &class in layout.order() {
+            let empty = Function::empty_init(class);
+            let function = program.init(class).unwrap_or(&empty);
+            let label = format!("${}_init", self.interner.lookup(&class));
+
+            // The attributes of the ancestors are initialized first.
+            let parent = layout.classes().get(class).unwrap().parent;
+            self.function(&label, function, |this| {
+                if let Some(parent) = parent {
+                    inst!(this, "local.get $self");
+                    inst!(this, "call ${}_init", this.interner.lookup(&parent));
+                    inst!(this, "drop");
+                }
+            });
+        }
+        for function in &program.functions {
+            if let FunctionKind::Method(name) = function.kind {
+                let label = format!(
+                    "${}.{}",
+                    self.interner.lookup(&function.class),
+                    self.interner.lookup(&name)
+                );
+                self.function(&label, function, |_| {});
+            }
+        }
+    }
+
+    /// Emits a function taking `self` and its parameters, running `prologue`
+    /// first and declaring the locals its body needs once it is generated.
+    ///
+    /// Blocks are laid out one after the other, each following the end of a
+    /// `block` of its own, in a loop that `br_table` dispatches on `$block`
+    /// from: jumping to a block sets `$block` and continues the loop, unless
+    /// the block follows.
+    fn function(&mut self, label: &str, function: &Function, prologue: impl FnOnce(&mut Self)) {
+        self.class = function.class;
+        self.max_locals = 0;
+        self.indent = 4;
+        self.temps = (0..function.temps)
+            .map(
+                |temp| match function.params.iter().position(|param| param.0 == temp) {
+                    Some(idx) => format!("$arg{}", idx),
+                    None => format!("$t{}", temp),
+                },
+            )
+            .collect();
+
+        let mut header = format!("  (func {} (param $self i32)", label);
+        for idx in 0..function.params.len() {
+            write!(header, " (param $arg{} i32)", idx).unwrap();
+        }
+        header.push_str(" (result i32)");
+
+        let out = std::mem::take(&mut self.out);
+        prologue(self);
+
+        let blocks = &function.blocks;
+        if let [block] = &blocks[..] {
+            self.block(block, 1);
+        } else {
+            inst!(self, "loop $dispatch");
+            self.nested(|this| {
+                for idx in (0..blocks.len()).rev() {
+                    inst!(this, "block $b{}", idx);
+                }
+                let labels: Vec<_> = (0..blocks.len()).map(|idx| format!("$b{}", idx)).collect();
+                inst!(this, "local.get $block");
+                inst!(this, "br_table {}", labels.join(" "));
+
+                for (idx, block) in blocks.iter().enumerate() {
+                    inst!(this, "end");
+                    this.block(block, idx + 1);
+                }
+            });
+            inst!(self, "end");
+            inst!(self, "unreachable");
+        }
+        let body = std::mem::replace(&mut self.out, out);
+
+        emit!(self.out, "{}", header);
+        for (temp, local) in self.temps.iter().enumerate() {
+            if !function.params.contains(&Temp(temp as u32)) {
+                emit!(self.out, "    (local {} i32)", local);
+            }
+        }
+        for idx in 0..self.max_locals {
+            emit!(self.out, "    (local $l{} i32)", idx);
+        }
+        if blocks.len() > 1 {
+            emit!(self.out, "    (local $block i32)");
+        }
+        self.out.push_str(&body);
+        emit!(self.out, "  )");
+    }
+
+    /// Generates a block, falling through when it jumps to the block at
+    /// index `next`.
+    fn block(&mut self, block: &ir::Block, next: usize) {
+        for inst in &block.insts {
+            self.inst(inst);
+        }
+
+        self.locals = 0;
+        match &block.terminator {
+            Terminator::Jump(target) => self.jump(*target, Some(next)),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.operand(condition);
+                inst!(self, "i32.load offset={}", VALUE_OFFSET);
+                inst!(self, "if");
+                self.nested(|this| this.jump(*then, None));
+                inst!(self, "end");
+                self.jump(*otherwise, Some(next));
+            }
+            Terminator::TypeCase { value, arms, span } => self.case(value, arms, *span),
+            Terminator::Return(value) => {
+                self.operand(value);
+                inst!(self, "return");
+            }
+        }
+    }
+
+    /// Jumps to a block, by falling through if it is at index `next`.
+    fn jump(&mut self, target: BlockId, next: Option<usize>) {
+        if Some(target.0 as usize) != next {
+            inst!(self, "i32.const {}", target.0);
+            inst!(self, "local.set $block");
+            inst!(self, "br $dispatch");
+        }
+    }
+
+    /// Generates an instruction, setting the local of the temporary it
+    /// assigns to the value it leaves on the stack.
+    fn inst(&mut self, inst: &Inst) {
+        self.locals = 0;
+
+        match inst {
+            Inst::Copy { value, .. } => self.operand(value),
+            Inst::Unary { op, value, .. } => match op {
+                UnaryOp::Complement => {
+                    let copy = self.alloc_local();
+
+                    self.operand(value);
+                    inst!(self, "call $Object.copy");
+                    inst!(self, "local.tee {}", copy);
+                    inst!(self, "i32.const 0");
+                    self.operand(value);
+                    inst!(self, "i32.load offset={}", VALUE_OFFSET);
+                    inst!(self, "i32.sub");
+                    inst!(self, "i32.store offset={}", VALUE_OFFSET);
+                    inst!(self, "local.get {}", copy);
+                }
+                UnaryOp::Not => {
+                    self.operand(value);
+                    inst!(self, "i32.load offset={}", VALUE_OFFSET);
+                    inst!(self, "i32.eqz");
+                    self.select_bool();
+                }
+            },
+            Inst::IsVoid { value, .. } => {
+                self.operand(value);
+                inst!(self, "i32.eqz");
+                self.select_bool();
+            }
+            Inst::Binary {
+                op,
+                left,
+                right,
+                span,
+                ..
+            } => self.binary(*op, left, right, *span),
+            Inst::GetAttr { name, .. } => {
+                let offset = self.attribute_offset(*name);
+                inst!(self, "local.get $self");
+                inst!(self, "i32.load offset={}", offset);
+            }
+            Inst::SetAttr { name, value } => {
+                let offset = self.attribute_offset(*name);
+                inst!(self, "local.get $self");
+                self.operand(value);
+                inst!(self, "i32.store offset={}", offset);
+            }
+            Inst::Dispatch {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                self.receiver(receiver, args, *span);
+
+                let idx = self.layout.method_index(*class, *method).unwrap();
+                self.operand(receiver);
+                inst!(self, "i32.load offset={}", DISPATCH_OFFSET);
+                inst!(self, "i32.const {}", idx);
+                inst!(self, "i32.add");
+                inst!(self, "call_indirect (type $method{})", args.len());
+            }
+            Inst::Call {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                self.receiver(receiver, args, *span);
+
+                let target = self.layout.classes().lookup_method(*class, *method);
+                inst!(self, "call {}", self.method_label(target.unwrap()));
+            }
+            Inst::New { class, .. } => self.new_object(*class),
+        }
+
+        if let Some(dest) = inst.dest() {
+            inst!(self, "local.set {}", self.temps[dest.0 as usize]);
+        }
+    }
+
+    /// Checks that the receiver of a call is not void unless it is `self`,
+    /// then pushes it and the arguments.
+    fn receiver(&mut self, receiver: &Operand, args: &[Operand], span: Span) {
+        if *receiver != Operand::SelfObject {
+            self.operand(receiver);
+            inst!(self, "i32.eqz");
+            self.check(span, "Dispatch to void.");
+        }
+
+        self.operand(receiver);
+        for arg in args {
+            self.operand(arg);
+        }
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: &Operand, right: &Operand, span: Span) {
+        if let BinaryOp::Eq = op {
+            self.operand(left);
+            self.operand(right);
+            inst!(self, "call $equals");
+            return;
+        }
+
+        let inst = match op {
+            BinaryOp::Add => "i32.add",
+            BinaryOp::Sub => "i32.sub",
+            BinaryOp::Mul => "i32.mul",
+            BinaryOp::Div => "i32.div_s",
+            BinaryOp::Lt => "i32.lt_s",
+            _ => "i32.le_s",
+        };
+
+        match op {
+            BinaryOp::Lt | BinaryOp::Lte => {
+                for value in [left, right] {
+                    self.operand(value);
+                    inst!(self, "i32.load offset={}", VALUE_OFFSET);
+                }
+                inst!(self, "{}", inst);
+                self.select_bool();
+            }
+            _ => {
+                if let BinaryOp::Div = op {
+                    self.operand(right);
+                    inst!(self, "i32.load offset={}", VALUE_OFFSET);
+                    inst!(self, "i32.eqz");
+                    self.check(span, "Division by zero.");
+                }
+
+                // The result is a fresh copy of the right operand.
+                let copy = self.alloc_local();
+                self.operand(right);
+                inst!(self, "call $Object.copy");
+                inst!(self, "local.tee {}", copy);
+                let mut values = Vec::new();
+                for value in [left, right] {
+                    self.operand(value);
+                    inst!(self, "i32.load offset={}", VALUE_OFFSET);
+                    if let BinaryOp::Div = op {
+                        let local = self.alloc_local();
+                        inst!(self, "local.set {}", local);
+                        values.push(local);
+                    }
+                }
+                if let [dividend, divisor] = &values[..] {
+                    // `i32.div_s` traps on INT_MIN / -1, so a divisor of -1
+                    // negates instead, which wraps like the interpreter.
+                    inst!(self, "local.get {}", divisor);
+                    inst!(self, "i32.const -1");
+                    inst!(self, "i32.eq");
+                    inst!(self, "if (result i32)");
+                    inst!(self, "  i32.const 0");
+                    inst!(self, "  local.get {}", dividend);
+                    inst!(self, "  i32.sub");
+                    inst!(self, "else");
+                    inst!(self, "  local.get {}", dividend);
+                    inst!(self, "  local.get {}", divisor);
+                    inst!(self, "  {}", inst);
+                    inst!(self, "end");
+                } else {
+                    inst!(self, "{}", inst);
+                }
+                inst!(self, "i32.store offset={}", VALUE_OFFSET);
+                inst!(self, "local.get {}", copy);
+            }
+        }
+    }
+
+    /// Jumps to the arm whose class is the closest ancestor of the dynamic
+    /// type of the value, by trying the most specific classes first.
+    fn case(&mut self, value: &Operand, arms: &[(InternedIdx, BlockId)], span: Span) {
+        let tag = self.alloc_local();
+
+        self.operand(value);
+        inst!(self, "i32.eqz");
+        self.check(span, "Match on void in case statement.");
+        self.operand(value);
+        inst!(self, "i32.load");
+        inst!(self, "local.set {}", tag);
+
+        let mut arms = arms.to_vec();
+        arms.sort_by_key(|&(class, _)| std::cmp::Reverse(self.layout.depth(class)));
+
+        for (class, block) in arms {
+            let tags = self.layout.tag_range(class);
+
+            inst!(self, "local.get {}", tag);
+            inst!(self, "i32.const {}", tags.start());
+            inst!(self, "i32.ge_s");
+            inst!(self, "local.get {}", tag);
+            inst!(self, "i32.const {}", tags.end());
+            inst!(self, "i32.le_s");
+            inst!(self, "i32.and");
+            inst!(self, "if");
+            self.nested(|this| this.jump(block, None));
+            inst!(self, "end");
+        }
+        self.operand(value);
+        inst!(self, "call $case_abort");
+        inst!(self, "unreachable");
+    }
+
+    fn new_object(&mut self, class: InternedIdx) {
+        if class != sym::SELF_TYPE {
+            let name = self.interner.lookup(&class);
+            inst!(self, "global.get ${}_protObj", name);
+            inst!(self, "call $Object.copy");
+            inst!(self, "call ${}_init", name);
+            return;
+        }
+
+        // Prototypes and initializers are indexed by tag in `class_objTab`.
+        let entry = self.alloc_local();
+
+        inst!(self, "global.get $class_objTab");
+        inst!(self, "local.get $self");
+        inst!(self, "i32.load");
+        inst!(self, "i32.const {}", 2 * WORD);
+        inst!(self, "i32.mul");
+        inst!(self, "i32.add");
+        inst!(self, "local.tee {}", entry);
+        inst!(self, "i32.load");
+        inst!(self, "call $Object.copy");
+        inst!(self, "local.get {}", entry);
+        inst!(self, "i32.load offset={}", WORD);
+        inst!(self, "call_indirect (type $method0)");
+    }
+
+    /// Turns the condition on the stack into a `Bool` object.
+    fn select_bool(&mut self) {
+        inst!(self, "if (result i32)");
+        inst!(self, "  global.get $bool_const1");
+        inst!(self, "else");
+        inst!(self, "  global.get $bool_const0");
+        inst!(self, "end");
+    }
+
+    /// Reports a runtime error at the line of `span` when the condition on
+    /// the stack holds.
+    fn check(&mut self, span: Span, message: &str) {
+        let filename = self.sources.get(span.file).file_name();
+        let line = self.sources.line(span);
+        let message = format!("{}:{}: {}\n", filename, line, message);
+        let idx = self.constants.string(&message);
+
+        inst!(self, "if");
+        inst!(self, "  global.get $str_const{}", idx);
+        inst!(self, "  call $runtime_error");
+        inst!(self, "end");
+    }
+
+    fn nested(&mut self, body: impl FnOnce(&mut Self)) {
+        self.indent += 2;
+        body(self);
+        self.indent -= 2;
+    }
+
+    fn operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Temp(temp) => inst!(self, "local.get {}", self.temps[temp.0 as usize]),
+            Operand::SelfObject => inst!(self, "local.get $self"),
+            Operand::Int(value) => {
+                let idx = self.constants.int(*value);
+                inst!(self, "global.get $int_const{}", idx);
+            }
+            Operand::Bool(value) => inst!(self, "global.get $bool_const{}", *value as u8),
+            Operand::String(value) => {
+                let idx = self.constants.string(value);
+                inst!(self, "global.get $str_const{}", idx);
+            }
+            Operand::Void => inst!(self, "i32.const 0"),
+        }
+    }
+
+    fn attribute_offset(&self, name: InternedIdx) -> usize {
+        let idx = self.layout.attribute_index(self.class, name).unwrap();
+        (HEADER_WORDS + idx) * WORD
+    }
+
+    fn alloc_local(&mut self) -> String {
+        self.locals += 1;
+        self.max_locals = self.max_locals.max(self.locals);
+        format!("$l{}", self.locals - 1)
+    }
+
+    fn method_label(&self, method: &MethodSig) -> String {
+        format!(
+            "${}.{}",
+            self.interner.lookup(&method.class),
+            self.interner.lookup(&method.name)
+        )
+    }
+}
+
+/// Escapes bytes for a string in the text format, writing anything but
+/// printable characters as hexadecimal escapes.
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+
+    for &byte in bytes {
+        if (b' '..=b'~').contains(&byte) && !matches!(byte, b'"' | b'\\') {
+            escaped.push(byte as char);
+        } else {
+            write!(escaped, "\\{:02x}", byte).unwrap();
+        }
+    }
+    escaped
+}
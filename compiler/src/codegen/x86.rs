@@ -0,0 +1,738 @@
+//! Code generation for x86-64 Linux, in AT&T syntax, to be linked with the
+//! runtime in `runtime/runtime.c`.
+//!
+//! Objects are made of 8-byte words: their class tag, their size in words, a
+//! pointer to their dispatch table and their attributes. Methods follow the
+//! System V calling convention, taking `self` as their first argument. Every
+//! intermediate value lives in a slot of the frame, so that `%rsp` stays
+//! aligned across calls.
+
+use std::{
+    env,
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    ast::BinaryOp,
+    codegen::{Constants, Layout},
+    ir::{self, BlockId, Function, FunctionKind, Inst, Operand, Terminator, UnaryOp},
+    semantic::{ClassTable, MethodSig},
+    util::{
+        interner::{InternedIdx, Interner, sym},
+        source::SourceMap,
+        span::Span,
+    },
+};
+
+macro_rules! emit {
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, $($arg)*).unwrap()
+    };
+}
+
+/// The runtime compiled by the build script, as a static library.
+pub const RUNTIME: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/libcoolrt.a"));
+
+const WORD: i32 = 8;
+
+/// Words before the attributes of an object: tag, size and dispatch table.
+const HEADER_WORDS: i32 = 3;
+
+const TAG_OFFSET: i32 = 0;
+const DISPATCH_OFFSET: i32 = 16;
+/// Offset of the value of `Int` and `Bool` objects, and of the length of
+/// `String` objects.
+const VALUE_OFFSET: i32 = 24;
+
+/// Registers holding the arguments following `self`, the remaining ones are
+/// passed on the stack.
+const ARG_REGISTERS: [&str; 5] = ["%rsi", "%rdx", "%rcx", "%r8", "%r9"];
+
+/// Generates x86-64 assembly for the IR of a program.
+///
+/// The name of the file of an expression, without its directories, is
+/// reported by the runtime along with the line of a runtime error.
+pub fn generate(
+    program: &ir::Program,
+    classes: &ClassTable,
+    interner: &Interner,
+    sources: &SourceMap,
+) -> String {
+    let layout = Layout::new(classes);
+    let mut generator = Generator {
+        layout: &layout,
+        interner,
+        out: String::new(),
+        constants: Constants::default(),
+        labels: 0,
+        class: sym::OBJECT,
+        slots: Vec::new(),
+        blocks: Vec::new(),
+        sources,
+        filenames: Vec::new(),
+    };
+    generator.filenames = sources
+        .files()
+        .map(|(_, file)| generator.string_const(file.file_name()))
+        .collect();
+
+    generator.text(program);
+    let text = std::mem::take(&mut generator.out);
+    generator.data();
+
+    generator.out + &text
+}
+
+/// Assembles the output of [`generate`] and links it with the runtime into
+/// an executable, using the C compiler named by `CC` or `cc`.
+pub fn link(asm: &str, output: &Path) -> io::Result<()> {
+    let dir = temp_dir()?;
+    let status = (|| {
+        let asm_path = dir.join("program.s");
+        let runtime_path = dir.join("libcoolrt.a");
+        fs::write(&asm_path, asm)?;
+        fs::write(&runtime_path, RUNTIME)?;
+
+        let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+        Command::new(compiler)
+            .arg(&asm_path)
+            .arg(&runtime_path)
+            .arg("-o")
+            .arg(output)
+            .status()
+    })();
+    fs::remove_dir_all(&dir)?;
+
+    if !status?.success() {
+        return Err(io::Error::other("linking failed"));
+    }
+    Ok(())
+}
+
+/// Creates a directory of its own for a call to [`link`], so that programs
+/// can be linked concurrently, by threads or processes.
+fn temp_dir() -> io::Result<PathBuf> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    loop {
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("cool-{}-{}", process::id(), count));
+        match fs::create_dir(&dir) {
+            // Left over by an earlier process with the same id.
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            result => return result.map(|()| dir),
+        }
+    }
+}
+
+/// The method a call jumps to.
+enum Callee {
+    Label(String),
+    /// The method at an index of the dispatch table of the receiver.
+    Dispatch(usize),
+}
+
+struct Generator<'g, 'c, 'a> {
+    layout: &'g Layout<'c, 'a>,
+    interner: &'g Interner,
+    out: String,
+    constants: Constants,
+    labels: usize,
+    /// The class of the function being generated.
+    class: InternedIdx,
+    /// Offset from `%rbp` of every temporary of the function being
+    /// generated.
+    slots: Vec<i32>,
+    /// Label of every block of the function being generated.
+    blocks: Vec<usize>,
+    sources: &'g SourceMap,
+    /// String constants holding the names of the source files.
+    filenames: Vec<usize>,
+}
+
+impl Generator<'_, '_, '_> {
+    fn data(&mut self) {
+        let layout = self.layout;
+        let classes = layout.order();
+
+        // Every constant must be known before the tables are written out.
+        let interner = self.interner;
+        let names: Vec<_> = classes
+            .iter()
+            .map(|class| self.string_const(interner.lookup(class)))
+            .collect();
+        let empty = self.string_const("");
+
+        emit!(self.out, "\t.data");
+        emit!(self.out, "\t.p2align\t3");
+        for global in [
+            "class_nameTab",
+            "Main_protObj",
+            "Int_protObj",
+            "String_protObj",
+            "bool_const0",
+            "bool_const1",
+            "_int_tag",
+            "_bool_tag",
+            "_string_tag",
+        ] {
+            emit!(self.out, "\t.globl\t{}", global);
+        }
+        for (label, class) in [
+            ("_int_tag", sym::INT),
+            ("_bool_tag", sym::BOOL),
+            ("_string_tag", sym::STRING),
+        ] {
+            emit!(self.out, "{}:", label);
+            emit!(self.out, "\t.quad\t{}", layout.tag(class));
+        }
+
+        self.constants();
+
+        emit!(self.out, "class_nameTab:");
+        for name in names {
+            emit!(self.out, "\t.quad\tstr_const{}", name);
+        }
+        emit!(self.out, "class_objTab:");
+        for &class in classes {
+            let name = self.interner.lookup(&class);
+            emit!(self.out, "\t.quad\t{}_protObj", name);
+            emit!(self.out, "\t.quad\t{}_init", name);
+        }
+
+        for &class in classes {
+            emit!(self.out, "{}_dispTab:", self.interner.lookup(&class));
+            for method in layout.methods(class) {
+                let label = self.method_label(method);
+                emit!(self.out, "\t.quad\t{}", label);
+            }
+        }
+        for &class in classes {
+            self.prototype(class, empty);
+        }
+    }
+
+    fn constants(&mut self) {
+        let string_tag = self.layout.tag(sym::STRING);
+        let int_tag = self.layout.tag(sym::INT);
+        let bool_tag = self.layout.tag(sym::BOOL);
+
+        for (idx, value) in self.constants.strings().iter().enumerate() {
+            emit!(self.out, "str_const{}:", idx);
+            emit!(self.out, "\t.quad\t{}", string_tag);
+            emit!(self.out, "\t.quad\t{}", HEADER_WORDS + 2);
+            emit!(self.out, "\t.quad\tString_dispTab");
+            emit!(self.out, "\t.quad\t{}", value.len());
+            emit!(self.out, "\t.quad\tstr_chars{}", idx);
+            emit!(self.out, "str_chars{}:", idx);
+            emit_ascii(&mut self.out, value.as_bytes());
+            emit!(self.out, "\t.byte\t0");
+            emit!(self.out, "\t.p2align\t3");
+        }
+        for (idx, value) in self.constants.ints().iter().enumerate() {
+            emit!(self.out, "int_const{}:", idx);
+            emit!(self.out, "\t.quad\t{}", int_tag);
+            emit!(self.out, "\t.quad\t{}", HEADER_WORDS + 1);
+            emit!(self.out, "\t.quad\tInt_dispTab");
+            emit!(self.out, "\t.quad\t{}", value);
+        }
+        for value in 0..2 {
+            emit!(self.out, "bool_const{}:", value);
+            emit!(self.out, "\t.quad\t{}", bool_tag);
+            emit!(self.out, "\t.quad\t{}", HEADER_WORDS + 1);
+            emit!(self.out, "\t.quad\tBool_dispTab");
+            emit!(self.out, "\t.quad\t{}", value);
+        }
+    }
+
+    /// The object `new` copies before running the initializer of the class.
+    fn prototype(&mut self, class: InternedIdx, empty: usize) {
+        let layout = self.layout;
+        let fields: Vec<String> = match class {
+            sym::INT | sym::BOOL => vec!["0".to_owned()],
+            sym::STRING => vec!["0".to_owned(), format!("str_chars{}", empty)],
+            _ => layout
+                .attributes(class)
+                .iter()
+                .map(|attribute| self.default_value(attribute.ty.0.name))
+                .collect(),
+        };
+
+        emit!(self.out, "{}_protObj:", self.interner.lookup(&class));
+        emit!(self.out, "\t.quad\t{}", self.layout.tag(class));
+        emit!(self.out, "\t.quad\t{}", HEADER_WORDS + fields.len() as i32);
+        emit!(
+            self.out,
+            "\t.quad\t{}_dispTab",
+            self.interner.lookup(&class)
+        );
+        for field in fields {
+            emit!(self.out, "\t.quad\t{}", field);
+        }
+    }
+
+    fn text(&mut self, program: &ir::Program) {
+        emit!(self.out, "\t.text");
+        emit!(self.out, "\t.globl\tMain_init");
+        emit!(self.out, "\t.globl\tMain.main");
+
+        let layout = self.layout;
+        for &class in layout.order() {
+            let empty = Function::empty_init(class);
+            let function = program.init(class).unwrap_or(&empty);
+            let label = format!("{}_init", self.interner.lookup(&class));
+
+            // The attributes of the ancestors are initialized first.
+            let parent = layout.classes().get(class).unwrap().parent;
+            self.function(&label, function, |this| {
+                if let Some(parent) = parent {
+                    emit!(this.out, "\tmovq\t-8(%rbp), %rdi");
+                    emit!(this.out, "\tcall\t{}_init", this.interner.lookup(&parent));
+                }
+            });
+        }
+        for function in &program.functions {
+            if let FunctionKind::Method(name) = function.kind {
+                let label = format!(
+                    "{}.{}",
+                    self.interner.lookup(&function.class),
+                    self.interner.lookup(&name)
+                );
+                self.function(&label, function, |_| {});
+            }
+        }
+
+        emit!(self.out, "\t.section\t.note.GNU-stack,\"\",@progbits");
+    }
+
+    /// Generates a function, running `prologue` once its frame is set up.
+    ///
+    /// The frame has a slot for `self`, the register arguments and every
+    /// other temporary, the stacked arguments staying above the return
+    /// address.
+    fn function(&mut self, label: &str, function: &Function, prologue: impl FnOnce(&mut Self)) {
+        let spilled = function.params.len().min(ARG_REGISTERS.len());
+        let mut slots = 1 + spilled as i32;
+        self.class = function.class;
+        self.slots = (0..function.temps)
+            .map(
+                |temp| match function.params.iter().position(|param| param.0 == temp) {
+                    Some(idx) => match idx.checked_sub(ARG_REGISTERS.len()) {
+                        None => -WORD * (idx as i32 + 2),
+                        Some(idx) => 2 * WORD + WORD * idx as i32,
+                    },
+                    None => {
+                        slots += 1;
+                        -WORD * slots
+                    }
+                },
+            )
+            .collect();
+        self.blocks = (0..function.blocks.len()).map(|_| self.label()).collect();
+
+        // Keep `%rsp` 16-byte aligned.
+        let frame = (WORD * slots + 15) & !15;
+
+        emit!(self.out, "{}:", label);
+        emit!(self.out, "\tpushq\t%rbp");
+        emit!(self.out, "\tmovq\t%rsp, %rbp");
+        emit!(self.out, "\tsubq\t${}, %rsp", frame);
+        emit!(self.out, "\tmovq\t%rdi, -8(%rbp)");
+        for (idx, register) in ARG_REGISTERS[..spilled].iter().enumerate() {
+            emit!(
+                self.out,
+                "\tmovq\t{}, {}(%rbp)",
+                register,
+                -WORD * (idx as i32 + 2)
+            );
+        }
+        prologue(self);
+
+        for (idx, block) in function.blocks.iter().enumerate() {
+            emit!(self.out, ".L{}:", self.blocks[idx]);
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            self.terminator(&block.terminator, idx + 1);
+        }
+    }
+
+    /// Generates an instruction, storing the value it assigns from `%rax`.
+    fn inst(&mut self, inst: &Inst) {
+        match inst {
+            Inst::Copy { value, .. } => self.load("%rax", value),
+            Inst::Unary { op, value, .. } => match op {
+                UnaryOp::Complement => {
+                    self.load("%rdi", value);
+                    emit!(self.out, "\tcall\tObject.copy");
+                    emit!(self.out, "\tnegl\t{}(%rax)", VALUE_OFFSET);
+                }
+                UnaryOp::Not => {
+                    self.load("%rax", value);
+                    emit!(self.out, "\tcmpq\t$0, {}(%rax)", VALUE_OFFSET);
+                    self.select_bool("je");
+                }
+            },
+            Inst::IsVoid { value, .. } => {
+                self.load("%rax", value);
+                emit!(self.out, "\ttestq\t%rax, %rax");
+                self.select_bool("je");
+            }
+            Inst::Binary {
+                op,
+                left,
+                right,
+                span,
+                ..
+            } => self.binary(*op, left, right, *span),
+            Inst::GetAttr { name, .. } => {
+                let offset = self.attribute_offset(*name);
+                emit!(self.out, "\tmovq\t-8(%rbp), %rcx");
+                emit!(self.out, "\tmovq\t{}(%rcx), %rax", offset);
+            }
+            Inst::SetAttr { name, value } => {
+                self.load("%rax", value);
+                let offset = self.attribute_offset(*name);
+                emit!(self.out, "\tmovq\t-8(%rbp), %rcx");
+                emit!(self.out, "\tmovq\t%rax, {}(%rcx)", offset);
+            }
+            Inst::Dispatch {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                let idx = self.layout.method_index(*class, *method).unwrap();
+                self.call(receiver, args, *span, Callee::Dispatch(idx));
+            }
+            Inst::Call {
+                receiver,
+                class,
+                method,
+                args,
+                span,
+                ..
+            } => {
+                let target = self.layout.classes().lookup_method(*class, *method);
+                let label = self.method_label(target.unwrap());
+                self.call(receiver, args, *span, Callee::Label(label));
+            }
+            Inst::New { class, .. } => self.new_object(*class),
+        }
+
+        if let Some(dest) = inst.dest() {
+            emit!(
+                self.out,
+                "\tmovq\t%rax, {}(%rbp)",
+                self.slots[dest.0 as usize]
+            );
+        }
+    }
+
+    /// Calls a method once the receiver is known not to be void, which it
+    /// is not checked for when it is `self`.
+    fn call(&mut self, receiver: &Operand, args: &[Operand], span: Span, callee: Callee) {
+        self.load("%rdi", receiver);
+        if *receiver != Operand::SelfObject {
+            let ok = self.label();
+            emit!(self.out, "\ttestq\t%rdi, %rdi");
+            emit!(self.out, "\tjne\t.L{}", ok);
+            self.runtime_error("cool_dispatch_abort", span);
+            emit!(self.out, ".L{}:", ok);
+        }
+        if let Callee::Dispatch(_) = callee {
+            emit!(self.out, "\tmovq\t{}(%rdi), %r10", DISPATCH_OFFSET);
+        }
+
+        let stacked = &args[args.len().min(ARG_REGISTERS.len())..];
+        let padding = stacked.len() % 2;
+        if padding == 1 {
+            emit!(self.out, "\tsubq\t$8, %rsp");
+        }
+        for arg in stacked.iter().rev() {
+            match arg {
+                Operand::Temp(temp) => {
+                    emit!(self.out, "\tpushq\t{}(%rbp)", self.slots[temp.0 as usize]);
+                }
+                Operand::SelfObject => emit!(self.out, "\tpushq\t-8(%rbp)"),
+                _ => {
+                    self.load("%rax", arg);
+                    emit!(self.out, "\tpushq\t%rax");
+                }
+            }
+        }
+        for (register, arg) in ARG_REGISTERS.iter().zip(args) {
+            self.load(register, arg);
+        }
+
+        match callee {
+            Callee::Label(label) => emit!(self.out, "\tcall\t{}", label),
+            Callee::Dispatch(idx) => emit!(self.out, "\tcall\t*{}(%r10)", WORD * idx as i32),
+        }
+        if !stacked.is_empty() {
+            let size = WORD * (stacked.len() + padding) as i32;
+            emit!(self.out, "\taddq\t${}, %rsp", size);
+        }
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: &Operand, right: &Operand, span: Span) {
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                let inst = match op {
+                    BinaryOp::Add => "addl",
+                    BinaryOp::Sub => "subl",
+                    _ => "imull",
+                };
+                // The result is a fresh copy of the right operand.
+                self.load("%rdi", right);
+                emit!(self.out, "\tcall\tObject.copy");
+                self.load("%rcx", left);
+                emit!(self.out, "\tmovl\t{}(%rcx), %ecx", VALUE_OFFSET);
+                emit!(self.out, "\t{}\t{}(%rax), %ecx", inst, VALUE_OFFSET);
+                emit!(self.out, "\tmovl\t%ecx, {}(%rax)", VALUE_OFFSET);
+            }
+            BinaryOp::Div => {
+                let ok = self.label();
+                self.load("%rdi", right);
+                emit!(self.out, "\tcmpl\t$0, {}(%rdi)", VALUE_OFFSET);
+                emit!(self.out, "\tjne\t.L{}", ok);
+                self.runtime_error("cool_division_by_zero", span);
+                emit!(self.out, ".L{}:", ok);
+
+                emit!(self.out, "\tcall\tObject.copy");
+                emit!(self.out, "\tmovq\t%rax, %rsi");
+                self.load("%rcx", left);
+                emit!(self.out, "\tmovl\t{}(%rcx), %eax", VALUE_OFFSET);
+                // `idivl` faults on INT_MIN / -1, so a divisor of -1 negates
+                // instead, which wraps like the interpreter.
+                let (divide, store) = (self.label(), self.label());
+                emit!(self.out, "\tcmpl\t$-1, {}(%rsi)", VALUE_OFFSET);
+                emit!(self.out, "\tjne\t.L{}", divide);
+                emit!(self.out, "\tnegl\t%eax");
+                emit!(self.out, "\tjmp\t.L{}", store);
+                emit!(self.out, ".L{}:", divide);
+                emit!(self.out, "\tcltd");
+                emit!(self.out, "\tidivl\t{}(%rsi)", VALUE_OFFSET);
+                emit!(self.out, ".L{}:", store);
+                emit!(self.out, "\tmovl\t%eax, {}(%rsi)", VALUE_OFFSET);
+                emit!(self.out, "\tmovq\t%rsi, %rax");
+            }
+            BinaryOp::Lt | BinaryOp::Lte => {
+                self.load("%rcx", left);
+                self.load("%rax", right);
+                emit!(self.out, "\tmovl\t{}(%rcx), %ecx", VALUE_OFFSET);
+                emit!(self.out, "\tcmpl\t{}(%rax), %ecx", VALUE_OFFSET);
+                self.select_bool(if matches!(op, BinaryOp::Lt) {
+                    "jl"
+                } else {
+                    "jle"
+                });
+            }
+            BinaryOp::Eq => {
+                let end = self.label();
+
+                self.load("%rdi", left);
+                self.load("%rsi", right);
+                emit!(self.out, "\tleaq\tbool_const1(%rip), %rax");
+                emit!(self.out, "\tcmpq\t%rsi, %rdi");
+                emit!(self.out, "\tje\t.L{}", end);
+                emit!(self.out, "\tcall\tcool_equals");
+                emit!(self.out, ".L{}:", end);
+            }
+        }
+    }
+
+    /// Generates a terminator, falling through when it jumps to the block
+    /// at index `next`.
+    fn terminator(&mut self, terminator: &Terminator, next: usize) {
+        match terminator {
+            Terminator::Jump(target) => self.jump(*target, next),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => {
+                self.load("%rax", condition);
+                emit!(self.out, "\tcmpq\t$0, {}(%rax)", VALUE_OFFSET);
+                emit!(self.out, "\tje\t.L{}", self.blocks[otherwise.0 as usize]);
+                self.jump(*then, next);
+            }
+            Terminator::TypeCase { value, arms, span } => self.case(value, arms, *span),
+            Terminator::Return(value) => {
+                self.load("%rax", value);
+                emit!(self.out, "\tleave");
+                emit!(self.out, "\tret");
+            }
+        }
+    }
+
+    fn jump(&mut self, target: BlockId, next: usize) {
+        if target.0 as usize != next {
+            emit!(self.out, "\tjmp\t.L{}", self.blocks[target.0 as usize]);
+        }
+    }
+
+    /// Jumps to the arm whose class is the closest ancestor of the dynamic
+    /// type of the value, by trying the most specific classes first.
+    fn case(&mut self, value: &Operand, arms: &[(InternedIdx, BlockId)], span: Span) {
+        let ok = self.label();
+
+        self.load("%rax", value);
+        emit!(self.out, "\ttestq\t%rax, %rax");
+        emit!(self.out, "\tjne\t.L{}", ok);
+        self.runtime_error("cool_case_abort2", span);
+        emit!(self.out, ".L{}:", ok);
+        emit!(self.out, "\tmovq\t{}(%rax), %rcx", TAG_OFFSET);
+
+        let mut arms = arms.to_vec();
+        arms.sort_by_key(|&(class, _)| std::cmp::Reverse(self.layout.depth(class)));
+
+        for (class, block) in arms {
+            let next = self.label();
+            let tags = self.layout.tag_range(class);
+
+            emit!(self.out, "\tcmpq\t${}, %rcx", tags.start());
+            emit!(self.out, "\tjl\t.L{}", next);
+            emit!(self.out, "\tcmpq\t${}, %rcx", tags.end());
+            emit!(self.out, "\tjle\t.L{}", self.blocks[block.0 as usize]);
+            emit!(self.out, ".L{}:", next);
+        }
+        emit!(self.out, "\tmovq\t%rax, %rdi");
+        emit!(self.out, "\tcall\tcool_case_abort");
+    }
+
+    fn new_object(&mut self, class: InternedIdx) {
+        if class == sym::SELF_TYPE {
+            // Prototypes and initializers are indexed by tag in
+            // `class_objTab`, which is looked up again after the copy.
+            self.class_entry();
+            emit!(self.out, "\tmovq\t(%rdx), %rdi");
+            emit!(self.out, "\tcall\tObject.copy");
+            emit!(self.out, "\tmovq\t%rax, %rdi");
+            self.class_entry();
+            emit!(self.out, "\tcall\t*8(%rdx)");
+        } else {
+            let name = self.interner.lookup(&class);
+            emit!(self.out, "\tleaq\t{}_protObj(%rip), %rdi", name);
+            emit!(self.out, "\tcall\tObject.copy");
+            emit!(self.out, "\tmovq\t%rax, %rdi");
+            emit!(self.out, "\tcall\t{}_init", name);
+        }
+    }
+
+    /// Loads the address of the entry of the class of `self` in
+    /// `class_objTab` into `%rdx`.
+    fn class_entry(&mut self) {
+        emit!(self.out, "\tmovq\t-8(%rbp), %rcx");
+        emit!(self.out, "\tmovq\t{}(%rcx), %rcx", TAG_OFFSET);
+        emit!(self.out, "\tshlq\t$4, %rcx");
+        emit!(self.out, "\tleaq\tclass_objTab(%rip), %rdx");
+        emit!(self.out, "\taddq\t%rcx, %rdx");
+    }
+
+    /// Loads `bool_const1` into `%rax` if the jump is taken, `bool_const0`
+    /// otherwise.
+    fn select_bool(&mut self, jump: &str) {
+        let end = self.label();
+
+        emit!(self.out, "\tleaq\tbool_const1(%rip), %rax");
+        emit!(self.out, "\t{}\t.L{}", jump, end);
+        emit!(self.out, "\tleaq\tbool_const0(%rip), %rax");
+        emit!(self.out, ".L{}:", end);
+    }
+
+    /// Calls a runtime routine taking the file name and the line of `span`.
+    fn runtime_error(&mut self, routine: &str, span: Span) {
+        let filename = self.filenames[span.file.index()];
+        emit!(self.out, "\tleaq\tstr_chars{}(%rip), %rdi", filename);
+        emit!(self.out, "\tmovq\t${}, %rsi", self.sources.line(span));
+        emit!(self.out, "\tcall\t{}", routine);
+    }
+
+    /// Loads an operand into a register.
+    fn load(&mut self, register: &str, operand: &Operand) {
+        match operand {
+            Operand::Temp(temp) => {
+                let offset = self.slots[temp.0 as usize];
+                emit!(self.out, "\tmovq\t{}(%rbp), {}", offset, register);
+            }
+            Operand::SelfObject => emit!(self.out, "\tmovq\t-8(%rbp), {}", register),
+            Operand::Int(value) => {
+                let idx = self.int_const(*value);
+                emit!(self.out, "\tleaq\tint_const{}(%rip), {}", idx, register);
+            }
+            Operand::Bool(value) => {
+                emit!(
+                    self.out,
+                    "\tleaq\tbool_const{}(%rip), {}",
+                    *value as u8,
+                    register
+                );
+            }
+            Operand::String(value) => {
+                let idx = self.string_const(value);
+                emit!(self.out, "\tleaq\tstr_const{}(%rip), {}", idx, register);
+            }
+            Operand::Void => emit!(self.out, "\tmovq\t$0, {}", register),
+        }
+    }
+
+    /// The value attributes of a type start with, as a
+    /// label or `0` for void.
+    fn default_value(&mut self, ty: InternedIdx) -> String {
+        match ty {
+            sym::INT => format!("int_const{}", self.int_const(0)),
+            sym::STRING => format!("str_const{}", self.string_const("")),
+            sym::BOOL => "bool_const0".to_owned(),
+            _ => "0".to_owned(),
+        }
+    }
+
+    fn attribute_offset(&self, name: InternedIdx) -> i32 {
+        let idx = self.layout.attribute_index(self.class, name).unwrap();
+        WORD * (HEADER_WORDS + idx as i32)
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels += 1;
+        self.labels - 1
+    }
+
+    fn method_label(&self, method: &MethodSig) -> String {
+        format!(
+            "{}.{}",
+            self.interner.lookup(&method.class),
+            self.interner.lookup(&method.name)
+        )
+    }
+
+    fn string_const(&mut self, value: &str) -> usize {
+        self.constants.string(value)
+    }
+
+    fn int_const(&mut self, value: i32) -> usize {
+        self.constants.int(value)
+    }
+}
+
+/// Writes printable characters with `.ascii` and everything else with
+/// `.byte`.
+fn emit_ascii(out: &mut String, bytes: &[u8]) {
+    let printable = |byte: &u8| (b' '..=b'~').contains(byte) && !matches!(byte, b'"' | b'\\');
+
+    for chunk in bytes.chunk_by(|a, b| printable(a) == printable(b)) {
+        if printable(&chunk[0]) {
+            emit!(out, "\t.ascii\t\"{}\"", std::str::from_utf8(chunk).unwrap());
+        } else {
+            for byte in chunk {
+                emit!(out, "\t.byte\t{}", byte);
+            }
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! Errors of every phase of the compiler, in a common form that can be
+//! rendered for humans, or as JSON or SARIF for tools.
+
+use crate::{
+    ast::{converter, lex::MAX_STRING_LEN},
+    interp,
+    ir::verify,
+    semantic,
+    util::{
+        interner::Interner,
+        source::SourceFile,
+        span::{Span, Spanned},
+    },
+};
+
+pub use self::render::Emitter;
+
+pub mod explain;
+pub mod json;
+pub mod render;
+pub mod sarif;
+
+/// How diagnostics are written, chosen with `--error-format`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    #[default]
+    Human,
+    /// One JSON object per line and diagnostic.
+    Json,
+    /// A single SARIF 2.1.0 log.
+    Sarif,
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<ErrorFormat> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            "sarif" => Some(ErrorFormat::Sarif),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// A message about a span of the source, with an optional label shown under
+/// it and notes and help shown after it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable identifier of the kind of error.
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub span: Span,
+    pub label: Option<String>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            message: message.into(),
+            span,
+            label: None,
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Diagnostic {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Diagnostic {
+        self.help.push(help.into());
+        self
+    }
+}
+
+/// A position in a source file, as both a byte offset and a line and
+/// column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    /// 1-based column, counted in bytes.
+    pub column: usize,
+    /// 1-based column, counted in characters.
+    pub char_column: usize,
+}
+
+impl Position {
+    /// Locates `offset` in `file`, clamped to the text there is.
+    pub fn new(file: &SourceFile, offset: usize) -> Position {
+        let mut offset = offset.min(file.src.len());
+        while !file.src.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let (line, column) = file.line_col(offset);
+        let start = file.line_start(line - 1);
+
+        Position {
+            offset,
+            line,
+            column,
+            char_column: file.src[start..offset].chars().count() + 1,
+        }
+    }
+
+    /// The start and end of a span.
+    pub fn span(file: &SourceFile, span: Span) -> (Position, Position) {
+        (
+            Position::new(file, span.start as usize),
+            Position::new(file, span.end as usize),
+        )
+    }
+}
+
+/// Errors that can be reported as a [`Diagnostic`].
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self, interner: &Interner) -> Diagnostic;
+}
+
+impl ToDiagnostic for Spanned<converter::Error> {
+    fn to_diagnostic(&self, _: &Interner) -> Diagnostic {
+        use converter::Error;
+
+        let span = self.span;
+
+        let diagnostic = match &self.inner {
+            Error::Unexpected(_) => Diagnostic::error("unexpected syntax", span),
+            Error::EmptyConstruct => Diagnostic::error("expected at least one element", span)
+                .with_note("blocks need an expression, `let` a binding and `case` a branch"),
+            Error::Unclosed { token, construct } => {
+                Diagnostic::error(format!("missing `{}` to close {}", token, construct), span)
+            }
+            Error::MissingSemicolon(construct) => {
+                Diagnostic::error(format!("expected `;` after {}", construct), span)
+            }
+            Error::MissingToken { token, construct } => {
+                Diagnostic::error(format!("expected `{}` in {}", token, construct), span)
+            }
+            Error::MissingNode {
+                expected,
+                after: Some(after),
+            } => Diagnostic::error(format!("expected {} after `{}`", expected, after), span),
+            Error::MissingNode {
+                expected,
+                after: None,
+            } => Diagnostic::error(format!("expected {}", expected), span),
+            Error::UnexpectedToken { token, construct } => {
+                Diagnostic::error(format!("unexpected `{}` in {}", token, construct), span)
+            }
+            Error::MissingElse => Diagnostic::error("`if` without `else`", span)
+                .with_note("every `if` needs an `else` branch, as it must have a value"),
+            Error::LowercaseClassName(name) => Diagnostic::error(
+                format!("class name `{}` must start with an uppercase letter", name),
+                span,
+            ),
+            Error::UnterminatedString => Diagnostic::error("unterminated string", span)
+                .with_note("a string ends on the line it starts on")
+                .with_help("escape the newline with `\\`, or write it as `\\n`"),
+            Error::NullInString { escaped: false } => {
+                Diagnostic::error("string contains a null character", span)
+            }
+            Error::NullInString { escaped: true } => {
+                Diagnostic::error("string contains an escaped null character", span)
+            }
+            Error::StringTooLong => Diagnostic::error(
+                format!("string is longer than {} bytes", MAX_STRING_LEN),
+                span,
+            ),
+            Error::IntegerTooLarge => Diagnostic::error("integer literal is too large", span)
+                .with_note(format!("an `Int` is at most {}", i32::MAX)),
+        };
+        diagnostic.with_code(self.inner.code())
+    }
+}
+
+impl ToDiagnostic for Spanned<semantic::Error> {
+    fn to_diagnostic(&self, interner: &Interner) -> Diagnostic {
+        use semantic::Error;
+
+        let name = |idx| interner.lookup(idx);
+        let span = self.span;
+
+        let diagnostic = match &self.inner {
+            Error::ClassRedefinition(class) => Diagnostic::error(
+                format!("class `{}` is defined more than once", name(class)),
+                span,
+            ),
+            Error::BasicClassRedefinition(class) => Diagnostic::error(
+                format!("cannot redefine basic class `{}`", name(class)),
+                span,
+            ),
+            Error::UndefinedParent { class, parent } => Diagnostic::error(
+                format!(
+                    "class `{}` inherits from undefined class `{}`",
+                    name(class),
+                    name(parent)
+                ),
+                span,
+            ),
+            Error::ForbiddenInheritance { class, parent } => Diagnostic::error(
+                format!(
+                    "class `{}` cannot inherit from `{}`",
+                    name(class),
+                    name(parent)
+                ),
+                span,
+            )
+            .with_note("`Int`, `String`, `Bool` and `SELF_TYPE` cannot be inherited from"),
+            Error::InheritanceCycle(class) => Diagnostic::error(
+                format!("class `{}` is part of an inheritance cycle", name(class)),
+                span,
+            )
+            .with_note("every class must eventually inherit from `Object`"),
+            Error::UndefinedType(ty) => {
+                Diagnostic::error(format!("undefined type `{}`", name(ty)), span)
+            }
+            Error::SelfTypeNotAllowed => Diagnostic::error("`SELF_TYPE` is not allowed here", span)
+                .with_note(
+                    "formal parameters, case branches and static dispatches cannot use `SELF_TYPE`",
+                ),
+            Error::UndeclaredIdentifier(ident) => {
+                Diagnostic::error(format!("cannot find `{}` in this scope", name(ident)), span)
+            }
+            Error::SelfAssignment => Diagnostic::error("cannot assign to `self`", span),
+            Error::SelfBinding => Diagnostic::error("`self` cannot be used as a name", span)
+                .with_help("rename the binding"),
+            Error::TypeMismatch { expected, found } => Diagnostic::error("mismatched types", span)
+                .with_label(format!(
+                    "expected `{}`, found `{}`",
+                    name(expected),
+                    name(found)
+                )),
+            Error::UndefinedMethod { class, method } => Diagnostic::error(
+                format!("no method `{}` in class `{}`", name(method), name(class)),
+                span,
+            ),
+            Error::ArgumentCountMismatch {
+                method,
+                expected,
+                found,
+            } => Diagnostic::error(
+                format!(
+                    "method `{}` takes {} but {} supplied",
+                    name(method),
+                    plural(*expected, "argument"),
+                    match found {
+                        1 => "1 was".to_owned(),
+                        _ => format!("{} were", found),
+                    }
+                ),
+                span,
+            ),
+            Error::InvalidComparison { left, right } => Diagnostic::error(
+                format!("cannot compare `{}` with `{}`", name(left), name(right)),
+                span,
+            )
+            .with_note("`Int`, `String` and `Bool` values only compare with values of their type"),
+            Error::DuplicateCaseBranch(ty) => Diagnostic::error(
+                format!("`case` has more than one branch on `{}`", name(ty)),
+                span,
+            ),
+            Error::MethodRedefinition(method) => Diagnostic::error(
+                format!("method `{}` is defined more than once", name(method)),
+                span,
+            ),
+            Error::AttributeRedefinition(attribute) => Diagnostic::error(
+                format!("attribute `{}` is defined more than once", name(attribute)),
+                span,
+            ),
+            Error::InheritedAttributeRedefinition { attribute, parent } => Diagnostic::error(
+                format!(
+                    "attribute `{}` is already defined by `{}`",
+                    name(attribute),
+                    name(parent)
+                ),
+                span,
+            )
+            .with_note("attributes cannot be overridden"),
+            Error::FormalRedefinition(param) => Diagnostic::error(
+                format!("parameter `{}` is defined more than once", name(param)),
+                span,
+            ),
+            Error::OverrideArgumentCountMismatch {
+                method,
+                expected,
+                found,
+            } => Diagnostic::error(
+                format!(
+                    "method `{}` takes {} but the method it overrides takes {}",
+                    name(method),
+                    plural(*found, "parameter"),
+                    expected
+                ),
+                span,
+            ),
+            Error::OverrideTypeMismatch {
+                method,
+                expected,
+                found,
+            } => Diagnostic::error(
+                format!(
+                    "method `{}` has a different signature than the method it overrides",
+                    name(method)
+                ),
+                span,
+            )
+            .with_label(format!(
+                "expected `{}`, found `{}`",
+                name(expected),
+                name(found)
+            )),
+            Error::MissingMainClass => Diagnostic::error("no `Main` class", span)
+                .with_help("every program starts by calling `main()` on a new `Main` object"),
+            Error::MissingMainMethod => {
+                Diagnostic::error("class `Main` has no `main` method", span)
+                    .with_help("add a `main()` method taking no parameters")
+            }
+        };
+        diagnostic.with_code(self.inner.code())
+    }
+}
+
+impl ToDiagnostic for Spanned<interp::Error> {
+    fn to_diagnostic(&self, interner: &Interner) -> Diagnostic {
+        use interp::Error;
+
+        let message = match &self.inner {
+            Error::DispatchOnVoid => "dispatch to void".to_owned(),
+            Error::CaseOnVoid => "`case` on void".to_owned(),
+            Error::CaseNoMatch(class) => {
+                format!("no `case` branch matches `{}`", interner.lookup(class))
+            }
+            Error::SubstrOutOfRange => "substring out of range".to_owned(),
+            Error::DivisionByZero => "division by zero".to_owned(),
+            Error::Abort(class) => format!("abort called from class `{}`", interner.lookup(class)),
+            Error::StackOverflow => "stack overflow".to_owned(),
+            Error::Io(error) => format!("input or output failed: {}", error),
+        };
+        Diagnostic::error(message, self.span)
+    }
+}
+
+impl ToDiagnostic for Spanned<verify::Error> {
+    fn to_diagnostic(&self, interner: &Interner) -> Diagnostic {
+        use verify::ErrorKind;
+
+        let name = |idx| interner.lookup(idx);
+
+        let message = match &self.inner.kind {
+            ErrorKind::UnknownBlock(block) => format!("jump to unknown block bb{}", block.0),
+            ErrorKind::UnknownTemp(temp) => format!("unknown temporary %{}", temp.0),
+            ErrorKind::UseBeforeDefinition(temp) => {
+                format!("temporary %{} is read before it is assigned", temp.0)
+            }
+            ErrorKind::EmptyTypeCase => "type case without arms".to_owned(),
+            ErrorKind::UndefinedClass(class) => format!("undefined class `{}`", name(class)),
+            ErrorKind::UndefinedMethod { class, method } => {
+                format!("no method `{}` in class `{}`", name(method), name(class))
+            }
+            ErrorKind::ArgumentCountMismatch {
+                method,
+                expected,
+                found,
+            } => format!(
+                "method `{}` takes {} but {} supplied",
+                name(method),
+                plural(*expected, "argument"),
+                match found {
+                    1 => "1 was".to_owned(),
+                    _ => format!("{} were", found),
+                }
+            ),
+            ErrorKind::UndefinedAttribute(attribute) => {
+                format!("undefined attribute `{}`", name(attribute))
+            }
+        };
+        Diagnostic::error(format!("invalid IR: {}", message), self.span)
+            .with_label(format!(
+                "in block bb{} of this function",
+                self.inner.block.0
+            ))
+            .with_note("this is a bug in the compiler")
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
@@ -0,0 +1,558 @@
+use std::fmt::Write;
+
+/// The longer description of an error code, shown by `cool --explain`.
+#[derive(Clone, Copy, Debug)]
+pub struct Explanation {
+    pub code: &'static str,
+    /// The message the error is reported with, without its details.
+    pub title: &'static str,
+    pub description: &'static str,
+    /// A program that fails with the error, if one can.
+    pub example: Option<&'static str>,
+}
+
+impl Explanation {
+    /// Finds the explanation of a code, whatever its case.
+    pub fn lookup(code: &str) -> Option<&'static Explanation> {
+        EXPLANATIONS
+            .iter()
+            .find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "error[{}]: {}\n\n{}\n",
+            self.code, self.title, self.description
+        );
+
+        if let Some(example) = self.example {
+            out.push_str("\nErroneous code example:\n\n");
+            for line in example.lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    writeln!(out, "    {}", line).unwrap();
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Every error code, in order.
+pub const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "C0001",
+        title: "expected at least one element",
+        description: "\
+A program, a block or a `let` is empty. A program needs at least one class, a
+block at least one expression and a `let` at least one binding.",
+        example: Some(
+            "\
+-- The classes of the program go here.
+",
+        ),
+    },
+    Explanation {
+        code: "C0002",
+        title: "missing closing token",
+        description: "\
+A construct is not closed. `if` ends with `fi`, `while` with `pool`, `case`
+with `esac`, and parentheses and braces with their closing counterpart.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { if true then 1 else 2 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0003",
+        title: "expected `;`",
+        description: "\
+A `;` is missing. Classes, attributes and methods are each followed by `;`, as
+are the expressions of a block and the branches of a `case`.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+}
+",
+        ),
+    },
+    Explanation {
+        code: "C0004",
+        title: "expected token",
+        description: "\
+A keyword or punctuation a construct needs is missing, like the `in` between
+the bindings of a `let` and its body.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { let x : Int <- 1 x + 1 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0005",
+        title: "expected expression, identifier or type",
+        description: "\
+An expression, a name or a type is missing, usually after an operator or a
+keyword that needs one.",
+        example: Some(
+            "\
+class Main {
+    x : Int;
+    main() : Int { x <- };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0006",
+        title: "unexpected token",
+        description: "\
+The parser found text it cannot make sense of where it is, and skipped it.
+The message names the first token skipped and the construct it appears in.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 1 ] };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0007",
+        title: "`if` without `else`",
+        description: "\
+An `if` has no `else` branch. Every expression has a value in COOL, so `if`
+needs a branch for when its condition is false.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { if true then 1 fi };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0008",
+        title: "class name must start with an uppercase letter",
+        description: "\
+A class is named like an object. Type names start with an uppercase letter,
+and object names with a lowercase one.",
+        example: Some(
+            "\
+class main {
+    main() : Int { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0009",
+        title: "unexpected syntax",
+        description: "\
+The tree the parser produced has a node the compiler does not expect where it
+is. Syntax errors are otherwise reported with more specific codes, so this is
+a bug in the compiler; please report it along with the program that caused it.",
+        example: None,
+    },
+    Explanation {
+        code: "C0010",
+        title: "unterminated string",
+        description: "\
+A string is not closed on the line it starts on. Strings cannot span lines,
+unless the newline is escaped with a backslash; a newline in a string is
+otherwise written `\\n`.",
+        example: Some(
+            "\
+class Main inherits IO {
+    main() : Object { out_string(\"Hello,
+world!\") };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "C0011",
+        title: "string contains a null character",
+        description: "\
+A string contains the null character, written as is or escaped with a
+backslash. COOL strings cannot contain it.",
+        example: None,
+    },
+    Explanation {
+        code: "C0012",
+        title: "string is too long",
+        description: "\
+A string is longer than 1024 bytes, once its escape sequences are decoded,
+characters outside ASCII taking more than one byte each. Split it into shorter
+strings and join them with `concat`.",
+        example: None,
+    },
+    Explanation {
+        code: "C0013",
+        title: "integer literal is too large",
+        description: "\
+An integer literal has a value too large for an `Int`, a 32-bit signed integer
+that is at most 2147483647.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 99999999999999999999999 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0001",
+        title: "class is defined more than once",
+        description: "\
+Two classes have the same name. Rename one of them.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+};
+
+class Main {
+    main() : Int { 1 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0002",
+        title: "cannot redefine basic class",
+        description: "\
+A class has the name of one of the basic classes, `Object`, `IO`, `Int`,
+`String` and `Bool`, or is named `SELF_TYPE`.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+};
+
+class String {
+    length() : Int { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0003",
+        title: "class inherits from undefined class",
+        description: "\
+The class after `inherits` is not defined in the program.",
+        example: Some(
+            "\
+class Main inherits Base {
+    main() : Int { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0004",
+        title: "class cannot inherit from this class",
+        description: "\
+A class inherits from `Int`, `String`, `Bool` or `SELF_TYPE`. Their values are
+represented differently from other objects, so no class can extend them.
+Wrap the value in an attribute instead.",
+        example: Some(
+            "\
+class Main inherits Int {
+    main() : Int { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0005",
+        title: "class is part of an inheritance cycle",
+        description: "\
+A class inherits from itself, directly or through its ancestors. Every class
+must eventually inherit from `Object`.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+};
+
+class A inherits B { };
+class B inherits A { };
+",
+        ),
+    },
+    Explanation {
+        code: "S0006",
+        title: "undefined type",
+        description: "\
+A type is neither a class of the program nor a basic class.",
+        example: Some(
+            "\
+class Main {
+    main() : Integer { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0007",
+        title: "`SELF_TYPE` is not allowed here",
+        description: "\
+`SELF_TYPE` is used where it has no meaning: as the type of a formal
+parameter, of a `case` branch, or as the class of a static dispatch.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+    copy_of(other : SELF_TYPE) : Object { other };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0008",
+        title: "cannot find identifier in this scope",
+        description: "\
+A name is neither an attribute of the class, a formal parameter of the method,
+nor bound by an enclosing `let` or `case`.",
+        example: Some(
+            "\
+class Main {
+    main() : Object { count };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0009",
+        title: "cannot assign to `self`",
+        description: "\
+An assignment has `self` on its left, as in `self <- new Main`. `self` always
+refers to the object a method was called on. The parser already rejects such
+assignments, so they are usually reported as C0006.",
+        example: None,
+    },
+    Explanation {
+        code: "S0010",
+        title: "`self` cannot be used as a name",
+        description: "\
+An attribute, a formal parameter, or a `let` or `case` binding is named `self`,
+which always refers to the object a method was called on.",
+        example: Some(
+            "\
+class Main {
+    main() : Object { let self : Int <- 0 in self };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0011",
+        title: "mismatched types",
+        description: "\
+An expression has a type that does not conform to the one expected where it
+is, like the declared type of a method, an attribute or a binding, or the
+type of a formal parameter. A type conforms to another if it is that class or
+one of its descendants.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { \"zero\" };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0012",
+        title: "no method in class",
+        description: "\
+A method is called that neither the class of the object nor any of its
+ancestors defines.",
+        example: Some(
+            "\
+class Main {
+    main() : Object { size() };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0013",
+        title: "wrong number of arguments",
+        description: "\
+A method is called with more or fewer arguments than it has formal
+parameters.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { add(1) };
+    add(a : Int, b : Int) : Int { a + b };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0014",
+        title: "cannot compare values of these types",
+        description: "\
+An `Int`, `String` or `Bool` is compared with `=` to a value of another type.
+Such values only compare with values of their own type.",
+        example: Some(
+            "\
+class Main {
+    main() : Bool { 1 = \"1\" };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0015",
+        title: "`case` has more than one branch on a type",
+        description: "\
+Two branches of a `case` have the same type, so the second could never be
+taken.",
+        example: Some(
+            "\
+class Main {
+    main() : Int {
+        case 0 of
+            a : Int => 1;
+            b : Int => 2;
+        esac
+    };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0016",
+        title: "method is defined more than once",
+        description: "\
+A class defines two methods with the same name. Methods cannot be overloaded
+in COOL; rename one of them.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+    main() : Int { 1 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0017",
+        title: "attribute is defined more than once",
+        description: "\
+A class defines two attributes with the same name.",
+        example: Some(
+            "\
+class Main {
+    count : Int;
+    count : Int;
+    main() : Int { count };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0018",
+        title: "attribute is already defined by an ancestor",
+        description: "\
+A class defines an attribute with the name of one it inherits. Unlike methods,
+attributes cannot be overridden; use the inherited one or rename it.",
+        example: Some(
+            "\
+class Base {
+    count : Int;
+};
+
+class Main inherits Base {
+    count : Int;
+    main() : Int { count };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0019",
+        title: "parameter is defined more than once",
+        description: "\
+Two formal parameters of a method have the same name.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { add(1, 2) };
+    add(a : Int, a : Int) : Int { a + a };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0020",
+        title: "overriding method has a different number of parameters",
+        description: "\
+A method overrides an inherited one, but takes a different number of formal
+parameters. An overriding method must keep the signature of the method it
+overrides.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+    abort(code : Int) : Object { code };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0021",
+        title: "overriding method has a different signature",
+        description: "\
+A method overrides an inherited one, but the type of a formal parameter or
+its return type differs. An overriding method must keep the signature of the
+method it overrides.",
+        example: Some(
+            "\
+class Main {
+    main() : Int { 0 };
+    type_name() : Int { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0022",
+        title: "no `Main` class",
+        description: "\
+The program has no class named `Main`. Programs start by calling `main()` on a
+new `Main` object.",
+        example: Some(
+            "\
+class Program {
+    main() : Int { 0 };
+};
+",
+        ),
+    },
+    Explanation {
+        code: "S0023",
+        title: "class `Main` has no `main` method",
+        description: "\
+Class `Main` neither defines nor inherits a `main` method without formal
+parameters, which programs start by calling.",
+        example: Some(
+            "\
+class Main {
+    start() : Int { 0 };
+};
+",
+        ),
+    },
+];
@@ -0,0 +1,125 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    diagnostics::{Diagnostic, Position},
+    util::source::SourceMap,
+};
+
+/// A JSON value, written without any whitespace.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Number(usize),
+    String(String),
+    Array(Vec<Json>),
+    /// Members in the order they are written.
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Json {
+        Json::String(value.to_owned())
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Json {
+        Json::Number(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => {
+                f.write_str("\"")?;
+                for c in value.chars() {
+                    match c {
+                        '"' => f.write_str("\\\"")?,
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        '\r' => f.write_str("\\r")?,
+                        '\t' => f.write_str("\\t")?,
+                        c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Json::Array(values) => {
+                f.write_str("[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(members) => {
+                f.write_str("{")?;
+                for (idx, (name, value)) in members.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}:{}", Json::from(*name), value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Renders diagnostics about the files of a source map as JSON lines, one
+/// object per diagnostic:
+///
+/// ```json
+/// {"severity":"error","code":null,"message":"mismatched types","file":"a.cl",
+///  "start":{"offset":12,"line":2,"column":3},"end":{...},"label":null,
+///  "notes":[],"help":[]}
+/// ```
+///
+/// Columns count bytes from 1, like those of human readable diagnostics.
+pub fn render(sources: &SourceMap, diagnostics: &[Diagnostic]) -> String {
+    let mut out = String::new();
+
+    for diagnostic in diagnostics {
+        let file = sources.get(diagnostic.span.file);
+        let (start, end) = Position::span(file, diagnostic.span);
+        let strings = |values: &[String]| {
+            Json::Array(values.iter().map(|value| value.as_str().into()).collect())
+        };
+
+        let object = Json::Object(vec![
+            ("severity", diagnostic.severity.as_str().into()),
+            ("code", diagnostic.code.into()),
+            ("message", diagnostic.message.as_str().into()),
+            ("file", file.name.as_str().into()),
+            ("start", position(start)),
+            ("end", position(end)),
+            ("label", diagnostic.label.as_deref().into()),
+            ("notes", strings(&diagnostic.notes)),
+            ("help", strings(&diagnostic.help)),
+        ]);
+        out.push_str(&object.to_string());
+        out.push('\n');
+    }
+
+    out
+}
+
+fn position(position: Position) -> Json {
+    Json::Object(vec![
+        ("offset", position.offset.into()),
+        ("line", position.line.into()),
+        ("column", position.column.into()),
+    ])
+}
@@ -0,0 +1,154 @@
+use std::{
+    fmt::Write,
+    io::{self, IsTerminal},
+};
+
+use crate::{
+    diagnostics::{Diagnostic, Position, Severity},
+    util::source::SourceMap,
+};
+
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+/// Columns a tab is expanded to in snippets, so that carets line up whatever
+/// the terminal does with them.
+const TAB_WIDTH: usize = 4;
+
+/// Renders diagnostics about the files of a source map the way `rustc`
+/// does: the location, the offending line with the span underlined, then
+/// notes and help.
+pub struct Emitter<'s> {
+    sources: &'s SourceMap,
+    color: bool,
+}
+
+impl<'s> Emitter<'s> {
+    pub fn new(sources: &'s SourceMap, color: bool) -> Emitter<'s> {
+        Emitter { sources, color }
+    }
+
+    /// Whether diagnostics written to stderr should be colored, i.e. it is a
+    /// terminal and `NO_COLOR` is not set.
+    pub fn stderr_supports_color() -> bool {
+        io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+        let file = self.sources.get(diagnostic.span.file);
+        let (start, end) = Position::span(file, diagnostic.span);
+        let (line, col) = (start.line, start.column);
+        let gutter = " ".repeat(line.to_string().len());
+
+        let severity = match diagnostic.code {
+            Some(code) => format!("{}[{}]", diagnostic.severity.as_str(), code),
+            None => diagnostic.severity.as_str().to_owned(),
+        };
+        writeln!(
+            out,
+            "{}{}",
+            self.paint(&severity, color),
+            self.paint(&format!(": {}", diagnostic.message), BOLD)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "{}{} {}:{}:{}",
+            gutter,
+            self.paint("-->", BLUE),
+            file.name,
+            line,
+            col
+        )
+        .unwrap();
+
+        let text = file.line_text(line - 1);
+        // Spans over several lines are underlined up to the end of the
+        // first one.
+        let start = (col - 1).min(text.len());
+        let end = if end.line == line {
+            (end.column - 1).clamp(start, text.len())
+        } else {
+            text.len()
+        };
+        let offset = display_width(&text[..start]);
+        let width = display_width(&text[start..end]).max(1);
+
+        let bar = self.paint("|", BLUE);
+        writeln!(out, "{} {}", gutter, bar).unwrap();
+        writeln!(
+            out,
+            "{} {} {}",
+            self.paint(&line.to_string(), BLUE),
+            bar,
+            expand_tabs(text)
+        )
+        .unwrap();
+
+        let mut underline = "^".repeat(width);
+        if let Some(label) = &diagnostic.label {
+            underline = format!("{} {}", underline, label);
+        }
+        writeln!(
+            out,
+            "{} {} {}{}",
+            gutter,
+            bar,
+            " ".repeat(offset),
+            self.paint(&underline, color)
+        )
+        .unwrap();
+
+        if !diagnostic.notes.is_empty() || !diagnostic.help.is_empty() {
+            writeln!(out, "{} {}", gutter, self.paint("|", BLUE)).unwrap();
+        }
+        let notes = diagnostic.notes.iter().map(|note| ("note", note));
+        for (kind, message) in notes.chain(diagnostic.help.iter().map(|help| ("help", help))) {
+            writeln!(
+                out,
+                "{} {} {}: {}",
+                gutter,
+                self.paint("=", BLUE),
+                self.paint(kind, BOLD),
+                message
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    /// Renders several diagnostics, separated by blank lines.
+    pub fn render_all(&self, diagnostics: &[Diagnostic]) -> String {
+        diagnostics
+            .iter()
+            .map(|diagnostic| self.render(diagnostic))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", style, text)
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
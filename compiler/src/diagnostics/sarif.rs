@@ -0,0 +1,103 @@
+use crate::{
+    diagnostics::{Diagnostic, Position, Severity, json::Json},
+    util::source::SourceMap,
+};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders diagnostics about the files of a source map as a SARIF 2.1.0 log
+/// with a single run.
+///
+/// Columns count characters rather than UTF-16 code units, as the run
+/// declares.
+pub fn render(sources: &SourceMap, diagnostics: &[Diagnostic]) -> String {
+    let mut codes: Vec<_> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code)
+        .collect();
+    codes.sort();
+    codes.dedup();
+
+    let rules = codes
+        .into_iter()
+        .map(|code| Json::Object(vec![("id", code.into())]))
+        .collect();
+    let driver = Json::Object(vec![
+        ("name", "cool".into()),
+        ("version", env!("CARGO_PKG_VERSION").into()),
+        ("rules", Json::Array(rules)),
+    ]);
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| result(sources, diagnostic))
+        .collect();
+
+    let log = Json::Object(vec![
+        ("$schema", SCHEMA.into()),
+        ("version", "2.1.0".into()),
+        (
+            "runs",
+            Json::Array(vec![Json::Object(vec![
+                ("tool", Json::Object(vec![("driver", driver)])),
+                ("columnKind", "unicodeCodePoints".into()),
+                ("results", Json::Array(results)),
+            ])]),
+        ),
+    ]);
+    format!("{}\n", log)
+}
+
+fn result(sources: &SourceMap, diagnostic: &Diagnostic) -> Json {
+    let file = sources.get(diagnostic.span.file);
+    let (start, end) = Position::span(file, diagnostic.span);
+
+    // SARIF messages are plain text, so the label, notes and help follow the
+    // message the way they would in a terminal.
+    let mut text = diagnostic.message.clone();
+    if let Some(label) = &diagnostic.label {
+        text = format!("{}: {}", text, label);
+    }
+    for note in &diagnostic.notes {
+        text = format!("{}\nnote: {}", text, note);
+    }
+    for help in &diagnostic.help {
+        text = format!("{}\nhelp: {}", text, help);
+    }
+
+    let region = Json::Object(vec![
+        ("startLine", start.line.into()),
+        ("startColumn", start.char_column.into()),
+        ("endLine", end.line.into()),
+        ("endColumn", end.char_column.into()),
+        ("byteOffset", start.offset.into()),
+        ("byteLength", end.offset.saturating_sub(start.offset).into()),
+    ]);
+    let location = Json::Object(vec![(
+        "physicalLocation",
+        Json::Object(vec![
+            (
+                "artifactLocation",
+                Json::Object(vec![("uri", file.name.as_str().into())]),
+            ),
+            ("region", region),
+        ]),
+    )]);
+
+    let mut members = Vec::new();
+    if let Some(code) = diagnostic.code {
+        members.push(("ruleId", code.into()));
+    }
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    members.extend([
+        ("level", level.into()),
+        (
+            "message",
+            Json::Object(vec![("text", text.as_str().into())]),
+        ),
+        ("locations", Json::Array(vec![location])),
+    ]);
+    Json::Object(members)
+}
@@ -0,0 +1,492 @@
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+use crate::{
+    ast::{self, BinaryOp, ExprKind, UnaryOp},
+    semantic::{ClassTable, MethodSig},
+    util::{
+        interner::{InternedIdx, Interner, sym},
+        span::{Span, Spanned},
+    },
+};
+
+pub use self::value::{Object, Value};
+
+pub mod value;
+
+/// Nesting of method calls after which the program is assumed to recurse
+/// infinitely.
+const MAX_DEPTH: usize = 10_000;
+
+pub type RunResult<T> = Result<T, Spanned<Error>>;
+
+/// Evaluates a program that passed semantic analysis, starting by
+/// dispatching `main()` on a new `Main` object.
+///
+/// Built-in methods of `IO` read from `input` and write to `output`.
+pub fn run<R: BufRead, W: Write>(
+    classes: &ClassTable,
+    interner: &Interner,
+    input: R,
+    output: W,
+) -> RunResult<()> {
+    let mut interp = Interpreter {
+        classes,
+        interner,
+        input,
+        output,
+        frame: Frame::new(Value::Void),
+        depth: 0,
+    };
+    // Errors outside of any expression, like failing to flush the output,
+    // are reported at the declaration of `Main`.
+    let entry = classes
+        .get(sym::MAIN_CLASS)
+        .and_then(|main| main.decl)
+        .map_or_else(Span::default, |decl| decl.span);
+
+    let main = interp.new_object(sym::MAIN_CLASS, entry)?;
+    interp.dispatch(main, sym::MAIN_CLASS, sym::MAIN, Vec::new(), entry)?;
+    interp.output.flush().map_err(|error| Spanned {
+        inner: Error::Io(error),
+        span: entry,
+    })
+}
+
+#[derive(Debug)]
+pub enum Error {
+    DispatchOnVoid,
+    CaseOnVoid,
+    /// No branch of a `case` matches the dynamic type of the value.
+    CaseNoMatch(InternedIdx),
+    SubstrOutOfRange,
+    DivisionByZero,
+    /// `abort()` was called on an object of the given class.
+    Abort(InternedIdx),
+    StackOverflow,
+    Io(io::Error),
+}
+
+/// Bindings visible while evaluating a method body or an attribute
+/// initializer.
+struct Frame {
+    self_value: Value,
+    /// Formals, `let` and `case` bindings in scope, innermost last.
+    locals: Vec<(InternedIdx, Value)>,
+}
+
+impl Frame {
+    fn new(self_value: Value) -> Frame {
+        Frame {
+            self_value,
+            locals: Vec::new(),
+        }
+    }
+}
+
+struct Interpreter<'c, 'a, R, W> {
+    classes: &'c ClassTable<'a>,
+    interner: &'c Interner,
+    input: R,
+    output: W,
+    frame: Frame,
+    /// Number of method calls being evaluated.
+    depth: usize,
+}
+
+impl<R: BufRead, W: Write> Interpreter<'_, '_, R, W> {
+    fn eval(&mut self, expr: &ast::Expr) -> RunResult<Value> {
+        let value = match &expr.kind {
+            ExprKind::Assignment { name, right } => {
+                let value = self.eval(right)?;
+                self.assign(name.name, value.clone());
+                value
+            }
+            ExprKind::Dispatch {
+                qualifier,
+                method,
+                args,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<RunResult<Vec<_>>>()?;
+
+                let (receiver, class) = match qualifier {
+                    Some(qualifier) => {
+                        let receiver = self.eval(&qualifier.value)?;
+                        let class = match &qualifier.parent {
+                            Some(ast::Type(parent)) => Some(parent.name),
+                            None => receiver.class(),
+                        };
+                        (receiver, class)
+                    }
+                    None => {
+                        let receiver = self.frame.self_value.clone();
+                        let class = receiver.class();
+                        (receiver, class)
+                    }
+                };
+                let Some(class) = class.filter(|_| !receiver.is_void()) else {
+                    return self.error(Error::DispatchOnVoid, method.span);
+                };
+                self.dispatch(receiver, class, method.name, args, expr.span)?
+            }
+            ExprKind::Conditional {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                if self.eval_bool(condition)? {
+                    self.eval(consequence)?
+                } else {
+                    self.eval(alternative)?
+                }
+            }
+            ExprKind::Repeat { condition, body } => {
+                while self.eval_bool(condition)? {
+                    self.eval(body)?;
+                }
+                Value::Void
+            }
+            ExprKind::Block { body } => {
+                let mut value = Value::Void;
+                for expr in body {
+                    value = self.eval(expr)?;
+                }
+                value
+            }
+            ExprKind::Let { bindings, body } => {
+                let depth = self.frame.locals.len();
+
+                for binding in &bindings.0 {
+                    let value = match &binding.right {
+                        Some(right) => self.eval(right)?,
+                        None => Value::default_for(binding.ty.0.name),
+                    };
+                    self.frame.locals.push((binding.name.name, value));
+                }
+                let value = self.eval(body);
+                self.frame.locals.truncate(depth);
+
+                value?
+            }
+            ExprKind::Case { value, body } => {
+                let value = self.eval(value)?;
+                let Some(class) = value.class() else {
+                    return self.error(Error::CaseOnVoid, expr.span);
+                };
+                // The branch with the closest ancestor of the dynamic type
+                // is selected.
+                let arm = self
+                    .classes
+                    .ancestors(class)
+                    .find_map(|ancestor| body.iter().find(|arm| arm.pat.ty.0.name == ancestor));
+                let Some(arm) = arm else {
+                    return self.error(Error::CaseNoMatch(class), expr.span);
+                };
+
+                self.frame.locals.push((arm.pat.name.name, value));
+                let value = self.eval(&arm.value);
+                self.frame.locals.pop();
+
+                value?
+            }
+            ExprKind::New { ty } => {
+                let class = match ty.0.name {
+                    sym::SELF_TYPE => self.frame.self_value.class().unwrap(),
+                    name => name,
+                };
+                self.new_object(class, expr.span)?
+            }
+            ExprKind::Unary { op, right } => {
+                let right = self.eval(right)?;
+
+                match op {
+                    UnaryOp::IsVoid => Value::Bool(right.is_void()),
+                    UnaryOp::Complement => Value::Int(int(&right).wrapping_neg()),
+                    UnaryOp::Not => Value::Bool(!bool(&right)),
+                }
+            }
+            ExprKind::Binary { op, left, right } => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+
+                match op {
+                    BinaryOp::Add => Value::Int(int(&left).wrapping_add(int(&right))),
+                    BinaryOp::Sub => Value::Int(int(&left).wrapping_sub(int(&right))),
+                    BinaryOp::Mul => Value::Int(int(&left).wrapping_mul(int(&right))),
+                    BinaryOp::Div => match int(&right) {
+                        0 => return self.error(Error::DivisionByZero, expr.span),
+                        right => Value::Int(int(&left).wrapping_div(right)),
+                    },
+                    BinaryOp::Lt => Value::Bool(int(&left) < int(&right)),
+                    BinaryOp::Lte => Value::Bool(int(&left) <= int(&right)),
+                    BinaryOp::Eq => Value::Bool(left.equals(&right)),
+                }
+            }
+            ExprKind::Paren { value } => self.eval(value)?,
+            ExprKind::Ident(ident) => self.lookup(ident.name),
+            ExprKind::String(value) => Value::String(Rc::from(value.as_bytes())),
+            ExprKind::Int(value) => Value::Int(*value),
+            ExprKind::Bool(value) => Value::Bool(*value),
+        };
+
+        Ok(value)
+    }
+
+    fn eval_bool(&mut self, expr: &ast::Expr) -> RunResult<bool> {
+        self.eval(expr).map(|value| bool(&value))
+    }
+
+    /// Calls the most specific definition of `method` visible from `class`
+    /// on `receiver`.
+    fn dispatch(
+        &mut self,
+        receiver: Value,
+        class: InternedIdx,
+        method: InternedIdx,
+        args: Vec<Value>,
+        span: Span,
+    ) -> RunResult<Value> {
+        let classes = self.classes;
+        let sig = classes
+            .lookup_method(class, method)
+            .expect("dispatch to be type checked");
+
+        let Some(decl) = sig.decl else {
+            return self.builtin(sig, receiver, args, span);
+        };
+        if self.depth == MAX_DEPTH {
+            return self.error(Error::StackOverflow, span);
+        }
+
+        let mut frame = Frame::new(receiver);
+        let params = decl.params.0.iter().map(|param| param.name.name);
+        frame.locals.extend(params.zip(args));
+
+        self.depth += 1;
+        let caller = std::mem::replace(&mut self.frame, frame);
+        let value = self.eval(&decl.body);
+        self.frame = caller;
+        self.depth -= 1;
+
+        value
+    }
+
+    fn builtin(
+        &mut self,
+        sig: &MethodSig,
+        receiver: Value,
+        args: Vec<Value>,
+        span: Span,
+    ) -> RunResult<Value> {
+        let value = match sig.name {
+            sym::ABORT => {
+                let result = self.output.flush();
+                self.io(result, span)?;
+
+                let class = receiver.class().unwrap();
+                return self.error(Error::Abort(class), span);
+            }
+            sym::TYPE_NAME => {
+                let class = receiver.class().unwrap();
+                Value::String(Rc::from(self.interner.lookup(&class).as_bytes()))
+            }
+            sym::COPY => receiver.shallow_copy(),
+            sym::OUT_STRING => {
+                let result = self.output.write_all(string(&args[0]));
+                self.io(result, span)?;
+                receiver
+            }
+            sym::OUT_INT => {
+                let result = write!(self.output, "{}", int(&args[0]));
+                self.io(result, span)?;
+                receiver
+            }
+            sym::IN_STRING => {
+                let line = self.read_line(span)?;
+                Value::String(Rc::from(line))
+            }
+            sym::IN_INT => {
+                let line = self.read_line(span)?;
+                Value::Int(parse_int(&String::from_utf8_lossy(&line)))
+            }
+            sym::LENGTH => Value::Int(string(&receiver).len() as i32),
+            sym::CONCAT => {
+                let value = [string(&receiver), string(&args[0])].concat();
+                Value::String(Rc::from(value))
+            }
+            sym::SUBSTR => {
+                let value = string(&receiver);
+                let (start, len) = (int(&args[0]), int(&args[1]));
+
+                let range = usize::try_from(start)
+                    .ok()
+                    .zip(usize::try_from(len).ok())
+                    .map(|(start, len)| start..start + len)
+                    .filter(|range| range.end <= value.len());
+                match range.and_then(|range| value.get(range)) {
+                    Some(value) => Value::String(Rc::from(value)),
+                    None => return self.error(Error::SubstrOutOfRange, span),
+                }
+            }
+            _ => unreachable!("every basic method has a built-in implementation"),
+        };
+
+        Ok(value)
+    }
+
+    /// Allocates an object of `class`, initializing the attributes of its
+    /// ancestors before its own.
+    fn new_object(&mut self, class: InternedIdx, span: Span) -> RunResult<Value> {
+        if [sym::INT, sym::BOOL, sym::STRING].contains(&class) {
+            return Ok(Value::default_for(class));
+        }
+
+        let mut ancestors: Vec<_> = self.classes.ancestors(class).collect();
+        ancestors.reverse();
+        let attributes = ancestors
+            .iter()
+            .flat_map(|&ancestor| &self.classes.get(ancestor).unwrap().attributes)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let fields = attributes
+            .iter()
+            .map(|attribute| (attribute.name.name, Value::default_for(attribute.ty.0.name)))
+            .collect();
+        let object = Value::Object(Rc::new(RefCell::new(Object { class, fields })));
+
+        if self.depth == MAX_DEPTH {
+            return self.error(Error::StackOverflow, span);
+        }
+        self.depth += 1;
+        let caller = std::mem::replace(&mut self.frame, Frame::new(object.clone()));
+
+        let mut result = Ok(());
+        for attribute in attributes {
+            let Some(initializer) = &attribute.initializer else {
+                continue;
+            };
+            match self.eval(initializer) {
+                Ok(value) => self.assign(attribute.name.name, value),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+
+        self.frame = caller;
+        self.depth -= 1;
+
+        result.map(|_| object)
+    }
+
+    fn lookup(&self, name: InternedIdx) -> Value {
+        if name == sym::SELF {
+            return self.frame.self_value.clone();
+        }
+        let local = self
+            .frame
+            .locals
+            .iter()
+            .rev()
+            .find(|(other, _)| *other == name);
+
+        match local {
+            Some((_, value)) => value.clone(),
+            None => match &self.frame.self_value {
+                Value::Object(object) => object.borrow().fields[&name].clone(),
+                _ => unreachable!("identifiers to be resolved"),
+            },
+        }
+    }
+
+    fn assign(&mut self, name: InternedIdx, value: Value) {
+        let local = self
+            .frame
+            .locals
+            .iter_mut()
+            .rev()
+            .find(|(other, _)| *other == name);
+
+        match local {
+            Some((_, slot)) => *slot = value,
+            None => match &self.frame.self_value {
+                Value::Object(object) => {
+                    object.borrow_mut().fields.insert(name, value);
+                }
+                _ => unreachable!("identifiers to be resolved"),
+            },
+        }
+    }
+
+    /// Reads a line of input without its terminator, flushing any pending
+    /// output first so that prompts are visible.
+    fn read_line(&mut self, span: Span) -> RunResult<Vec<u8>> {
+        let result = self.output.flush();
+        self.io(result, span)?;
+
+        let mut line = Vec::new();
+        let result = self.input.read_until(b'\n', &mut line);
+        self.io(result, span)?;
+
+        if line.ends_with(b"\n") {
+            line.pop();
+            if line.ends_with(b"\r") {
+                line.pop();
+            }
+        }
+        Ok(line)
+    }
+
+    fn io<T>(&self, result: io::Result<T>, span: Span) -> RunResult<T> {
+        result.map_err(|error| Spanned {
+            inner: Error::Io(error),
+            span,
+        })
+    }
+
+    fn error<T>(&self, error: Error, span: Span) -> RunResult<T> {
+        Err(Spanned { inner: error, span })
+    }
+}
+
+/// Parses a leading, optionally negative, integer, yielding 0 when there is
+/// none or it does not fit in an `Int`.
+fn parse_int(line: &str) -> i32 {
+    let line = line.trim_start();
+    let digits = line.strip_prefix('-').unwrap_or(line);
+    let len = digits.bytes().take_while(u8::is_ascii_digit).count();
+    let end = line.len() - digits.len() + len;
+
+    line[..end].parse().unwrap_or(0)
+}
+
+// Operands are guaranteed to have the right type by the type checker.
+
+fn int(value: &Value) -> i32 {
+    match value {
+        Value::Int(value) => *value,
+        _ => unreachable!("expected an Int, found {:?}", value),
+    }
+}
+
+fn bool(value: &Value) -> bool {
+    match value {
+        Value::Bool(value) => *value,
+        _ => unreachable!("expected a Bool, found {:?}", value),
+    }
+}
+
+fn string(value: &Value) -> &[u8] {
+    match value {
+        Value::String(value) => value,
+        _ => unreachable!("expected a String, found {:?}", value),
+    }
+}
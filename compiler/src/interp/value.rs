@@ -0,0 +1,77 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rustc_hash::FxHashMap;
+
+use crate::util::interner::{InternedIdx, sym};
+
+/// A runtime value.
+///
+/// `Int`, `Bool` and `String` are stored unboxed and compared by value, every
+/// other object lives behind a shared reference and is compared by identity.
+#[derive(Clone, Debug)]
+pub enum Value {
+    Void,
+    Int(i32),
+    Bool(bool),
+    /// The bytes of the string, which `length` and `substr` count in.
+    String(Rc<[u8]>),
+    Object(Rc<RefCell<Object>>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Object {
+    /// The dynamic type of the object.
+    pub class: InternedIdx,
+    /// Attributes of the class and of all its ancestors.
+    pub fields: FxHashMap<InternedIdx, Value>,
+}
+
+impl Value {
+    /// The value of an attribute or `let` binding without an initializer.
+    pub fn default_for(ty: InternedIdx) -> Value {
+        match ty {
+            sym::INT => Value::Int(0),
+            sym::BOOL => Value::Bool(false),
+            sym::STRING => Value::String(Rc::from(&b""[..])),
+            _ => Value::Void,
+        }
+    }
+
+    /// The dynamic type of the value, absent for `void`.
+    pub fn class(&self) -> Option<InternedIdx> {
+        match self {
+            Value::Void => None,
+            Value::Int(_) => Some(sym::INT),
+            Value::Bool(_) => Some(sym::BOOL),
+            Value::String(_) => Some(sym::STRING),
+            Value::Object(object) => Some(object.borrow().class),
+        }
+    }
+
+    pub fn is_void(&self) -> bool {
+        matches!(self, Value::Void)
+    }
+
+    /// Equality as defined by the `=` operator.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Void, Value::Void) => true,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// A shallow copy, as returned by `Object.copy`.
+    pub fn shallow_copy(&self) -> Value {
+        match self {
+            Value::Object(object) => {
+                let object = object.borrow().clone();
+                Value::Object(Rc::new(RefCell::new(object)))
+            }
+            other => other.clone(),
+        }
+    }
+}
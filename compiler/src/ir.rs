@@ -0,0 +1,269 @@
+//! Three-address intermediate representation, between the typed AST and the
+//! backends.
+//!
+//! Every method and class initializer is lowered to a [`Function`]: a
+//! control-flow graph of basic blocks whose instructions read operands and
+//! write temporaries. Formals and `let` and `case` bindings are temporaries
+//! too. Temporaries are not in SSA form: bindings may be assigned any number
+//! of times, and the result of a conditional or a `case` is assigned once in
+//! each of its branches.
+
+use std::rc::Rc;
+
+use crate::{
+    ast::BinaryOp,
+    util::{interner::InternedIdx, span::Span},
+};
+
+pub use self::{lower::lower, print::print, verify::verify};
+
+pub mod lower;
+pub mod opt;
+pub mod print;
+pub mod verify;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Temp(pub u32);
+
+/// Index of a block in [`Function::blocks`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub u32);
+
+#[derive(Debug)]
+pub struct Program {
+    pub functions: Vec<Function>,
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub class: InternedIdx,
+    pub kind: FunctionKind,
+    /// Temporaries holding the formal parameters, `self` excluded.
+    pub params: Vec<Temp>,
+    /// Number of temporaries, parameters included.
+    pub temps: u32,
+    /// Basic blocks, starting with the entry block.
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    /// Initializes the attributes the class defines, once those of its
+    /// parent are.
+    Init,
+    Method(InternedIdx),
+}
+
+#[derive(Debug)]
+pub struct Block {
+    pub insts: Vec<Inst>,
+    pub terminator: Terminator,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Temp(Temp),
+    SelfObject,
+    Int(i32),
+    Bool(bool),
+    String(Rc<str>),
+    Void,
+}
+
+#[derive(Debug)]
+pub enum Inst {
+    Copy {
+        dest: Temp,
+        value: Operand,
+    },
+    Unary {
+        dest: Temp,
+        op: UnaryOp,
+        value: Operand,
+    },
+    IsVoid {
+        dest: Temp,
+        value: Operand,
+    },
+    /// `Div` reports a division by zero at `span`.
+    Binary {
+        dest: Temp,
+        op: BinaryOp,
+        left: Operand,
+        right: Operand,
+        span: Span,
+    },
+    /// Reads an attribute of `self`.
+    GetAttr {
+        dest: Temp,
+        name: InternedIdx,
+    },
+    SetAttr {
+        name: InternedIdx,
+        value: Operand,
+    },
+    /// Calls the method the dynamic type of the receiver has, where `class`
+    /// is its static type. A void receiver is reported at `span`.
+    Dispatch {
+        dest: Temp,
+        receiver: Operand,
+        class: InternedIdx,
+        method: InternedIdx,
+        args: Vec<Operand>,
+        span: Span,
+    },
+    /// Calls the method `class` has, whatever the dynamic type of the
+    /// receiver. A void receiver is reported at `span`.
+    Call {
+        dest: Temp,
+        receiver: Operand,
+        class: InternedIdx,
+        method: InternedIdx,
+        args: Vec<Operand>,
+        span: Span,
+    },
+    /// Creates an object, `SELF_TYPE` standing for the dynamic type of
+    /// `self`.
+    New {
+        dest: Temp,
+        class: InternedIdx,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Complement,
+    Not,
+}
+
+#[derive(Debug)]
+pub enum Terminator {
+    Jump(BlockId),
+    Branch {
+        condition: Operand,
+        then: BlockId,
+        otherwise: BlockId,
+    },
+    /// Jumps to the arm whose class is the closest ancestor of the dynamic
+    /// type of the value. A void value, or one no arm matches, is reported
+    /// at `span`.
+    TypeCase {
+        value: Operand,
+        arms: Vec<(InternedIdx, BlockId)>,
+        span: Span,
+    },
+    Return(Operand),
+}
+
+impl Program {
+    /// The initializer of a class, which only the user classes have.
+    pub fn init(&self, class: InternedIdx) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|function| function.class == class && function.kind == FunctionKind::Init)
+    }
+}
+
+impl Function {
+    /// An initializer leaving the attributes of the class as its prototype
+    /// has them, for the classes [`Program::init`] has none for.
+    pub fn empty_init(class: InternedIdx) -> Function {
+        Function {
+            class,
+            kind: FunctionKind::Init,
+            params: Vec::new(),
+            temps: 0,
+            blocks: vec![Block {
+                insts: Vec::new(),
+                terminator: Terminator::Return(Operand::SelfObject),
+            }],
+        }
+    }
+}
+
+impl Inst {
+    /// The temporary the instruction assigns, if any.
+    pub fn dest(&self) -> Option<Temp> {
+        match *self {
+            Inst::Copy { dest, .. }
+            | Inst::Unary { dest, .. }
+            | Inst::IsVoid { dest, .. }
+            | Inst::Binary { dest, .. }
+            | Inst::GetAttr { dest, .. }
+            | Inst::Dispatch { dest, .. }
+            | Inst::Call { dest, .. }
+            | Inst::New { dest, .. } => Some(dest),
+            Inst::SetAttr { .. } => None,
+        }
+    }
+
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Inst::Copy { value, .. }
+            | Inst::Unary { value, .. }
+            | Inst::IsVoid { value, .. }
+            | Inst::SetAttr { value, .. } => vec![value],
+            Inst::Binary { left, right, .. } => vec![left, right],
+            Inst::Dispatch { receiver, args, .. } | Inst::Call { receiver, args, .. } => {
+                std::iter::once(receiver).chain(args).collect()
+            }
+            Inst::GetAttr { .. } | Inst::New { .. } => Vec::new(),
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Inst::Copy { value, .. }
+            | Inst::Unary { value, .. }
+            | Inst::IsVoid { value, .. }
+            | Inst::SetAttr { value, .. } => vec![value],
+            Inst::Binary { left, right, .. } => vec![left, right],
+            Inst::Dispatch { receiver, args, .. } | Inst::Call { receiver, args, .. } => {
+                std::iter::once(receiver).chain(args).collect()
+            }
+            Inst::GetAttr { .. } | Inst::New { .. } => Vec::new(),
+        }
+    }
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![*then, *otherwise],
+            Terminator::TypeCase { arms, .. } => arms.iter().map(|&(_, block)| block).collect(),
+            Terminator::Return(_) => Vec::new(),
+        }
+    }
+
+    pub fn successors_mut(&mut self) -> Vec<&mut BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![then, otherwise],
+            Terminator::TypeCase { arms, .. } => arms.iter_mut().map(|(_, block)| block).collect(),
+            Terminator::Return(_) => Vec::new(),
+        }
+    }
+
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Terminator::Jump(_) => Vec::new(),
+            Terminator::Branch { condition, .. } => vec![condition],
+            Terminator::TypeCase { value, .. } => vec![value],
+            Terminator::Return(value) => vec![value],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Terminator::Jump(_) => Vec::new(),
+            Terminator::Branch { condition, .. } => vec![condition],
+            Terminator::TypeCase { value, .. } => vec![value],
+            Terminator::Return(value) => vec![value],
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    ast::{self, ExprKind},
+    ir::{
+        Block, BlockId, Function, FunctionKind, Inst, Operand, Program, Temp, Terminator, UnaryOp,
+    },
+    semantic::Analysis,
+    util::interner::{InternedIdx, sym},
+};
+
+/// Lowers the methods and attribute initializers of the user classes of a
+/// program that passed semantic analysis.
+pub fn lower(analysis: &Analysis) -> Program {
+    let mut lowerer = Lowerer {
+        analysis,
+        class: sym::OBJECT,
+        scope: Vec::new(),
+        temps: 0,
+        blocks: Vec::new(),
+        current: BlockId(0),
+    };
+    let mut functions = Vec::new();
+
+    for class in analysis.classes.iter() {
+        if class.decl.is_none() {
+            continue;
+        }
+        lowerer.class = class.name;
+
+        functions.push(lowerer.function(FunctionKind::Init, &[], |this| {
+            for attribute in &class.attributes {
+                if let Some(initializer) = &attribute.initializer {
+                    let value = this.expr(initializer);
+                    this.push(Inst::SetAttr {
+                        name: attribute.name.name,
+                        value,
+                    });
+                }
+            }
+            Operand::SelfObject
+        }));
+
+        for method in &class.methods {
+            let Some(decl) = method.decl else {
+                continue;
+            };
+            let kind = FunctionKind::Method(method.name);
+            functions.push(lowerer.function(kind, &decl.params.0, |this| this.expr(&decl.body)));
+        }
+    }
+
+    Program { functions }
+}
+
+struct Lowerer<'l, 'a> {
+    analysis: &'l Analysis<'a>,
+    /// The class whose features are being lowered.
+    class: InternedIdx,
+    /// Formals, `let` and `case` bindings in scope with their temporary,
+    /// innermost last.
+    scope: Vec<(InternedIdx, Temp)>,
+    temps: u32,
+    /// Blocks of the function being lowered, terminated once complete.
+    blocks: Vec<(Vec<Inst>, Option<Terminator>)>,
+    /// The block instructions are being appended to.
+    current: BlockId,
+}
+
+impl Lowerer<'_, '_> {
+    fn function(
+        &mut self,
+        kind: FunctionKind,
+        params: &[ast::Param],
+        body: impl FnOnce(&mut Self) -> Operand,
+    ) -> Function {
+        self.temps = 0;
+        self.current = self.new_block();
+
+        let params = params
+            .iter()
+            .map(|param| {
+                let temp = self.temp();
+                self.scope.push((param.name.name, temp));
+                temp
+            })
+            .collect();
+
+        let value = body(self);
+        self.terminate(Terminator::Return(value));
+        self.scope.clear();
+
+        let blocks = std::mem::take(&mut self.blocks)
+            .into_iter()
+            .map(|(insts, terminator)| Block {
+                insts,
+                terminator: terminator.unwrap(),
+            })
+            .collect();
+
+        Function {
+            class: self.class,
+            kind,
+            params,
+            temps: self.temps,
+            blocks,
+        }
+    }
+
+    fn expr(&mut self, expr: &ast::Expr) -> Operand {
+        match &expr.kind {
+            ExprKind::Assignment { name, right } => {
+                let value = self.expr(right);
+
+                match self.lookup(name.name) {
+                    Some(dest) => self.push(Inst::Copy {
+                        dest,
+                        value: value.clone(),
+                    }),
+                    None => self.push(Inst::SetAttr {
+                        name: name.name,
+                        value: value.clone(),
+                    }),
+                }
+                value
+            }
+            ExprKind::Dispatch {
+                qualifier,
+                method,
+                args,
+            } => {
+                let args = args.iter().map(|arg| self.expr(arg)).collect();
+                let dest = self.temp();
+
+                let inst = match qualifier {
+                    Some(ast::Qualifier {
+                        value,
+                        parent: Some(ast::Type(parent)),
+                    }) => Inst::Call {
+                        dest,
+                        receiver: self.expr(value),
+                        class: parent.name,
+                        method: method.name,
+                        args,
+                        span: expr.span,
+                    },
+                    Some(ast::Qualifier {
+                        value,
+                        parent: None,
+                    }) => Inst::Dispatch {
+                        dest,
+                        receiver: self.expr(value),
+                        class: self.static_type(value),
+                        method: method.name,
+                        args,
+                        span: expr.span,
+                    },
+                    None => Inst::Dispatch {
+                        dest,
+                        receiver: Operand::SelfObject,
+                        class: self.class,
+                        method: method.name,
+                        args,
+                        span: expr.span,
+                    },
+                };
+                self.push(inst);
+
+                Operand::Temp(dest)
+            }
+            ExprKind::Conditional {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                let condition = self.expr(condition);
+                let (then, otherwise, end) = (self.new_block(), self.new_block(), self.new_block());
+                let dest = self.temp();
+
+                self.terminate(Terminator::Branch {
+                    condition,
+                    then,
+                    otherwise,
+                });
+                for (block, branch) in [(then, consequence), (otherwise, alternative)] {
+                    self.current = block;
+                    let value = self.expr(branch);
+                    self.push(Inst::Copy { dest, value });
+                    self.terminate(Terminator::Jump(end));
+                }
+                self.current = end;
+
+                Operand::Temp(dest)
+            }
+            ExprKind::Repeat { condition, body } => {
+                let (check, iteration, end) =
+                    (self.new_block(), self.new_block(), self.new_block());
+
+                self.terminate(Terminator::Jump(check));
+                self.current = check;
+                let condition = self.expr(condition);
+                self.terminate(Terminator::Branch {
+                    condition,
+                    then: iteration,
+                    otherwise: end,
+                });
+
+                self.current = iteration;
+                self.expr(body);
+                self.terminate(Terminator::Jump(check));
+                self.current = end;
+
+                Operand::Void
+            }
+            ExprKind::Block { body } => body
+                .iter()
+                .map(|expr| self.expr(expr))
+                .last()
+                .unwrap_or(Operand::Void),
+            ExprKind::Let { bindings, body } => {
+                for binding in &bindings.0 {
+                    let value = match &binding.right {
+                        Some(right) => self.expr(right),
+                        None => default_value(binding.ty.0.name),
+                    };
+                    let dest = self.temp();
+                    self.push(Inst::Copy { dest, value });
+                    self.scope.push((binding.name.name, dest));
+                }
+
+                let value = self.expr(body);
+
+                let len = self.scope.len() - bindings.0.len();
+                self.scope.truncate(len);
+                value
+            }
+            ExprKind::Case { value, body } => {
+                let value = self.expr(value);
+                let arms: Vec<_> = body.iter().map(|arm| (arm, self.new_block())).collect();
+                let end = self.new_block();
+                let dest = self.temp();
+
+                self.terminate(Terminator::TypeCase {
+                    value: value.clone(),
+                    arms: arms
+                        .iter()
+                        .map(|(arm, block)| (arm.pat.ty.0.name, *block))
+                        .collect(),
+                    span: expr.span,
+                });
+
+                for (arm, block) in arms {
+                    self.current = block;
+
+                    let binding = self.temp();
+                    self.push(Inst::Copy {
+                        dest: binding,
+                        value: value.clone(),
+                    });
+                    self.scope.push((arm.pat.name.name, binding));
+                    let result = self.expr(&arm.value);
+                    self.scope.pop();
+
+                    self.push(Inst::Copy {
+                        dest,
+                        value: result,
+                    });
+                    self.terminate(Terminator::Jump(end));
+                }
+                self.current = end;
+
+                Operand::Temp(dest)
+            }
+            ExprKind::New { ty } => {
+                let dest = self.temp();
+                self.push(Inst::New {
+                    dest,
+                    class: ty.0.name,
+                });
+                Operand::Temp(dest)
+            }
+            ExprKind::Unary { op, right } => {
+                let value = self.expr(right);
+                let dest = self.temp();
+
+                let inst = match op {
+                    ast::UnaryOp::IsVoid => Inst::IsVoid { dest, value },
+                    ast::UnaryOp::Complement => Inst::Unary {
+                        dest,
+                        op: UnaryOp::Complement,
+                        value,
+                    },
+                    ast::UnaryOp::Not => Inst::Unary {
+                        dest,
+                        op: UnaryOp::Not,
+                        value,
+                    },
+                };
+                self.push(inst);
+
+                Operand::Temp(dest)
+            }
+            ExprKind::Binary { op, left, right } => {
+                let left = self.expr(left);
+                let right = self.expr(right);
+                let dest = self.temp();
+
+                self.push(Inst::Binary {
+                    dest,
+                    op: *op,
+                    left,
+                    right,
+                    span: expr.span,
+                });
+                Operand::Temp(dest)
+            }
+            ExprKind::Paren { value } => self.expr(value),
+            ExprKind::Ident(ident) if ident.name == sym::SELF => Operand::SelfObject,
+            ExprKind::Ident(ident) => {
+                // Variables are read into a fresh temporary, as they may be
+                // assigned before the value is used.
+                let dest = self.temp();
+
+                match self.lookup(ident.name) {
+                    Some(temp) => self.push(Inst::Copy {
+                        dest,
+                        value: Operand::Temp(temp),
+                    }),
+                    None => self.push(Inst::GetAttr {
+                        dest,
+                        name: ident.name,
+                    }),
+                }
+                Operand::Temp(dest)
+            }
+            ExprKind::String(value) => Operand::String(value.as_ref().into()),
+            ExprKind::Int(value) => Operand::Int(*value),
+            ExprKind::Bool(value) => Operand::Bool(*value),
+        }
+    }
+
+    fn static_type(&self, expr: &ast::Expr) -> InternedIdx {
+        match self.analysis.types.get(expr) {
+            Some(sym::SELF_TYPE) | None => self.class,
+            Some(ty) => ty,
+        }
+    }
+
+    /// The temporary of a local binding, or `None` for an attribute.
+    fn lookup(&self, name: InternedIdx) -> Option<Temp> {
+        self.scope
+            .iter()
+            .rev()
+            .find(|(other, _)| *other == name)
+            .map(|&(_, temp)| temp)
+    }
+
+    fn temp(&mut self) -> Temp {
+        self.temps += 1;
+        Temp(self.temps - 1)
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push((Vec::new(), None));
+        BlockId(self.blocks.len() as u32 - 1)
+    }
+
+    fn push(&mut self, inst: Inst) {
+        self.blocks[self.current.0 as usize].0.push(inst);
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        self.blocks[self.current.0 as usize].1 = Some(terminator);
+    }
+}
+
+/// The value attributes and `let` bindings of a type start with.
+fn default_value(ty: InternedIdx) -> Operand {
+    match ty {
+        sym::INT => Operand::Int(0),
+        sym::STRING => Operand::String("".into()),
+        sym::BOOL => Operand::Bool(false),
+        _ => Operand::Void,
+    }
+}
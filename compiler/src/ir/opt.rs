@@ -0,0 +1,413 @@
+//! Optimization passes over the intermediate representation.
+//!
+//! Passes rewrite functions in place and keep them valid for
+//! [`verify`](crate::ir::verify), so that any subset of them can run in any
+//! order.
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::BinaryOp,
+    ir::{BlockId, Function, Inst, Operand, Program, Temp, Terminator, UnaryOp},
+    semantic::{ClassTable, Hierarchy},
+};
+
+/// Upper bound on the rounds of the pass manager, as a pass may enable
+/// another one that enables the first again.
+const MAX_ROUNDS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pass {
+    /// Replaces reads of temporaries holding a copy of another operand with
+    /// that operand.
+    CopyPropagation,
+    /// Evaluates operators whose operands are literals, and branches on
+    /// literal conditions.
+    ConstantFolding,
+    /// Removes instructions without side effects whose result is never read,
+    /// such as unused `let` bindings, and blocks that cannot be reached.
+    DeadCodeElimination,
+    /// Turns dispatches into calls when no descendant of the static type of
+    /// the receiver overrides the method.
+    Devirtualization,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 4] = [
+        Pass::CopyPropagation,
+        Pass::ConstantFolding,
+        Pass::DeadCodeElimination,
+        Pass::Devirtualization,
+    ];
+
+    /// The name of the pass on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Pass::CopyPropagation => "copy-prop",
+            Pass::ConstantFolding => "const-fold",
+            Pass::DeadCodeElimination => "dce",
+            Pass::Devirtualization => "devirt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Pass> {
+        Pass::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+
+    /// Runs the pass over a function of the program whose classes form
+    /// `hierarchy`, returning whether it changed anything.
+    pub fn run(self, function: &mut Function, hierarchy: &Hierarchy) -> bool {
+        match self {
+            Pass::CopyPropagation => propagate_copies(function),
+            Pass::ConstantFolding => fold_constants(function),
+            Pass::DeadCodeElimination => eliminate_dead_code(function),
+            Pass::Devirtualization => devirtualize(function, hierarchy),
+        }
+    }
+}
+
+/// The passes to run over a program, until none of them changes anything.
+#[derive(Clone, Debug, Default)]
+pub struct PassManager {
+    passes: Vec<Pass>,
+}
+
+impl PassManager {
+    /// The passes of an optimization level: none for `0`, all of them
+    /// otherwise.
+    pub fn with_level(level: u8) -> PassManager {
+        let passes = if level == 0 {
+            Vec::new()
+        } else {
+            Pass::ALL.to_vec()
+        };
+        PassManager { passes }
+    }
+
+    pub fn enable(&mut self, pass: Pass) {
+        if !self.passes.contains(&pass) {
+            self.passes.push(pass);
+            self.passes
+                .sort_by_key(|pass| Pass::ALL.iter().position(|other| other == pass));
+        }
+    }
+
+    pub fn disable(&mut self, pass: Pass) {
+        self.passes.retain(|&other| other != pass);
+    }
+
+    pub fn passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    /// Runs the passes over a program whose classes are `classes`.
+    pub fn run(&self, program: &mut Program, classes: &ClassTable) {
+        let hierarchy = Hierarchy::new(classes);
+
+        for function in &mut program.functions {
+            for _ in 0..MAX_ROUNDS {
+                let mut changed = false;
+                for pass in &self.passes {
+                    changed |= pass.run(function, &hierarchy);
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn devirtualize(function: &mut Function, hierarchy: &Hierarchy) -> bool {
+    let mut changed = false;
+
+    for inst in function
+        .blocks
+        .iter_mut()
+        .flat_map(|block| &mut block.insts)
+    {
+        if let Inst::Dispatch {
+            dest,
+            receiver,
+            class,
+            method,
+            args,
+            span,
+        } = inst
+            && hierarchy.static_target(*class, *method).is_some()
+        {
+            *inst = Inst::Call {
+                dest: *dest,
+                receiver: receiver.clone(),
+                class: *class,
+                method: *method,
+                args: std::mem::take(args),
+                span: *span,
+            };
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn propagate_copies(function: &mut Function) -> bool {
+    let mut definitions = vec![0; function.temps as usize];
+    for param in &function.params {
+        definitions[param.0 as usize] += 1;
+    }
+    for inst in function.blocks.iter().flat_map(|block| &block.insts) {
+        if let Some(dest) = inst.dest() {
+            definitions[dest.0 as usize] += 1;
+        }
+    }
+
+    // A temporary assigned once holds the same value wherever it is read,
+    // as long as the operand it copies is not assigned again either.
+    let mut copies = vec![None; function.temps as usize];
+    for inst in function.blocks.iter().flat_map(|block| &block.insts) {
+        if let Inst::Copy { dest, value } = inst
+            && definitions[dest.0 as usize] == 1
+            && match value {
+                Operand::Temp(source) => definitions[source.0 as usize] == 1 && source != dest,
+                _ => true,
+            }
+        {
+            copies[dest.0 as usize] = Some(value.clone());
+        }
+    }
+
+    let mut changed = false;
+    for block in &mut function.blocks {
+        // Copies of temporaries assigned more than once only hold within
+        // the block, until either side is assigned again.
+        let mut local: FxHashMap<Temp, Operand> = FxHashMap::default();
+
+        for inst in &mut block.insts {
+            for operand in inst.operands_mut() {
+                changed |= substitute(operand, &copies, &local);
+            }
+
+            if let Some(dest) = inst.dest() {
+                local.retain(|&temp, value| temp != dest && *value != Operand::Temp(dest));
+            }
+            if let Inst::Copy { dest, value } = inst
+                && *value != Operand::Temp(*dest)
+            {
+                local.insert(*dest, value.clone());
+            }
+        }
+        for operand in block.terminator.operands_mut() {
+            changed |= substitute(operand, &copies, &local);
+        }
+    }
+    changed
+}
+
+/// Replaces a temporary by the operand it copies, returning whether it did.
+fn substitute(
+    operand: &mut Operand,
+    copies: &[Option<Operand>],
+    local: &FxHashMap<Temp, Operand>,
+) -> bool {
+    let mut changed = false;
+
+    while let Operand::Temp(temp) = *operand {
+        let copy = local.get(&temp).or(copies[temp.0 as usize].as_ref());
+        match copy {
+            Some(value) if *value != *operand => {
+                *operand = value.clone();
+                changed = true;
+            }
+            _ => break,
+        }
+    }
+    changed
+}
+
+fn fold_constants(function: &mut Function) -> bool {
+    let mut changed = false;
+
+    for block in &mut function.blocks {
+        for inst in &mut block.insts {
+            let folded = match inst {
+                Inst::Binary {
+                    dest,
+                    op,
+                    left,
+                    right,
+                    ..
+                } => fold_binary(*op, left, right).map(|value| (*dest, value)),
+                Inst::Unary { dest, op, value } => match (op, value) {
+                    (UnaryOp::Complement, Operand::Int(value)) => {
+                        Some((*dest, Operand::Int(value.wrapping_neg())))
+                    }
+                    (UnaryOp::Not, Operand::Bool(value)) => Some((*dest, Operand::Bool(!*value))),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            if let Some((dest, value)) = folded {
+                *inst = Inst::Copy { dest, value };
+                changed = true;
+            }
+        }
+
+        if let Terminator::Branch {
+            condition: Operand::Bool(condition),
+            then,
+            otherwise,
+        } = block.terminator
+        {
+            block.terminator = Terminator::Jump(if condition { then } else { otherwise });
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Evaluates a binary operator on literals. Arithmetic wraps like the code
+/// generated for it, `INT_MIN / -1` included, and divisions by zero are left
+/// for the program to report.
+fn fold_binary(op: BinaryOp, left: &Operand, right: &Operand) -> Option<Operand> {
+    let value = match (op, left, right) {
+        (BinaryOp::Add, Operand::Int(left), Operand::Int(right)) => {
+            Operand::Int(left.wrapping_add(*right))
+        }
+        (BinaryOp::Sub, Operand::Int(left), Operand::Int(right)) => {
+            Operand::Int(left.wrapping_sub(*right))
+        }
+        (BinaryOp::Mul, Operand::Int(left), Operand::Int(right)) => {
+            Operand::Int(left.wrapping_mul(*right))
+        }
+        (BinaryOp::Div, Operand::Int(left), Operand::Int(right)) if *right != 0 => {
+            Operand::Int(left.wrapping_div(*right))
+        }
+        (BinaryOp::Lt, Operand::Int(left), Operand::Int(right)) => Operand::Bool(left < right),
+        (BinaryOp::Lte, Operand::Int(left), Operand::Int(right)) => Operand::Bool(left <= right),
+        (BinaryOp::Eq, Operand::Int(_), Operand::Int(_))
+        | (BinaryOp::Eq, Operand::Bool(_), Operand::Bool(_))
+        | (BinaryOp::Eq, Operand::String(_), Operand::String(_))
+        | (BinaryOp::Eq, Operand::Void, Operand::Void) => Operand::Bool(left == right),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn eliminate_dead_code(function: &mut Function) -> bool {
+    let mut changed = merge_blocks(function);
+    changed |= remove_unreachable_blocks(function);
+
+    loop {
+        let mut read = vec![false; function.temps as usize];
+        for block in &function.blocks {
+            let operands = block.insts.iter().flat_map(|inst| inst.operands());
+            for operand in operands.chain(block.terminator.operands()) {
+                if let Operand::Temp(temp) = operand {
+                    read[temp.0 as usize] = true;
+                }
+            }
+        }
+
+        let mut removed = false;
+        for block in &mut function.blocks {
+            let len = block.insts.len();
+            block.insts.retain(|inst| match inst {
+                Inst::Copy {
+                    dest,
+                    value: Operand::Temp(source),
+                } if dest == source => false,
+                _ => match inst.dest() {
+                    Some(dest) => read[dest.0 as usize] || has_side_effects(inst),
+                    None => true,
+                },
+            });
+            removed |= block.insts.len() != len;
+        }
+
+        if !removed {
+            return changed;
+        }
+        changed = true;
+    }
+}
+
+fn has_side_effects(inst: &Inst) -> bool {
+    match inst {
+        Inst::Copy { .. } | Inst::Unary { .. } | Inst::IsVoid { .. } | Inst::GetAttr { .. } => {
+            false
+        }
+        // A division may report a division by zero.
+        Inst::Binary { op, right, .. } => {
+            *op == BinaryOp::Div && !matches!(right, Operand::Int(value) if *value != 0)
+        }
+        Inst::SetAttr { .. } | Inst::Dispatch { .. } | Inst::Call { .. } | Inst::New { .. } => true,
+    }
+}
+
+/// Appends to each block ending with a jump the block it jumps to, when it
+/// is its only predecessor. The latter is left unreachable.
+fn merge_blocks(function: &mut Function) -> bool {
+    let mut predecessors = vec![0; function.blocks.len()];
+    for block in &function.blocks {
+        for target in block.terminator.successors() {
+            predecessors[target.0 as usize] += 1;
+        }
+    }
+
+    let mut changed = false;
+    for idx in 0..function.blocks.len() {
+        while let Terminator::Jump(target) = function.blocks[idx].terminator {
+            let target = target.0 as usize;
+            if target == 0 || target == idx || predecessors[target] != 1 {
+                break;
+            }
+            predecessors[target] = 0;
+
+            let merged = &mut function.blocks[target];
+            let insts = std::mem::take(&mut merged.insts);
+            let terminator =
+                std::mem::replace(&mut merged.terminator, Terminator::Return(Operand::Void));
+
+            let block = &mut function.blocks[idx];
+            block.insts.extend(insts);
+            block.terminator = terminator;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Removes the blocks the entry block does not lead to, renumbering the
+/// others.
+fn remove_unreachable_blocks(function: &mut Function) -> bool {
+    let mut reachable = vec![false; function.blocks.len()];
+    let mut pending = vec![BlockId(0)];
+
+    while let Some(block) = pending.pop() {
+        if !std::mem::replace(&mut reachable[block.0 as usize], true) {
+            pending.extend(function.blocks[block.0 as usize].terminator.successors());
+        }
+    }
+    if reachable.iter().all(|&reachable| reachable) {
+        return false;
+    }
+
+    let mut indices = Vec::with_capacity(reachable.len());
+    let mut next = 0;
+    for &reachable in &reachable {
+        indices.push(BlockId(next));
+        next += reachable as u32;
+    }
+
+    let blocks = std::mem::take(&mut function.blocks);
+    for (mut block, reachable) in blocks.into_iter().zip(reachable) {
+        if reachable {
+            for target in block.terminator.successors_mut() {
+                *target = indices[target.0 as usize];
+            }
+            function.blocks.push(block);
+        }
+    }
+    true
+}
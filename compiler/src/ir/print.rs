@@ -0,0 +1,184 @@
+use std::fmt::Write;
+
+use crate::{
+    ast::BinaryOp,
+    ir::{Function, FunctionKind, Inst, Operand, Program, Terminator, UnaryOp},
+    util::interner::Interner,
+};
+
+macro_rules! emit {
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, $($arg)*).unwrap()
+    };
+}
+
+/// Renders a program as text, one function after the other.
+///
+/// ```text
+/// method Main.main() {
+/// bb0:
+///   %0 = dispatch self Main.out_int(42)
+///   return %0
+/// }
+/// ```
+pub fn print(program: &Program, interner: &Interner) -> String {
+    let mut printer = Printer {
+        interner,
+        out: String::new(),
+    };
+
+    for (idx, function) in program.functions.iter().enumerate() {
+        if idx > 0 {
+            printer.out.push('\n');
+        }
+        printer.function(function);
+    }
+    printer.out
+}
+
+struct Printer<'p> {
+    interner: &'p Interner,
+    out: String,
+}
+
+impl Printer<'_> {
+    fn function(&mut self, function: &Function) {
+        let class = self.interner.lookup(&function.class);
+
+        match function.kind {
+            FunctionKind::Init => emit!(self.out, "init {} {{", class),
+            FunctionKind::Method(name) => {
+                let params: Vec<_> = function
+                    .params
+                    .iter()
+                    .map(|param| format!("%{}", param.0))
+                    .collect();
+                emit!(
+                    self.out,
+                    "method {}.{}({}) {{",
+                    class,
+                    self.interner.lookup(&name),
+                    params.join(", ")
+                );
+            }
+        }
+
+        for (idx, block) in function.blocks.iter().enumerate() {
+            emit!(self.out, "bb{}:", idx);
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            self.terminator(&block.terminator);
+        }
+        emit!(self.out, "}}");
+    }
+
+    fn inst(&mut self, inst: &Inst) {
+        let text = match inst {
+            Inst::Copy { value, .. } => self.operand(value),
+            Inst::Unary { op, value, .. } => {
+                let op = match op {
+                    UnaryOp::Complement => "neg",
+                    UnaryOp::Not => "not",
+                };
+                format!("{} {}", op, self.operand(value))
+            }
+            Inst::IsVoid { value, .. } => format!("isvoid {}", self.operand(value)),
+            Inst::Binary {
+                op, left, right, ..
+            } => {
+                let op = match op {
+                    BinaryOp::Add => "add",
+                    BinaryOp::Sub => "sub",
+                    BinaryOp::Mul => "mul",
+                    BinaryOp::Div => "div",
+                    BinaryOp::Lt => "lt",
+                    BinaryOp::Lte => "le",
+                    BinaryOp::Eq => "eq",
+                };
+                format!("{} {}, {}", op, self.operand(left), self.operand(right))
+            }
+            Inst::GetAttr { name, .. } => format!("getattr {}", self.interner.lookup(name)),
+            Inst::SetAttr { name, value } => format!(
+                "setattr {}, {}",
+                self.interner.lookup(name),
+                self.operand(value)
+            ),
+            Inst::Dispatch {
+                receiver,
+                class,
+                method,
+                args,
+                ..
+            }
+            | Inst::Call {
+                receiver,
+                class,
+                method,
+                args,
+                ..
+            } => {
+                let op = match inst {
+                    Inst::Dispatch { .. } => "dispatch",
+                    _ => "call",
+                };
+                let args: Vec<_> = args.iter().map(|arg| self.operand(arg)).collect();
+                format!(
+                    "{} {} {}.{}({})",
+                    op,
+                    self.operand(receiver),
+                    self.interner.lookup(class),
+                    self.interner.lookup(method),
+                    args.join(", ")
+                )
+            }
+            Inst::New { class, .. } => format!("new {}", self.interner.lookup(class)),
+        };
+
+        match inst.dest() {
+            Some(dest) => emit!(self.out, "  %{} = {}", dest.0, text),
+            None => emit!(self.out, "  {}", text),
+        }
+    }
+
+    fn terminator(&mut self, terminator: &Terminator) {
+        match terminator {
+            Terminator::Jump(target) => emit!(self.out, "  jump bb{}", target.0),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => emit!(
+                self.out,
+                "  branch {}, bb{}, bb{}",
+                self.operand(condition),
+                then.0,
+                otherwise.0
+            ),
+            Terminator::TypeCase { value, arms, .. } => {
+                let arms: Vec<_> = arms
+                    .iter()
+                    .map(|(class, block)| format!("{}: bb{}", self.interner.lookup(class), block.0))
+                    .collect();
+                emit!(
+                    self.out,
+                    "  typecase {} [{}]",
+                    self.operand(value),
+                    arms.join(", ")
+                );
+            }
+            Terminator::Return(value) => emit!(self.out, "  return {}", self.operand(value)),
+        }
+    }
+
+    fn operand(&self, operand: &Operand) -> String {
+        match operand {
+            Operand::Temp(temp) => format!("%{}", temp.0),
+            Operand::SelfObject => "self".to_owned(),
+            Operand::Int(value) => value.to_string(),
+            Operand::Bool(value) => value.to_string(),
+            Operand::String(value) => format!("{:?}", value),
+            Operand::Void => "void".to_owned(),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    ir::{BlockId, Function, FunctionKind, Inst, Operand, Program, Temp, Terminator},
+    semantic::ClassTable,
+    util::{
+        interner::{InternedIdx, sym},
+        span::Spanned,
+    },
+};
+
+/// An inconsistency found in a function, reported with the block it is in.
+#[derive(Debug, PartialEq)]
+pub struct Error {
+    /// Index of the function in [`Program::functions`].
+    pub function: usize,
+    pub block: BlockId,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ErrorKind {
+    UnknownBlock(BlockId),
+    UnknownTemp(Temp),
+    /// A temporary read on a path where it was never assigned.
+    UseBeforeDefinition(Temp),
+    EmptyTypeCase,
+    UndefinedClass(InternedIdx),
+    UndefinedMethod {
+        class: InternedIdx,
+        method: InternedIdx,
+    },
+    ArgumentCountMismatch {
+        method: InternedIdx,
+        expected: usize,
+        found: usize,
+    },
+    UndefinedAttribute(InternedIdx),
+}
+
+/// Checks that every function of a program is well formed: blocks and
+/// temporaries exist, temporaries are assigned before being read, and
+/// classes, methods and attributes are those of the class table.
+pub fn verify(program: &Program, classes: &ClassTable) -> Result<(), Vec<Error>> {
+    let mut errors = Vec::new();
+
+    for (idx, function) in program.functions.iter().enumerate() {
+        let mut verifier = Verifier {
+            function,
+            classes,
+            idx,
+            block: BlockId(0),
+            errors: &mut errors,
+        };
+        verifier.check();
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Locates errors at the name of the declaration their function was lowered
+/// from: its method, or its class for an initializer.
+pub fn locate(errors: Vec<Error>, program: &Program, classes: &ClassTable) -> Vec<Spanned<Error>> {
+    errors
+        .into_iter()
+        .map(|error| {
+            let function = &program.functions[error.function];
+            let class = classes.get(function.class);
+            let span = match function.kind {
+                FunctionKind::Init => class
+                    .and_then(|class| class.decl)
+                    .map(|decl| decl.name.0.span),
+                FunctionKind::Method(name) => class
+                    .and_then(|class| class.method(name)?.decl)
+                    .map(|decl| decl.name.span),
+            };
+
+            Spanned {
+                inner: error,
+                span: span.unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+struct Verifier<'v, 'a> {
+    function: &'v Function,
+    classes: &'v ClassTable<'a>,
+    idx: usize,
+    /// The block being checked.
+    block: BlockId,
+    errors: &'v mut Vec<Error>,
+}
+
+impl Verifier<'_, '_> {
+    fn check(&mut self) {
+        let function = self.function;
+        // The flow of definitions can only be followed through existing
+        // blocks and temporaries.
+        let mut malformed = false;
+
+        for (idx, block) in function.blocks.iter().enumerate() {
+            self.block = BlockId(idx as u32);
+
+            for inst in &block.insts {
+                self.inst(inst);
+            }
+            if let Terminator::TypeCase { arms, .. } = &block.terminator {
+                if arms.is_empty() {
+                    self.error(ErrorKind::EmptyTypeCase);
+                }
+                for &(class, _) in arms {
+                    self.class(class);
+                }
+            }
+            for target in block.terminator.successors() {
+                if target.0 as usize >= function.blocks.len() {
+                    self.error(ErrorKind::UnknownBlock(target));
+                    malformed = true;
+                }
+            }
+        }
+
+        let temps = function
+            .blocks
+            .iter()
+            .flat_map(|block| {
+                let operands = block.insts.iter().flat_map(|inst| inst.operands());
+                let operands = operands.chain(block.terminator.operands());
+                let reads = operands.filter_map(|operand| match operand {
+                    Operand::Temp(temp) => Some(*temp),
+                    _ => None,
+                });
+                let writes = block.insts.iter().filter_map(|inst| inst.dest());
+                reads.chain(writes).collect::<Vec<_>>()
+            })
+            .chain(function.params.iter().copied());
+        let mut unknown: Vec<_> = temps.filter(|temp| temp.0 >= function.temps).collect();
+        unknown.sort();
+        unknown.dedup();
+
+        self.block = BlockId(0);
+        for &temp in &unknown {
+            self.error(ErrorKind::UnknownTemp(temp));
+            malformed = true;
+        }
+
+        if !malformed {
+            self.check_definitions();
+        }
+    }
+
+    fn inst(&mut self, inst: &Inst) {
+        match *inst {
+            Inst::GetAttr { name, .. } | Inst::SetAttr { name, .. }
+                if self
+                    .classes
+                    .lookup_attribute(self.function.class, name)
+                    .is_none() =>
+            {
+                self.error(ErrorKind::UndefinedAttribute(name));
+            }
+            Inst::Dispatch {
+                class,
+                method,
+                ref args,
+                ..
+            }
+            | Inst::Call {
+                class,
+                method,
+                ref args,
+                ..
+            } => {
+                if !self.class(class) {
+                    return;
+                }
+                match self.classes.lookup_method(class, method) {
+                    Some(sig) if sig.params.len() != args.len() => {
+                        self.error(ErrorKind::ArgumentCountMismatch {
+                            method,
+                            expected: sig.params.len(),
+                            found: args.len(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => self.error(ErrorKind::UndefinedMethod { class, method }),
+                }
+            }
+            Inst::New { class, .. } if class != sym::SELF_TYPE => {
+                self.class(class);
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks, along every path from the entry block, that temporaries are
+    /// assigned before being read.
+    fn check_definitions(&mut self) {
+        let function = self.function;
+        let blocks = &function.blocks;
+        let temps = function.temps as usize;
+
+        // Temporaries assigned on every path to the start of each block,
+        // starting from all of them and narrowing down to a fixed point.
+        let mut entry = vec![vec![true; temps]; blocks.len()];
+        let mut reachable = vec![false; blocks.len()];
+        let mut params = vec![false; temps];
+        for param in &function.params {
+            params[param.0 as usize] = true;
+        }
+        if !blocks.is_empty() {
+            entry[0] = params;
+            reachable[0] = true;
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (idx, block) in blocks.iter().enumerate() {
+                if !reachable[idx] {
+                    continue;
+                }
+                let mut defined = entry[idx].clone();
+                for dest in block.insts.iter().filter_map(|inst| inst.dest()) {
+                    defined[dest.0 as usize] = true;
+                }
+
+                for target in block.terminator.successors() {
+                    let target = target.0 as usize;
+                    let narrowed: Vec<_> = if reachable[target] {
+                        entry[target]
+                            .iter()
+                            .zip(&defined)
+                            .map(|(&a, &b)| a && b)
+                            .collect()
+                    } else {
+                        defined.clone()
+                    };
+
+                    if !reachable[target] || narrowed != entry[target] {
+                        reachable[target] = true;
+                        entry[target] = narrowed;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        for (idx, block) in blocks.iter().enumerate() {
+            if !reachable[idx] {
+                continue;
+            }
+            self.block = BlockId(idx as u32);
+            let mut defined = entry[idx].clone();
+
+            for inst in &block.insts {
+                for operand in inst.operands() {
+                    self.check_defined(operand, &defined);
+                }
+                if let Some(dest) = inst.dest() {
+                    defined[dest.0 as usize] = true;
+                }
+            }
+            for operand in block.terminator.operands() {
+                self.check_defined(operand, &defined);
+            }
+        }
+    }
+
+    fn check_defined(&mut self, operand: &Operand, defined: &[bool]) {
+        if let Operand::Temp(temp) = *operand
+            && !defined[temp.0 as usize]
+        {
+            self.error(ErrorKind::UseBeforeDefinition(temp));
+        }
+    }
+
+    /// Checks that a class exists, returning whether it does.
+    fn class(&mut self, class: InternedIdx) -> bool {
+        let exists = self.classes.contains(class);
+        if !exists {
+            self.error(ErrorKind::UndefinedClass(class));
+        }
+        exists
+    }
+
+    fn error(&mut self, kind: ErrorKind) {
+        self.errors.push(Error {
+            function: self.idx,
+            block: self.block,
+            kind,
+        });
+    }
+}
@@ -1,3 +1,6 @@
+// `num_derive` expands `FromPrimitive` inside an anonymous constant.
+#![allow(non_local_definitions)]
+
 use num_derive::FromPrimitive;
 
 #[derive(Clone, Debug, PartialEq, Eq, FromPrimitive)]
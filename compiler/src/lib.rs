@@ -1,4 +1,9 @@
 pub mod ast;
+pub mod codegen;
+pub mod diagnostics;
+pub mod interp;
+pub mod ir;
+pub mod semantic;
 pub mod util;
 
 pub mod language;
@@ -1,27 +1,653 @@
 use std::{
-    env::{self},
-    fs,
+    env, fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    process, thread,
 };
 
 use cool::{
-    ast::{bindings::Tree, converter},
-    util::interner::Interner,
+    ast::{self, bindings::Tree, converter},
+    codegen,
+    diagnostics::{self, Emitter, ErrorFormat, ToDiagnostic, explain::Explanation},
+    interp,
+    ir::{
+        self,
+        opt::{Pass, PassManager},
+        verify,
+    },
+    semantic::{self, Analysis},
+    util::{interner::Interner, source::SourceMap},
 };
 
+/// Stack size of the thread evaluating programs, so that deeply recursive
+/// COOL methods hit the interpreter's own limit first.
+const RUN_STACK_SIZE: usize = 512 * 1024 * 1024;
+
+/// The program has errors, or failed at run time.
+const EXIT_FAILURE: i32 = 1;
+/// The command line is invalid.
+const EXIT_USAGE: i32 = 2;
+/// A file could not be read or written, or a tool the compiler runs failed.
+const EXIT_IO: i32 = 3;
+
+const USAGE: &str = "\
+Usage: cool <command> [options] <files>...
+
+Commands:
+    parse       Report the syntax errors of files
+    check       Report the syntax and type errors of a program
+    run         Run a program with the interpreter
+    build       Compile a program
+    fmt         Format files in place
+    dump-ast    Print the syntax tree of files
+    lex         Print the tokens of files as the reference lexer does
+
+The files given to `parse`, `check`, `run` and `build` form a single program.
+
+Options:
+    -o <path>                   Write the output of `build` to <path>
+    --emit=<exe|asm|ir|ast|sexp|ref-ast>
+                                What `build` writes, an executable by default
+    --target=<x86|mips|llvm|wasm>
+                                What `--emit=asm` generates, x86 by default
+    -O0, -O1                    Run no or every optimization on the IR
+    -f<pass>, -fno-<pass>       Run or skip one optimization on the IR
+    --check                     Only report files `fmt` would change
+    --error-format=<human|json|sarif>
+                                How errors are written, for humans by default
+    --explain <code>            Describe an error code
+    -h, --help                  Print this help
+    -V, --version               Print the version
+
+Exit status:
+    0   Success
+    1   The program has errors, or failed at run time
+    2   The command line is invalid
+    3   A file could not be read or written, or linking failed
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Parse,
+    Check,
+    Run,
+    Build,
+    Fmt,
+    DumpAst,
+    Lex,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "parse" => Some(Command::Parse),
+            "check" => Some(Command::Check),
+            "run" => Some(Command::Run),
+            "build" => Some(Command::Build),
+            "fmt" => Some(Command::Fmt),
+            "dump-ast" => Some(Command::DumpAst),
+            "lex" => Some(Command::Lex),
+            _ => None,
+        }
+    }
+}
+
+/// What `build` writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Emit {
+    Exe,
+    Asm,
+    Ir,
+    Ast,
+    Sexp,
+    /// The syntax tree as the reference COOL parser prints it.
+    RefAst,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    X86,
+    Mips,
+    Llvm,
+    Wasm,
+}
+
+impl Target {
+    /// Extension of the assembly files of the target.
+    fn extension(self) -> &'static str {
+        match self {
+            Target::X86 | Target::Mips => "s",
+            Target::Llvm => "ll",
+            Target::Wasm => "wat",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Options {
+    command: Command,
+    files: Vec<String>,
+    output: Option<PathBuf>,
+    emit: Emit,
+    target: Target,
+    passes: PassManager,
+    check: bool,
+    format: ErrorFormat,
+}
+
+enum Invocation {
+    Help,
+    Version,
+    Explain(String),
+    Command(Options),
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let file = &args[1];
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let options = match parse_args(&args) {
+        Ok(Invocation::Help) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Ok(Invocation::Version) => {
+            println!("cool {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Ok(Invocation::Explain(code)) => process::exit(explain(&code)),
+        Ok(Invocation::Command(options)) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\nRun `cool --help` for usage.", message);
+            process::exit(EXIT_USAGE);
+        }
+    };
 
-    let src = fs::read(file).unwrap();
+    let status = match options.command {
+        Command::Parse => parse(&options),
+        Command::Check => check(&options),
+        Command::Run => {
+            let runner = thread::Builder::new()
+                .stack_size(RUN_STACK_SIZE)
+                .spawn(move || run(&options));
 
+            match runner {
+                Ok(runner) => runner.join().unwrap_or(EXIT_FAILURE),
+                Err(error) => {
+                    eprintln!("error: cannot start the interpreter: {}", error);
+                    EXIT_IO
+                }
+            }
+        }
+        Command::Build => build(&options),
+        Command::Fmt => fmt(&options),
+        Command::DumpAst => dump_ast(&options),
+        Command::Lex => lex(&options),
+    };
+    process::exit(status);
+}
+
+fn parse_args(args: &[String]) -> Result<Invocation, String> {
+    let Some(first) = args.first() else {
+        return Err("no command given".to_owned());
+    };
+    match first.as_str() {
+        "-h" | "--help" | "help" => return Ok(Invocation::Help),
+        "-V" | "--version" => return Ok(Invocation::Version),
+        "--explain" => {
+            let code = args.get(1).ok_or("`--explain` takes an error code")?;
+            return Ok(Invocation::Explain(code.clone()));
+        }
+        _ => {}
+    }
+
+    let command =
+        Command::from_name(first).ok_or_else(|| format!("unknown command `{}`", first))?;
+    let mut options = Options {
+        command,
+        files: Vec::new(),
+        output: None,
+        emit: Emit::Exe,
+        target: Target::X86,
+        passes: PassManager::with_level(0),
+        check: false,
+        format: ErrorFormat::Human,
+    };
+    let mut emit = None;
+    let mut target = None;
+    let mut optimizes = false;
+
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        // Options taking a value accept it either after `=` or as the next
+        // argument.
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| format!("`{}` takes a value", name))
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(Invocation::Help),
+            "-o" => options.output = Some(PathBuf::from(value()?)),
+            "--emit" => {
+                let value = value()?;
+                emit = Some(match value.as_str() {
+                    "exe" => Emit::Exe,
+                    "asm" => Emit::Asm,
+                    "ir" => Emit::Ir,
+                    "ast" => Emit::Ast,
+                    "sexp" => Emit::Sexp,
+                    "ref-ast" => Emit::RefAst,
+                    _ => return Err(format!("unknown emit kind `{}`", value)),
+                });
+            }
+            "--target" => {
+                let value = value()?;
+                target = Some(match value.as_str() {
+                    "x86" => Target::X86,
+                    "mips" => Target::Mips,
+                    "llvm" => Target::Llvm,
+                    "wasm" => Target::Wasm,
+                    _ => return Err(format!("unknown target `{}`", value)),
+                });
+            }
+            "--error-format" => {
+                let value = value()?;
+                options.format = ErrorFormat::from_name(&value)
+                    .ok_or_else(|| format!("unknown error format `{}`", value))?;
+            }
+            "--check" => options.check = true,
+            // `-O0` and `-O1` select every pass or none, then `-f<pass>` and
+            // `-fno-<pass>` toggle them one by one.
+            "-O0" => {
+                options.passes = PassManager::with_level(0);
+                optimizes = true;
+            }
+            "-O1" => {
+                options.passes = PassManager::with_level(1);
+                optimizes = true;
+            }
+            _ if name.starts_with("-f") => {
+                let (pass, enable) = match name.strip_prefix("-fno-") {
+                    Some(pass) => (pass, false),
+                    None => (&name[2..], true),
+                };
+                let pass = Pass::from_name(pass)
+                    .ok_or_else(|| format!("unknown optimization `{}`", pass))?;
+                if enable {
+                    options.passes.enable(pass);
+                } else {
+                    options.passes.disable(pass);
+                }
+                optimizes = true;
+            }
+            _ if name.starts_with('-') && name.len() > 1 => {
+                return Err(format!("unknown option `{}`", name));
+            }
+            _ => options.files.push(arg.clone()),
+        }
+    }
+
+    let only = |used: bool, option: &str, command: Command, name: &str| {
+        if used && options.command != command {
+            Err(format!("`{}` only applies to `{}`", option, name))
+        } else {
+            Ok(())
+        }
+    };
+    only(options.output.is_some(), "-o", Command::Build, "build")?;
+    only(emit.is_some(), "--emit", Command::Build, "build")?;
+    only(target.is_some(), "--target", Command::Build, "build")?;
+    only(optimizes, "-O", Command::Build, "build")?;
+    only(options.check, "--check", Command::Fmt, "fmt")?;
+
+    // Only x86 programs are linked; other targets default to assembly.
+    options.target = target.unwrap_or(Target::X86);
+    options.emit = match emit {
+        Some(Emit::Exe) if options.target != Target::X86 => {
+            return Err("only x86 programs can be linked into executables".to_owned());
+        }
+        Some(emit) => emit,
+        None if options.target == Target::X86 => Emit::Exe,
+        None => Emit::Asm,
+    };
+    // Syntax trees are written before the program is lowered to the IR the
+    // passes run on.
+    if optimizes && matches!(options.emit, Emit::Ast | Emit::Sexp | Emit::RefAst) {
+        return Err("`-O` only applies to executables, assembly and IR".to_owned());
+    }
+
+    if options.files.is_empty() {
+        return Err(format!("`{}` takes at least one file", first));
+    }
+
+    Ok(Invocation::Command(options))
+}
+
+fn explain(code: &str) -> i32 {
+    match Explanation::lookup(code) {
+        Some(explanation) => {
+            print!("{}", explanation.render());
+            0
+        }
+        None => {
+            eprintln!("error: no explanation for error code `{}`", code);
+            EXIT_USAGE
+        }
+    }
+}
+
+fn parse(options: &Options) -> i32 {
+    with_program(&options.files, options.format, |_, _, _| 0)
+}
+
+fn check(options: &Options) -> i32 {
+    with_analysis(&options.files, options.format, |_, _, _| 0)
+}
+
+fn run(options: &Options) -> i32 {
+    with_analysis(
+        &options.files,
+        options.format,
+        |sources, analysis, interner| {
+            let input = io::stdin().lock();
+            let output = BufWriter::new(io::stdout().lock());
+
+            match interp::run(&analysis.classes, interner, input, output) {
+                Ok(()) => 0,
+                Err(error) => {
+                    if let interp::Error::Abort(class) = error.inner {
+                        eprintln!("Abort called from class {}", interner.lookup(&class));
+                    } else {
+                        report(sources, &[error], interner, options.format);
+                    }
+                    EXIT_FAILURE
+                }
+            }
+        },
+    )
+}
+
+fn build(options: &Options) -> i32 {
+    let file = &options.files[0];
+
+    match options.emit {
+        Emit::Ast => {
+            return with_program(&options.files, options.format, |_, program, _| {
+                write_output(options.output.as_deref(), &format!("{:#?}\n", program))
+            });
+        }
+        Emit::Sexp => {
+            return with_program(&options.files, options.format, |_, program, interner| {
+                write_output(
+                    options.output.as_deref(),
+                    &ast::sexp::print(program, interner),
+                )
+            });
+        }
+        Emit::RefAst => {
+            return with_program(
+                &options.files,
+                options.format,
+                |sources, program, interner| {
+                    let tree = ast::reference::print(program, sources, interner);
+                    write_output(options.output.as_deref(), &tree)
+                },
+            );
+        }
+        _ => {}
+    }
+
+    with_analysis(
+        &options.files,
+        options.format,
+        |sources, analysis, interner| {
+            let mut program = ir::lower(analysis);
+            options.passes.run(&mut program, &analysis.classes);
+            if let Err(errors) = ir::verify(&program, &analysis.classes) {
+                let errors = verify::locate(errors, &program, &analysis.classes);
+                report(sources, &errors, interner, options.format);
+                return EXIT_FAILURE;
+            }
+            let classes = &analysis.classes;
+
+            match options.emit {
+                // Executables and assembly are written next to the first source
+                // file by default, as `coolc` does.
+                Emit::Exe => {
+                    let output = options
+                        .output
+                        .clone()
+                        .unwrap_or_else(|| Path::new(file).with_extension(""));
+                    let asm = codegen::x86::generate(&program, classes, interner, sources);
+
+                    match codegen::x86::link(&asm, &output) {
+                        Ok(()) => 0,
+                        Err(error) => {
+                            eprintln!("error: cannot link `{}`: {}", output.display(), error);
+                            EXIT_IO
+                        }
+                    }
+                }
+                Emit::Asm => {
+                    let asm = match options.target {
+                        Target::X86 => codegen::x86::generate(&program, classes, interner, sources),
+                        Target::Mips => {
+                            codegen::mips::generate(&program, classes, interner, sources)
+                        }
+                        Target::Llvm => {
+                            codegen::llvm::generate(&program, classes, interner, sources)
+                        }
+                        Target::Wasm => {
+                            codegen::wasm::generate(&program, classes, interner, sources)
+                        }
+                    };
+                    let output = options.output.clone().unwrap_or_else(|| {
+                        Path::new(file).with_extension(options.target.extension())
+                    });
+                    write_output(Some(&output), &asm)
+                }
+                Emit::Ir => write_output(options.output.as_deref(), &ir::print(&program, interner)),
+                Emit::Ast | Emit::Sexp | Emit::RefAst => {
+                    unreachable!("the AST is written before analysis")
+                }
+            }
+        },
+    )
+}
+
+/// Formats every file in place, or only lists those it would change with
+/// `--check`.
+fn fmt(options: &Options) -> i32 {
+    let mut status = 0;
+
+    for file in &options.files {
+        let Some(src) = read(file) else {
+            status = EXIT_IO;
+            continue;
+        };
+        let mut interner = Interner::with_capacity(1_024);
+        let tree = Tree::new(&src);
+
+        let result = match converter::convert(&src, &tree, &mut interner) {
+            Ok(program) => {
+                let formatted = ast::pretty::format(&src, &tree, &program, &interner);
+
+                if formatted.as_bytes() == src {
+                    0
+                } else if options.check {
+                    eprintln!("`{}` is not formatted", file);
+                    EXIT_FAILURE
+                } else {
+                    write_output(Some(Path::new(file)), &formatted)
+                }
+            }
+            Err((_, errors)) => {
+                let mut sources = SourceMap::new();
+                sources.add(file.as_str(), &src);
+                report(&sources, &errors, &interner, options.format);
+                EXIT_FAILURE
+            }
+        };
+        status = status.max(result);
+    }
+
+    status
+}
+
+/// Prints the syntax tree of every file, even one with errors.
+fn dump_ast(options: &Options) -> i32 {
+    let mut status = 0;
+
+    for file in &options.files {
+        let Some(sources) = load(std::slice::from_ref(file)) else {
+            status = EXIT_IO;
+            continue;
+        };
+        let mut interner = Interner::with_capacity(1_024);
+
+        match converter::convert_all(&sources, &mut interner) {
+            Ok(program) => println!("{:#?}", program),
+            Err((program, errors)) => {
+                println!("{:#?}", program);
+                report(&sources, &errors, &interner, options.format);
+                status = status.max(EXIT_FAILURE);
+            }
+        }
+    }
+
+    status
+}
+
+/// Prints the tokens of every file. Text that is not a token is printed as
+/// an `ERROR` token rather than reported.
+fn lex(options: &Options) -> i32 {
+    let mut status = 0;
+
+    for file in &options.files {
+        let Some(sources) = load(std::slice::from_ref(file)) else {
+            status = EXIT_IO;
+            continue;
+        };
+
+        for (_, file) in sources.files() {
+            let tree = Tree::new(file.src.as_bytes());
+            let tokens = ast::lex::tokens(file, &tree);
+            print!("{}", ast::lex::print(file, &tokens));
+        }
+    }
+
+    status
+}
+
+fn read(file: &str) -> Option<Vec<u8>> {
+    match fs::read(file) {
+        Ok(src) => Some(src),
+        Err(error) => {
+            eprintln!("error: cannot read `{}`: {}", file, error);
+            None
+        }
+    }
+}
+
+/// Reads files into a source map, or reports every file that cannot be read.
+fn load(files: &[String]) -> Option<SourceMap> {
+    let mut sources = SourceMap::new();
+    let mut readable = true;
+
+    for file in files {
+        match read(file) {
+            Some(src) => {
+                sources.add(file.as_str(), &src);
+            }
+            None => readable = false,
+        }
+    }
+
+    readable.then_some(sources)
+}
+
+/// Writes `contents` to `output`, or to stdout without one.
+fn write_output(output: Option<&Path>, contents: &str) -> i32 {
+    let Some(output) = output else {
+        print!("{}", contents);
+        return 0;
+    };
+
+    match fs::write(output, contents) {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("error: cannot write `{}`: {}", output.display(), error);
+            EXIT_IO
+        }
+    }
+}
+
+/// Parses files into one program and hands it to `f`, or reports its
+/// syntax errors.
+fn with_program(
+    files: &[String],
+    format: ErrorFormat,
+    f: impl FnOnce(&SourceMap, &ast::Program, &Interner) -> i32,
+) -> i32 {
+    let Some(sources) = load(files) else {
+        return EXIT_IO;
+    };
     let mut interner = Interner::with_capacity(1_024);
-    let tree = Tree::new(&src);
 
-    match converter::convert(&src, &tree, &mut interner) {
-        Ok(program) => println!("{:#?}", program),
-        Err((program, errors)) => {
-            println!("{:#?}", program);
-            println!("{:#?}", errors);
+    match converter::convert_all(&sources, &mut interner) {
+        Ok(program) => f(&sources, &program, &interner),
+        Err((_, errors)) => {
+            report(&sources, &errors, &interner, format);
+            EXIT_FAILURE
         }
     }
 }
+
+/// Parses and analyzes files and hands the analysis to `f`, or reports the
+/// errors of the program.
+fn with_analysis(
+    files: &[String],
+    format: ErrorFormat,
+    f: impl FnOnce(&SourceMap, &Analysis, &Interner) -> i32,
+) -> i32 {
+    with_program(
+        files,
+        format,
+        |sources, program, interner| match semantic::analyze(program) {
+            Ok(analysis) => f(sources, &analysis, interner),
+            Err((_, errors)) => {
+                report(sources, &errors, interner, format);
+                EXIT_FAILURE
+            }
+        },
+    )
+}
+
+/// Writes errors about the files of a source map to stderr.
+fn report(
+    sources: &SourceMap,
+    errors: &[impl ToDiagnostic],
+    interner: &Interner,
+    format: ErrorFormat,
+) {
+    let diagnostics: Vec<_> = errors
+        .iter()
+        .map(|error| error.to_diagnostic(interner))
+        .collect();
+
+    let output = match format {
+        ErrorFormat::Human => {
+            let emitter = Emitter::new(sources, Emitter::stderr_supports_color());
+            emitter.render_all(&diagnostics)
+        }
+        ErrorFormat::Json => diagnostics::json::render(sources, &diagnostics),
+        ErrorFormat::Sarif => diagnostics::sarif::render(sources, &diagnostics),
+    };
+    eprint!("{}", output);
+}
@@ -0,0 +1,155 @@
+use crate::{
+    ast,
+    util::{interner::InternedIdx, span::Spanned},
+};
+
+pub use self::{
+    class_table::{ClassInfo, ClassTable, MethodSig},
+    hierarchy::Hierarchy,
+    scope::{Binding, BindingTable},
+    typeck::TypeTable,
+};
+
+pub mod class_table;
+pub mod hierarchy;
+pub mod resolve;
+pub mod scope;
+pub mod typeck;
+
+pub type AnalysisResult<T> = Result<T, (T, Vec<Spanned<Error>>)>;
+
+/// Everything the semantic passes learn about a program.
+#[derive(Debug)]
+pub struct Analysis<'a> {
+    pub classes: ClassTable<'a>,
+    pub bindings: BindingTable<'a>,
+    pub types: TypeTable,
+}
+
+// The analysis is returned either way, boxing it would only add an indirection.
+#[allow(clippy::result_large_err)]
+pub fn analyze(program: &ast::Program) -> AnalysisResult<Analysis<'_>> {
+    let mut errors = Vec::new();
+    let classes = ClassTable::new(program, &mut errors);
+    resolve::check(&classes, &mut errors);
+    let bindings = scope::resolve(&classes, &mut errors);
+    let types = typeck::check(&classes, &bindings, &mut errors);
+
+    let analysis = Analysis {
+        classes,
+        bindings,
+        types,
+    };
+
+    if errors.is_empty() {
+        Ok(analysis)
+    } else {
+        errors.sort_by_key(|error| error.span);
+        Err((analysis, errors))
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// A class with the same name was already defined in the program.
+    ClassRedefinition(InternedIdx),
+    /// A user class uses the name of a basic class or `SELF_TYPE`.
+    BasicClassRedefinition(InternedIdx),
+    UndefinedParent {
+        class: InternedIdx,
+        parent: InternedIdx,
+    },
+    /// A class inherits from `Int`, `String`, `Bool` or `SELF_TYPE`.
+    ForbiddenInheritance {
+        class: InternedIdx,
+        parent: InternedIdx,
+    },
+    InheritanceCycle(InternedIdx),
+    UndefinedType(InternedIdx),
+    /// `SELF_TYPE` used as the type of a formal parameter, a case branch or
+    /// the target of a static dispatch.
+    SelfTypeNotAllowed,
+    UndeclaredIdentifier(InternedIdx),
+    SelfAssignment,
+    /// `self` used as the name of an attribute, a formal parameter, or a
+    /// `let` or `case` binding.
+    SelfBinding,
+    TypeMismatch {
+        expected: InternedIdx,
+        found: InternedIdx,
+    },
+    UndefinedMethod {
+        class: InternedIdx,
+        method: InternedIdx,
+    },
+    ArgumentCountMismatch {
+        method: InternedIdx,
+        expected: usize,
+        found: usize,
+    },
+    /// An `Int`, `String` or `Bool` compared with a value of another type.
+    InvalidComparison {
+        left: InternedIdx,
+        right: InternedIdx,
+    },
+    DuplicateCaseBranch(InternedIdx),
+    /// A method defined twice in the same class.
+    MethodRedefinition(InternedIdx),
+    /// An attribute defined twice in the same class.
+    AttributeRedefinition(InternedIdx),
+    /// An attribute with the same name as one defined by an ancestor.
+    InheritedAttributeRedefinition {
+        attribute: InternedIdx,
+        parent: InternedIdx,
+    },
+    /// Two formal parameters of a method share the same name.
+    FormalRedefinition(InternedIdx),
+    OverrideArgumentCountMismatch {
+        method: InternedIdx,
+        expected: usize,
+        found: usize,
+    },
+    /// A formal parameter or return type that differs from the one of the
+    /// overridden method.
+    OverrideTypeMismatch {
+        method: InternedIdx,
+        expected: InternedIdx,
+        found: InternedIdx,
+    },
+    MissingMainClass,
+    /// `Main` neither defines nor inherits a `main` method without formal
+    /// parameters.
+    MissingMainMethod,
+}
+
+impl Error {
+    /// Stable identifier of the kind of error, that `cool --explain`
+    /// describes at length.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ClassRedefinition(_) => "S0001",
+            Error::BasicClassRedefinition(_) => "S0002",
+            Error::UndefinedParent { .. } => "S0003",
+            Error::ForbiddenInheritance { .. } => "S0004",
+            Error::InheritanceCycle(_) => "S0005",
+            Error::UndefinedType(_) => "S0006",
+            Error::SelfTypeNotAllowed => "S0007",
+            Error::UndeclaredIdentifier(_) => "S0008",
+            Error::SelfAssignment => "S0009",
+            Error::SelfBinding => "S0010",
+            Error::TypeMismatch { .. } => "S0011",
+            Error::UndefinedMethod { .. } => "S0012",
+            Error::ArgumentCountMismatch { .. } => "S0013",
+            Error::InvalidComparison { .. } => "S0014",
+            Error::DuplicateCaseBranch(_) => "S0015",
+            Error::MethodRedefinition(_) => "S0016",
+            Error::AttributeRedefinition(_) => "S0017",
+            Error::InheritedAttributeRedefinition { .. } => "S0018",
+            Error::FormalRedefinition(_) => "S0019",
+            Error::OverrideArgumentCountMismatch { .. } => "S0020",
+            Error::OverrideTypeMismatch { .. } => "S0021",
+            Error::MissingMainClass => "S0022",
+            Error::MissingMainMethod => "S0023",
+        }
+    }
+}
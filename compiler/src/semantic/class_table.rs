@@ -0,0 +1,346 @@
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast,
+    semantic::Error,
+    util::{
+        interner::{InternedIdx, sym},
+        span::Spanned,
+    },
+};
+
+/// Classes every program implicitly contains, in the order they are
+/// registered.
+const BASIC_CLASSES: [InternedIdx; 5] = [sym::OBJECT, sym::IO, sym::INT, sym::STRING, sym::BOOL];
+
+/// Classes that cannot be inherited from.
+const FINAL_CLASSES: [InternedIdx; 4] = [sym::INT, sym::STRING, sym::BOOL, sym::SELF_TYPE];
+
+/// Methods of the basic classes as `(class, method, formals, return type)`.
+const BASIC_METHODS: &[(InternedIdx, InternedIdx, &[InternedIdx], InternedIdx)] = &[
+    (sym::OBJECT, sym::ABORT, &[], sym::OBJECT),
+    (sym::OBJECT, sym::TYPE_NAME, &[], sym::STRING),
+    (sym::OBJECT, sym::COPY, &[], sym::SELF_TYPE),
+    (sym::IO, sym::OUT_STRING, &[sym::STRING], sym::SELF_TYPE),
+    (sym::IO, sym::OUT_INT, &[sym::INT], sym::SELF_TYPE),
+    (sym::IO, sym::IN_STRING, &[], sym::STRING),
+    (sym::IO, sym::IN_INT, &[], sym::INT),
+    (sym::STRING, sym::LENGTH, &[], sym::INT),
+    (sym::STRING, sym::CONCAT, &[sym::STRING], sym::STRING),
+    (sym::STRING, sym::SUBSTR, &[sym::INT, sym::INT], sym::STRING),
+];
+
+#[derive(Debug)]
+pub struct ClassInfo<'a> {
+    pub name: InternedIdx,
+    /// Only `Object` has no parent.
+    pub parent: Option<InternedIdx>,
+    /// The declaration in the source program, absent for basic classes.
+    pub decl: Option<&'a ast::Class>,
+    /// Methods defined by the class itself, in declaration order.
+    pub methods: Vec<MethodSig<'a>>,
+    /// Attributes defined by the class itself, in declaration order.
+    pub attributes: Vec<&'a ast::Attribute>,
+}
+
+impl<'a> ClassInfo<'a> {
+    fn new(name: InternedIdx, decl: Option<&'a ast::Class>) -> ClassInfo<'a> {
+        let mut class = ClassInfo {
+            name,
+            parent: None,
+            decl,
+            methods: Vec::new(),
+            attributes: Vec::new(),
+        };
+
+        let features = decl.iter().flat_map(|decl| &decl.features.0);
+        for feature in features {
+            match feature {
+                ast::Feature::Method(method) => {
+                    // Only the first definition is visible to other passes,
+                    // redefinitions are diagnosed during resolution.
+                    if class.method(method.name.name).is_none() {
+                        class.methods.push(MethodSig {
+                            class: name,
+                            name: method.name.name,
+                            params: method
+                                .params
+                                .0
+                                .iter()
+                                .map(|param| param.ty.0.name)
+                                .collect(),
+                            return_ty: method.return_ty.0.name,
+                            decl: Some(method),
+                        });
+                    }
+                }
+                ast::Feature::Attribute(attribute) => {
+                    if class.attribute(attribute.name.name).is_none() {
+                        class.attributes.push(attribute);
+                    }
+                }
+            }
+        }
+
+        class
+    }
+
+    pub fn is_basic(&self) -> bool {
+        self.decl.is_none()
+    }
+
+    pub fn method(&self, name: InternedIdx) -> Option<&MethodSig<'a>> {
+        self.methods.iter().find(|method| method.name == name)
+    }
+
+    pub fn attribute(&self, name: InternedIdx) -> Option<&'a ast::Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.name == name)
+            .copied()
+    }
+}
+
+#[derive(Debug)]
+pub struct MethodSig<'a> {
+    /// The class defining the method.
+    pub class: InternedIdx,
+    pub name: InternedIdx,
+    /// Declared types of the formal parameters.
+    pub params: Vec<InternedIdx>,
+    pub return_ty: InternedIdx,
+    /// The declaration in the source program, absent for basic methods.
+    pub decl: Option<&'a ast::Method>,
+}
+
+/// Every class known to a program, with an inheritance graph that is
+/// guaranteed to be a tree rooted at `Object`.
+///
+/// Invalid parents, be it undefined, forbidden or part of a cycle, are
+/// replaced by `Object` once reported, so that later passes can walk the
+/// hierarchy without guarding against them again.
+#[derive(Debug)]
+pub struct ClassTable<'a> {
+    classes: Vec<ClassInfo<'a>>,
+    indices: FxHashMap<InternedIdx, usize>,
+}
+
+impl<'a> ClassTable<'a> {
+    pub fn new(program: &'a ast::Program, errors: &mut Vec<Spanned<Error>>) -> ClassTable<'a> {
+        let mut table = ClassTable {
+            classes: Vec::with_capacity(BASIC_CLASSES.len() + program.classes.len()),
+            indices: FxHashMap::default(),
+        };
+        table.install_basic_classes();
+        table.install_user_classes(program, errors);
+        table.resolve_parents(errors);
+        table.break_cycles(errors);
+
+        table
+    }
+
+    pub fn get(&self, name: InternedIdx) -> Option<&ClassInfo<'a>> {
+        self.indices.get(&name).map(|&idx| &self.classes[idx])
+    }
+
+    pub fn contains(&self, name: InternedIdx) -> bool {
+        self.indices.contains_key(&name)
+    }
+
+    pub fn parent(&self, name: InternedIdx) -> Option<InternedIdx> {
+        self.get(name).and_then(|class| class.parent)
+    }
+
+    /// Walks the inheritance chain of `name` up to `Object`, starting with
+    /// the class itself.
+    pub fn ancestors(&self, name: InternedIdx) -> impl Iterator<Item = InternedIdx> + '_ {
+        std::iter::successors(self.get(name).map(|class| class.name), |&name| {
+            self.parent(name)
+        })
+    }
+
+    /// Finds the most specific definition of a method visible from `class`.
+    pub fn lookup_method(&self, class: InternedIdx, name: InternedIdx) -> Option<&MethodSig<'a>> {
+        self.ancestors(class)
+            .find_map(|ancestor| self.get(ancestor)?.method(name))
+    }
+
+    /// Finds an attribute defined by `class` or any of its ancestors.
+    pub fn lookup_attribute(
+        &self,
+        class: InternedIdx,
+        name: InternedIdx,
+    ) -> Option<&'a ast::Attribute> {
+        self.ancestors(class)
+            .find_map(|ancestor| self.get(ancestor)?.attribute(name))
+    }
+
+    /// Whether `sub` is `sup` or one of its descendants.
+    ///
+    /// Undefined types conform to and from anything, as they have already
+    /// been reported where they were written.
+    pub fn conforms(&self, sub: InternedIdx, sup: InternedIdx) -> bool {
+        if !self.contains(sub) || !self.contains(sup) {
+            return true;
+        }
+        self.ancestors(sub).any(|ancestor| ancestor == sup)
+    }
+
+    /// The least upper bound of two classes, i.e. their closest common
+    /// ancestor.
+    pub fn lub(&self, a: InternedIdx, b: InternedIdx) -> InternedIdx {
+        self.ancestors(a)
+            .find(|&ancestor| self.conforms(b, ancestor))
+            .unwrap_or(sym::OBJECT)
+    }
+
+    /// Basic classes first, then user classes in declaration order.
+    pub fn iter(&self) -> impl Iterator<Item = &ClassInfo<'a>> {
+        self.classes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    fn install_basic_classes(&mut self) {
+        for name in BASIC_CLASSES {
+            let mut class = ClassInfo::new(name, None);
+            class.parent = (name != sym::OBJECT).then_some(sym::OBJECT);
+            class.methods = BASIC_METHODS
+                .iter()
+                .filter(|(class, ..)| *class == name)
+                .map(|&(class, name, params, return_ty)| MethodSig {
+                    class,
+                    name,
+                    params: params.to_vec(),
+                    return_ty,
+                    decl: None,
+                })
+                .collect();
+
+            self.insert(class);
+        }
+    }
+
+    fn install_user_classes(
+        &mut self,
+        program: &'a ast::Program,
+        errors: &mut Vec<Spanned<Error>>,
+    ) {
+        for class in &program.classes {
+            let name = &class.name.0;
+
+            if BASIC_CLASSES.contains(&name.name) || name.name == sym::SELF_TYPE {
+                errors.push(Spanned {
+                    inner: Error::BasicClassRedefinition(name.name),
+                    span: name.span,
+                });
+            } else if self.contains(name.name) {
+                errors.push(Spanned {
+                    inner: Error::ClassRedefinition(name.name),
+                    span: name.span,
+                });
+            } else {
+                // The parent is only known to exist once every class is
+                // installed, since COOL allows inheriting from a class that is
+                // declared later in the program.
+                self.insert(ClassInfo::new(name.name, Some(class)));
+            }
+        }
+    }
+
+    fn resolve_parents(&mut self, errors: &mut Vec<Spanned<Error>>) {
+        for idx in BASIC_CLASSES.len()..self.classes.len() {
+            let class = self.classes[idx].decl.unwrap();
+
+            let parent = match &class.inherits {
+                None => sym::OBJECT,
+                Some(ast::Type(parent)) if FINAL_CLASSES.contains(&parent.name) => {
+                    errors.push(Spanned {
+                        inner: Error::ForbiddenInheritance {
+                            class: class.name.0.name,
+                            parent: parent.name,
+                        },
+                        span: parent.span,
+                    });
+                    sym::OBJECT
+                }
+                Some(ast::Type(parent)) if !self.contains(parent.name) => {
+                    errors.push(Spanned {
+                        inner: Error::UndefinedParent {
+                            class: class.name.0.name,
+                            parent: parent.name,
+                        },
+                        span: parent.span,
+                    });
+                    sym::OBJECT
+                }
+                Some(ast::Type(parent)) => parent.name,
+            };
+            self.classes[idx].parent = Some(parent);
+        }
+    }
+
+    /// Reports every class that takes part in an inheritance cycle, then
+    /// reattaches it to `Object`.
+    ///
+    /// Classes that merely descend from a cycle are left untouched, as they
+    /// become valid once the cycle is broken.
+    fn break_cycles(&mut self, errors: &mut Vec<Spanned<Error>>) {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            Unvisited,
+            OnPath,
+            Done,
+        }
+        let mut states = vec![State::Unvisited; self.classes.len()];
+
+        for start in 0..self.classes.len() {
+            let mut path = Vec::new();
+            let mut cycle = Vec::new();
+            let mut current = Some(start);
+
+            while let Some(idx) = current {
+                match states[idx] {
+                    State::Done => break,
+                    State::OnPath => {
+                        let pos = path.iter().position(|&other| other == idx).unwrap();
+                        cycle = path[pos..].to_vec();
+                        break;
+                    }
+                    State::Unvisited => {
+                        states[idx] = State::OnPath;
+                        path.push(idx);
+                        current = self.classes[idx].parent.map(|parent| self.indices[&parent]);
+                    }
+                }
+            }
+
+            cycle.sort_unstable();
+
+            for idx in cycle {
+                let class = &mut self.classes[idx];
+                let name = &class.decl.unwrap().name.0;
+
+                errors.push(Spanned {
+                    inner: Error::InheritanceCycle(name.name),
+                    span: name.span,
+                });
+                class.parent = Some(sym::OBJECT);
+            }
+            for idx in path {
+                states[idx] = State::Done;
+            }
+        }
+    }
+
+    fn insert(&mut self, class: ClassInfo<'a>) {
+        self.indices.insert(class.name, self.classes.len());
+        self.classes.push(class);
+    }
+}
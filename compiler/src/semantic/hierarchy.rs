@@ -0,0 +1,151 @@
+use std::ops::RangeInclusive;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    semantic::{ClassTable, MethodSig},
+    util::interner::InternedIdx,
+};
+
+/// The inheritance tree of a program, with the methods each class responds
+/// to.
+///
+/// Tags are assigned in a preorder walk of the tree, so that the descendants
+/// of a class have contiguous tags and a `case` branch can match them with a
+/// single range check.
+#[derive(Debug)]
+pub struct Hierarchy<'c, 'a> {
+    classes: &'c ClassTable<'a>,
+    /// Classes in tag order.
+    order: Vec<InternedIdx>,
+    info: FxHashMap<InternedIdx, Node<'c, 'a>>,
+}
+
+#[derive(Debug)]
+struct Node<'c, 'a> {
+    tag: usize,
+    /// The highest tag among the class and its descendants.
+    max_tag: usize,
+    /// Distance from `Object` in the inheritance tree.
+    depth: usize,
+    /// Dispatch table, where overrides keep the slot of the method they
+    /// replace.
+    methods: Vec<&'c MethodSig<'a>>,
+}
+
+impl<'c, 'a> Hierarchy<'c, 'a> {
+    pub fn new(classes: &'c ClassTable<'a>) -> Hierarchy<'c, 'a> {
+        let mut children: FxHashMap<InternedIdx, Vec<InternedIdx>> = FxHashMap::default();
+        let mut roots = Vec::new();
+
+        for class in classes.iter() {
+            match class.parent {
+                Some(parent) => children.entry(parent).or_default().push(class.name),
+                None => roots.push(class.name),
+            }
+        }
+
+        let mut hierarchy = Hierarchy {
+            classes,
+            order: Vec::with_capacity(classes.len()),
+            info: FxHashMap::default(),
+        };
+        for root in roots {
+            hierarchy.visit(root, None, &children);
+        }
+
+        hierarchy
+    }
+
+    pub fn classes(&self) -> &'c ClassTable<'a> {
+        self.classes
+    }
+
+    /// Every class, in tag order, ancestors before their descendants.
+    pub fn order(&self) -> &[InternedIdx] {
+        &self.order
+    }
+
+    pub fn tag(&self, class: InternedIdx) -> usize {
+        self.info[&class].tag
+    }
+
+    /// Tags of the class and all of its descendants.
+    pub fn tag_range(&self, class: InternedIdx) -> RangeInclusive<usize> {
+        let info = &self.info[&class];
+        info.tag..=info.max_tag
+    }
+
+    pub fn depth(&self, class: InternedIdx) -> usize {
+        self.info[&class].depth
+    }
+
+    pub fn methods(&self, class: InternedIdx) -> &[&'c MethodSig<'a>] {
+        &self.info[&class].methods
+    }
+
+    pub fn method_index(&self, class: InternedIdx, name: InternedIdx) -> Option<usize> {
+        self.methods(class)
+            .iter()
+            .position(|method| method.name == name)
+    }
+
+    /// The method a dispatch on a receiver of static type `class` always
+    /// calls, when no descendant of the class overrides it. Such a dispatch
+    /// can call the method directly instead of going through the dispatch
+    /// table.
+    pub fn static_target(
+        &self,
+        class: InternedIdx,
+        name: InternedIdx,
+    ) -> Option<&'c MethodSig<'a>> {
+        let idx = self.method_index(class, name)?;
+        let method = self.methods(class)[idx];
+
+        self.order[self.tag_range(class)]
+            .iter()
+            .all(|&descendant| std::ptr::eq(self.methods(descendant)[idx], method))
+            .then_some(method)
+    }
+
+    fn visit(
+        &mut self,
+        class: InternedIdx,
+        parent: Option<InternedIdx>,
+        children: &FxHashMap<InternedIdx, Vec<InternedIdx>>,
+    ) {
+        let classes = self.classes;
+        let info = classes.get(class).unwrap();
+        let (depth, mut methods) = match parent {
+            Some(parent) => {
+                let parent = &self.info[&parent];
+                (parent.depth + 1, parent.methods.clone())
+            }
+            None => (0, Vec::new()),
+        };
+
+        for method in &info.methods {
+            match methods.iter().position(|other| other.name == method.name) {
+                Some(idx) => methods[idx] = method,
+                None => methods.push(method),
+            }
+        }
+
+        let tag = self.order.len();
+        self.order.push(class);
+        self.info.insert(
+            class,
+            Node {
+                tag,
+                max_tag: tag,
+                depth,
+                methods,
+            },
+        );
+
+        for &child in children.get(&class).into_iter().flatten() {
+            self.visit(child, Some(class), children);
+        }
+        self.info.get_mut(&class).unwrap().max_tag = self.order.len() - 1;
+    }
+}
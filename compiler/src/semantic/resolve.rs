@@ -0,0 +1,136 @@
+use crate::{
+    ast,
+    semantic::{ClassTable, Error},
+    util::{
+        interner::{InternedIdx, sym},
+        span::{FileId, Span, Spanned},
+    },
+};
+
+/// Checks the features of every user class against those it defines itself
+/// and those it inherits.
+pub fn check(classes: &ClassTable, errors: &mut Vec<Spanned<Error>>) {
+    let mut resolver = Resolver { classes, errors };
+
+    for class in classes.iter() {
+        let Some(decl) = class.decl else {
+            continue;
+        };
+        resolver.check_class(decl, class.parent.unwrap());
+    }
+    resolver.check_entry_point();
+}
+
+struct Resolver<'c, 'a> {
+    classes: &'c ClassTable<'a>,
+    errors: &'c mut Vec<Spanned<Error>>,
+}
+
+impl Resolver<'_, '_> {
+    fn check_class(&mut self, class: &ast::Class, parent: InternedIdx) {
+        let mut methods = Vec::new();
+        let mut attributes = Vec::new();
+
+        for feature in &class.features.0 {
+            match feature {
+                ast::Feature::Method(method) => {
+                    let name = &method.name;
+
+                    if methods.contains(&name.name) {
+                        self.error(Error::MethodRedefinition(name.name), name.span);
+                        continue;
+                    }
+                    methods.push(name.name);
+
+                    self.check_override(method, parent);
+                }
+                ast::Feature::Attribute(attribute) => {
+                    let name = &attribute.name;
+
+                    if name.name == sym::SELF {
+                        self.error(Error::SelfBinding, name.span);
+                    } else if attributes.contains(&name.name) {
+                        self.error(Error::AttributeRedefinition(name.name), name.span);
+                    } else if let Some(inherited) = self.find_attribute_owner(parent, name.name) {
+                        let error = Error::InheritedAttributeRedefinition {
+                            attribute: name.name,
+                            parent: inherited,
+                        };
+                        self.error(error, name.span);
+                    }
+                    attributes.push(name.name);
+                }
+            }
+        }
+    }
+
+    /// A method may only override another if they agree on the number and
+    /// types of their formal parameters and on their return type.
+    fn check_override(&mut self, method: &ast::Method, parent: InternedIdx) {
+        let Some(inherited) = self.classes.lookup_method(parent, method.name.name) else {
+            return;
+        };
+        let params = &method.params.0;
+
+        if params.len() != inherited.params.len() {
+            let error = Error::OverrideArgumentCountMismatch {
+                method: method.name.name,
+                expected: inherited.params.len(),
+                found: params.len(),
+            };
+            self.error(error, method.name.span);
+        } else {
+            for (param, &expected) in params.iter().zip(&inherited.params) {
+                let ast::Type(found) = &param.ty;
+
+                if found.name != expected {
+                    let error = Error::OverrideTypeMismatch {
+                        method: method.name.name,
+                        expected,
+                        found: found.name,
+                    };
+                    self.error(error, found.span);
+                }
+            }
+        }
+
+        let ast::Type(found) = &method.return_ty;
+
+        if found.name != inherited.return_ty {
+            let error = Error::OverrideTypeMismatch {
+                method: method.name.name,
+                expected: inherited.return_ty,
+                found: found.name,
+            };
+            self.error(error, found.span);
+        }
+    }
+
+    /// Every program starts by dispatching `main()` on a new `Main` object.
+    fn check_entry_point(&mut self) {
+        let Some(main) = self.classes.get(sym::MAIN_CLASS) else {
+            // Reported at the start of the first file.
+            let span = Span::new(FileId::default(), 0, 0);
+            self.error(Error::MissingMainClass, span);
+            return;
+        };
+        let method = self.classes.lookup_method(main.name, sym::MAIN);
+
+        if !method.is_some_and(|method| method.params.is_empty()) {
+            let span = main.decl.unwrap().name.0.span;
+            self.error(Error::MissingMainMethod, span);
+        }
+    }
+
+    fn find_attribute_owner(&self, class: InternedIdx, name: InternedIdx) -> Option<InternedIdx> {
+        self.classes.ancestors(class).find(|&ancestor| {
+            self.classes
+                .get(ancestor)
+                .is_some_and(|class| class.attribute(name).is_some())
+        })
+    }
+
+    fn error(&mut self, error: Error, span: Span) {
+        self.errors.push(Spanned { inner: error, span });
+    }
+}
@@ -0,0 +1,198 @@
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::{
+        self, ExprId, ExprKind,
+        visit::{self, Visitor},
+    },
+    semantic::{ClassTable, Error},
+    util::{
+        interner::{InternedIdx, sym},
+        span::{Span, Spanned},
+    },
+};
+
+/// The declaration an identifier refers to.
+#[derive(Clone, Copy, Debug)]
+pub enum Binding<'a> {
+    /// The implicit `self` parameter of every method.
+    SelfObject,
+    Attribute {
+        /// The class defining the attribute, which may be an ancestor of
+        /// the one the identifier appears in.
+        class: InternedIdx,
+        decl: &'a ast::Attribute,
+    },
+    Param(&'a ast::Param),
+    Let(&'a ast::LetBinding),
+    Case(&'a ast::CasePattern),
+}
+
+impl<'a> Binding<'a> {
+    /// The identifier introducing the binding, absent for `self`.
+    pub fn name(&self) -> Option<&'a ast::Ident> {
+        match *self {
+            Binding::SelfObject => None,
+            Binding::Attribute { decl, .. } => Some(&decl.name),
+            Binding::Param(decl) => Some(&decl.name),
+            Binding::Let(decl) => Some(&decl.name),
+            Binding::Case(decl) => Some(&decl.name),
+        }
+    }
+
+    /// The type the binding was declared with, `SELF_TYPE` for `self`.
+    pub fn ty(&self) -> InternedIdx {
+        match *self {
+            Binding::SelfObject => sym::SELF_TYPE,
+            Binding::Attribute { decl, .. } => decl.ty.0.name,
+            Binding::Param(decl) => decl.ty.0.name,
+            Binding::Let(decl) => decl.ty.0.name,
+            Binding::Case(decl) => decl.ty.0.name,
+        }
+    }
+}
+
+/// Bindings of the identifiers used in a program, keyed by the expression
+/// that reads or assigns them.
+#[derive(Debug, Default)]
+pub struct BindingTable<'a> {
+    bindings: FxHashMap<ExprId, Binding<'a>>,
+}
+
+impl<'a> BindingTable<'a> {
+    /// Resolves an identifier expression, or the target of an assignment.
+    pub fn get(&self, expr: &ast::Expr) -> Option<Binding<'a>> {
+        self.bindings.get(&expr.id).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ExprId, Binding<'a>)> + '_ {
+        self.bindings.iter().map(|(&id, &binding)| (id, binding))
+    }
+}
+
+pub fn resolve<'a>(classes: &ClassTable<'a>, errors: &mut Vec<Spanned<Error>>) -> BindingTable<'a> {
+    let mut resolver = ScopeResolver {
+        classes,
+        class: sym::OBJECT,
+        scope: Vec::new(),
+        bindings: BindingTable::default(),
+        errors,
+    };
+
+    for class in classes.iter() {
+        let Some(decl) = class.decl else {
+            continue;
+        };
+        resolver.class = class.name;
+        resolver.visit_class(decl);
+    }
+
+    resolver.bindings
+}
+
+struct ScopeResolver<'c, 'a> {
+    classes: &'c ClassTable<'a>,
+    /// The class whose features are being resolved.
+    class: InternedIdx,
+    /// Formals, `let` and `case` bindings in scope, innermost last.
+    scope: Vec<(InternedIdx, Binding<'a>)>,
+    bindings: BindingTable<'a>,
+    errors: &'c mut Vec<Spanned<Error>>,
+}
+
+impl<'a> Visitor<'a> for ScopeResolver<'_, 'a> {
+    fn visit_method(&mut self, method: &'a ast::Method) {
+        visit::walk_method(self, method);
+        self.scope.clear();
+    }
+
+    fn visit_param(&mut self, param: &'a ast::Param) {
+        let name = &param.name;
+
+        if self.scope.iter().any(|(other, _)| *other == name.name) {
+            self.error(Error::FormalRedefinition(name.name), name.span);
+        } else {
+            self.declare(name, Binding::Param(param));
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        match &expr.kind {
+            ExprKind::Assignment { name, right } => {
+                self.visit_expr(right);
+
+                if name.name == sym::SELF {
+                    self.error(Error::SelfAssignment, name.span);
+                } else {
+                    self.reference(expr, name);
+                }
+            }
+            ExprKind::Let { .. } => {
+                let depth = self.scope.len();
+                visit::walk_expr(self, expr);
+                self.scope.truncate(depth);
+            }
+            ExprKind::Ident(ident) => self.reference(expr, ident),
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+
+    /// Each initializer sees the bindings that precede it, but not its own.
+    fn visit_let_binding(&mut self, binding: &'a ast::LetBinding) {
+        if let Some(right) = &binding.right {
+            self.visit_expr(right);
+        }
+        self.declare(&binding.name, Binding::Let(binding));
+    }
+
+    fn visit_case_arm(&mut self, arm: &'a ast::CaseArm) {
+        let depth = self.scope.len();
+
+        self.declare(&arm.pat.name, Binding::Case(&arm.pat));
+        self.visit_expr(&arm.value);
+        self.scope.truncate(depth);
+    }
+}
+
+impl<'a> ScopeResolver<'_, 'a> {
+    /// Brings a binding into scope, unless it tries to rebind `self`.
+    fn declare(&mut self, name: &ast::Ident, binding: Binding<'a>) {
+        if name.name == sym::SELF {
+            self.error(Error::SelfBinding, name.span);
+        } else {
+            self.scope.push((name.name, binding));
+        }
+    }
+
+    fn reference(&mut self, expr: &ast::Expr, ident: &ast::Ident) {
+        match self.lookup(ident.name) {
+            Some(binding) => {
+                self.bindings.bindings.insert(expr.id, binding);
+            }
+            None => self.error(Error::UndeclaredIdentifier(ident.name), ident.span),
+        }
+    }
+
+    fn lookup(&self, name: InternedIdx) -> Option<Binding<'a>> {
+        let scoped = self
+            .scope
+            .iter()
+            .rev()
+            .find(|(other, _)| *other == name)
+            .map(|&(_, binding)| binding);
+
+        scoped.or_else(|| {
+            if name == sym::SELF {
+                return Some(Binding::SelfObject);
+            }
+            self.classes.ancestors(self.class).find_map(|class| {
+                let decl = self.classes.get(class)?.attribute(name)?;
+                Some(Binding::Attribute { class, decl })
+            })
+        })
+    }
+
+    fn error(&mut self, error: Error, span: Span) {
+        self.errors.push(Spanned { inner: error, span });
+    }
+}
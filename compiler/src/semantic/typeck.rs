@@ -0,0 +1,329 @@
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::{self, BinaryOp, ExprId, ExprKind, UnaryOp},
+    semantic::{BindingTable, ClassTable, Error, scope::Binding},
+    util::{
+        interner::{InternedIdx, sym},
+        span::{Span, Spanned},
+    },
+};
+
+/// Static types of the checked expressions.
+///
+/// `SELF_TYPE` is recorded as is and stands for the class enclosing the
+/// expression.
+#[derive(Debug, Default)]
+pub struct TypeTable {
+    types: FxHashMap<ExprId, InternedIdx>,
+}
+
+impl TypeTable {
+    pub fn get(&self, expr: &ast::Expr) -> Option<InternedIdx> {
+        self.types.get(&expr.id).copied()
+    }
+}
+
+pub fn check(
+    classes: &ClassTable,
+    bindings: &BindingTable,
+    errors: &mut Vec<Spanned<Error>>,
+) -> TypeTable {
+    let mut checker = TypeChecker {
+        classes,
+        bindings,
+        class: sym::OBJECT,
+        types: TypeTable::default(),
+        errors,
+    };
+
+    for class in classes.iter() {
+        let Some(decl) = class.decl else {
+            continue;
+        };
+        checker.class = class.name;
+
+        for feature in &decl.features.0 {
+            match feature {
+                ast::Feature::Attribute(attribute) => checker.check_attribute(attribute),
+                ast::Feature::Method(method) => checker.check_method(method),
+            }
+        }
+    }
+
+    checker.types
+}
+
+struct TypeChecker<'c, 'a> {
+    classes: &'c ClassTable<'a>,
+    bindings: &'c BindingTable<'a>,
+    /// The class whose features are being checked.
+    class: InternedIdx,
+    types: TypeTable,
+    errors: &'c mut Vec<Spanned<Error>>,
+}
+
+impl TypeChecker<'_, '_> {
+    fn check_attribute(&mut self, attribute: &ast::Attribute) {
+        let ty = self.declared_type(&attribute.ty, true);
+
+        if let Some(initializer) = &attribute.initializer {
+            self.expect(initializer, ty);
+        }
+    }
+
+    fn check_method(&mut self, method: &ast::Method) {
+        for param in &method.params.0 {
+            self.declared_type(&param.ty, false);
+        }
+        let return_ty = self.declared_type(&method.return_ty, true);
+
+        self.expect(&method.body, return_ty);
+    }
+
+    fn expr(&mut self, expr: &ast::Expr) -> InternedIdx {
+        let ty = self.infer(expr);
+        self.types.types.insert(expr.id, ty);
+
+        ty
+    }
+
+    fn infer(&mut self, expr: &ast::Expr) -> InternedIdx {
+        match &expr.kind {
+            ExprKind::Assignment { right, .. } => {
+                let ty = self.expr(right);
+
+                if let Some(expected) = self.binding_type(expr) {
+                    self.check_conformance(ty, expected, right.span);
+                }
+                ty
+            }
+            ExprKind::Dispatch {
+                qualifier,
+                method,
+                args,
+            } => self.dispatch(qualifier.as_ref(), method, args),
+            ExprKind::Conditional {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.expect(condition, sym::BOOL);
+                let consequence = self.expr(consequence);
+                let alternative = self.expr(alternative);
+
+                self.lub(consequence, alternative)
+            }
+            ExprKind::Repeat { condition, body } => {
+                self.expect(condition, sym::BOOL);
+                self.expr(body);
+
+                sym::OBJECT
+            }
+            ExprKind::Block { body } => body
+                .iter()
+                .map(|expr| self.expr(expr))
+                .last()
+                .unwrap_or(sym::OBJECT),
+            ExprKind::Let { bindings, body } => {
+                for binding in &bindings.0 {
+                    let ty = self.declared_type(&binding.ty, true);
+
+                    if let Some(right) = &binding.right {
+                        self.expect(right, ty);
+                    }
+                }
+                self.expr(body)
+            }
+            ExprKind::Case { value, body } => {
+                self.expr(value);
+
+                let mut branches = Vec::with_capacity(body.len());
+                let mut ty = None;
+
+                for arm in body {
+                    let branch = self.declared_type(&arm.pat.ty, false);
+
+                    if branches.contains(&branch) {
+                        self.error(Error::DuplicateCaseBranch(branch), arm.pat.ty.0.span);
+                    }
+                    branches.push(branch);
+
+                    let arm_ty = self.expr(&arm.value);
+
+                    ty = Some(ty.map_or(arm_ty, |ty| self.lub(ty, arm_ty)));
+                }
+                ty.unwrap_or(sym::OBJECT)
+            }
+            ExprKind::New { ty } => self.declared_type(ty, true),
+            ExprKind::Unary { op, right } => match op {
+                UnaryOp::IsVoid => {
+                    self.expr(right);
+                    sym::BOOL
+                }
+                UnaryOp::Complement => {
+                    self.expect(right, sym::INT);
+                    sym::INT
+                }
+                UnaryOp::Not => {
+                    self.expect(right, sym::BOOL);
+                    sym::BOOL
+                }
+            },
+            ExprKind::Binary { op, left, right } => match op {
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => {
+                    self.expect(left, sym::INT);
+                    self.expect(right, sym::INT);
+                    sym::INT
+                }
+                BinaryOp::Lt | BinaryOp::Lte => {
+                    self.expect(left, sym::INT);
+                    self.expect(right, sym::INT);
+                    sym::BOOL
+                }
+                BinaryOp::Eq => {
+                    let left = self.expr(left);
+                    let right = self.expr(right);
+                    let is_basic = |ty| [sym::INT, sym::STRING, sym::BOOL].contains(&ty);
+
+                    if left != right && (is_basic(left) || is_basic(right)) {
+                        self.error(Error::InvalidComparison { left, right }, expr.span);
+                    }
+                    sym::BOOL
+                }
+            },
+            ExprKind::Paren { value } => self.expr(value),
+            ExprKind::Ident(_) => self.binding_type(expr).unwrap_or(sym::OBJECT),
+            ExprKind::String(_) => sym::STRING,
+            ExprKind::Int(_) => sym::INT,
+            ExprKind::Bool(_) => sym::BOOL,
+        }
+    }
+
+    fn dispatch(
+        &mut self,
+        qualifier: Option<&ast::Qualifier>,
+        method: &ast::Ident,
+        args: &[ast::Expr],
+    ) -> InternedIdx {
+        let receiver = match qualifier {
+            Some(qualifier) => self.expr(&qualifier.value),
+            None => sym::SELF_TYPE,
+        };
+        let arg_tys: Vec<_> = args.iter().map(|arg| self.expr(arg)).collect();
+
+        let class = match qualifier.and_then(|qualifier| qualifier.parent.as_ref()) {
+            Some(parent) => {
+                let ty = self.declared_type(parent, false);
+                self.check_conformance(receiver, ty, parent.0.span);
+                ty
+            }
+            None if receiver == sym::SELF_TYPE => self.class,
+            None => receiver,
+        };
+        // Dispatching on an undefined type has already been reported.
+        if !self.classes.contains(class) {
+            return sym::OBJECT;
+        }
+
+        let classes = self.classes;
+        let Some(sig) = classes.lookup_method(class, method.name) else {
+            let error = Error::UndefinedMethod {
+                class,
+                method: method.name,
+            };
+            self.error(error, method.span);
+            return sym::OBJECT;
+        };
+
+        if sig.params.len() != args.len() {
+            let error = Error::ArgumentCountMismatch {
+                method: method.name,
+                expected: sig.params.len(),
+                found: args.len(),
+            };
+            self.error(error, method.span);
+        } else {
+            for ((arg, &ty), &param) in args.iter().zip(&arg_tys).zip(&sig.params) {
+                self.check_conformance(ty, param, arg.span);
+            }
+        }
+
+        if sig.return_ty == sym::SELF_TYPE {
+            receiver
+        } else {
+            sig.return_ty
+        }
+    }
+
+    /// The declared type of the binding an identifier or assignment refers
+    /// to, if it could be resolved.
+    fn binding_type(&self, expr: &ast::Expr) -> Option<InternedIdx> {
+        let binding = self.bindings.get(expr)?;
+
+        match binding {
+            // `SELF_TYPE` is already reported for formals and case branches.
+            Binding::Param(_) | Binding::Case(_) if binding.ty() == sym::SELF_TYPE => {
+                Some(sym::OBJECT)
+            }
+            _ => Some(binding.ty()),
+        }
+    }
+
+    /// Validates a type written in the source, falling back to `Object` when
+    /// `SELF_TYPE` is not allowed in that position.
+    ///
+    /// Undefined types are returned as is so that they don't cause further
+    /// errors down the line.
+    fn declared_type(&mut self, ty: &ast::Type, allow_self_type: bool) -> InternedIdx {
+        let ast::Type(ident) = ty;
+
+        if ident.name == sym::SELF_TYPE {
+            if allow_self_type {
+                return sym::SELF_TYPE;
+            }
+            self.error(Error::SelfTypeNotAllowed, ident.span);
+            return sym::OBJECT;
+        }
+        if !self.classes.contains(ident.name) {
+            self.error(Error::UndefinedType(ident.name), ident.span);
+        }
+        ident.name
+    }
+
+    fn expect(&mut self, expr: &ast::Expr, expected: InternedIdx) {
+        let found = self.expr(expr);
+        self.check_conformance(found, expected, expr.span);
+    }
+
+    fn check_conformance(&mut self, found: InternedIdx, expected: InternedIdx, span: Span) {
+        if !self.conforms(found, expected) {
+            self.error(Error::TypeMismatch { expected, found }, span);
+        }
+    }
+
+    /// Conformance extended to `SELF_TYPE`, which conforms to any ancestor of
+    /// the current class while only `SELF_TYPE` itself conforms to it.
+    fn conforms(&self, sub: InternedIdx, sup: InternedIdx) -> bool {
+        match (sub == sym::SELF_TYPE, sup == sym::SELF_TYPE) {
+            (true, true) => true,
+            (true, false) => self.classes.conforms(self.class, sup),
+            (false, true) => !self.classes.contains(sub),
+            (false, false) => self.classes.conforms(sub, sup),
+        }
+    }
+
+    fn lub(&self, a: InternedIdx, b: InternedIdx) -> InternedIdx {
+        if a == b {
+            return a;
+        }
+        let resolve = |ty| {
+            if ty == sym::SELF_TYPE { self.class } else { ty }
+        };
+        self.classes.lub(resolve(a), resolve(b))
+    }
+
+    fn error(&mut self, error: Error, span: Span) {
+        self.errors.push(Spanned { inner: error, span });
+    }
+}
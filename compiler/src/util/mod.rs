@@ -1,11 +1,54 @@
 pub mod interner {
-    use std::rc::Rc;
+    use std::{io, rc::Rc};
 
     use rustc_hash::FxHashMap;
+    use sexp_core::{SexpSerializer, ToSexp};
 
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
     pub struct InternedIdx(u32);
 
+    macro_rules! symbols {
+        ($($name:ident: $value:literal),* $(,)?) => {
+            /// Names the compiler refers to directly, interned ahead of any
+            /// source text so that they have fixed indices.
+            pub mod sym {
+                use super::InternedIdx;
+
+                #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+                #[repr(u32)]
+                enum Idx {
+                    $($name),*
+                }
+
+                $(pub const $name: InternedIdx = InternedIdx(Idx::$name as u32);)*
+            }
+
+            const PREINTERNED: &[&str] = &[$($value),*];
+        };
+    }
+
+    symbols! {
+        OBJECT: "Object",
+        IO: "IO",
+        INT: "Int",
+        STRING: "String",
+        BOOL: "Bool",
+        SELF_TYPE: "SELF_TYPE",
+        SELF: "self",
+        MAIN_CLASS: "Main",
+        MAIN: "main",
+        ABORT: "abort",
+        TYPE_NAME: "type_name",
+        COPY: "copy",
+        OUT_STRING: "out_string",
+        OUT_INT: "out_int",
+        IN_STRING: "in_string",
+        IN_INT: "in_int",
+        LENGTH: "length",
+        CONCAT: "concat",
+        SUBSTR: "substr",
+    }
+
     pub struct Interner {
         map: FxHashMap<Rc<str>, u32>,
         vec: Vec<Rc<str>>,
@@ -13,10 +56,15 @@ pub mod interner {
 
     impl Interner {
         pub fn with_capacity(capacity: usize) -> Interner {
-            Interner {
+            let mut interner = Interner {
                 map: FxHashMap::with_capacity_and_hasher(capacity, Default::default()),
                 vec: Vec::with_capacity(capacity),
-            }
+            };
+            PREINTERNED.iter().for_each(|name| {
+                interner.intern(name);
+            });
+
+            interner
         }
 
         pub fn intern(&mut self, name: &str) -> InternedIdx {
@@ -35,10 +83,76 @@ pub mod interner {
             &self.vec[idx.0 as usize]
         }
     }
+
+    /// Names are written as symbols, looked up in the [`Interner`] the
+    /// serializer carries as its context.
+    impl ToSexp for InternedIdx {
+        fn to_sexp<S: SexpSerializer>(&self, s: &mut S) -> sexp_core::Result<()> {
+            let interner = s
+                .context()
+                .and_then(|context| context.downcast_ref::<Interner>())
+                .ok_or_else(|| io::Error::other("serializing a name requires an interner"))?;
+            let name = interner.lookup(self).to_owned();
+            s.serialize_symbol(&name)
+        }
+    }
 }
 
 pub mod span {
-    pub type Span = (usize, usize, usize, usize);
+    use std::ops::Range;
+
+    /// Identifies a source file of a program, by the order it was added to
+    /// its [`SourceMap`](super::source::SourceMap).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct FileId(pub u32);
+
+    impl FileId {
+        pub fn index(self) -> usize {
+            self.0 as usize
+        }
+    }
+
+    /// A range of bytes of a source file. Lines and columns are found
+    /// through the [`SourceFile`](super::source::SourceFile) it belongs to.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct Span {
+        pub file: FileId,
+        pub start: u32,
+        pub end: u32,
+    }
+
+    impl Span {
+        pub fn new(file: FileId, start: usize, end: usize) -> Span {
+            Span {
+                file,
+                start: start as u32,
+                end: end as u32,
+            }
+        }
+
+        /// The span covering both `self` and `other`, and whatever lies
+        /// between them, in the file of `self`.
+        pub fn to(self, other: Span) -> Span {
+            debug_assert_eq!(self.file, other.file, "spans of different files");
+            Span {
+                file: self.file,
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
+            }
+        }
+
+        pub fn range(self) -> Range<usize> {
+            self.start as usize..self.end as usize
+        }
+
+        pub fn len(self) -> usize {
+            (self.end - self.start) as usize
+        }
+
+        pub fn is_empty(self) -> bool {
+            self.start == self.end
+        }
+    }
 
     #[derive(Debug, PartialEq)]
     pub struct Spanned<T> {
@@ -46,3 +160,146 @@ pub mod span {
         pub span: Span,
     }
 }
+
+pub mod source {
+    use std::path::Path;
+
+    use super::span::{FileId, Span};
+
+    pub struct SourceFile {
+        pub name: String,
+        pub src: String,
+        /// Byte offset of the start of every line.
+        line_starts: Vec<u32>,
+    }
+
+    impl SourceFile {
+        /// Reads the text of a file, whose invalid UTF-8 sequences are
+        /// replaced so that it can be shown in diagnostics.
+        pub fn new(name: impl Into<String>, src: &[u8]) -> SourceFile {
+            let src = String::from_utf8_lossy(src).into_owned();
+            let line_starts = std::iter::once(0)
+                .chain(src.match_indices('\n').map(|(idx, _)| idx as u32 + 1))
+                .collect();
+
+            SourceFile {
+                name: name.into(),
+                src,
+                line_starts,
+            }
+        }
+
+        /// The name of the file without its directories.
+        pub fn file_name(&self) -> &str {
+            Path::new(&self.name)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(&self.name)
+        }
+
+        pub fn line_count(&self) -> usize {
+            self.line_starts.len()
+        }
+
+        /// The 0-based index of the line `offset` is on.
+        pub fn line_index(&self, offset: usize) -> usize {
+            self.line_starts
+                .partition_point(|&start| start as usize <= offset)
+                - 1
+        }
+
+        /// The offset the 0-based line `line` starts at.
+        pub fn line_start(&self, line: usize) -> usize {
+            self.line_starts[line] as usize
+        }
+
+        /// The text of the 0-based line `line`, without its line break.
+        pub fn line_text(&self, line: usize) -> &str {
+            let start = self.line_start(line);
+            let end = self
+                .line_starts
+                .get(line + 1)
+                .map_or(self.src.len(), |&end| end as usize);
+
+            self.src[start..end].trim_end_matches(['\n', '\r'])
+        }
+
+        /// The 1-based line and byte column of `offset`.
+        pub fn line_col(&self, offset: usize) -> (usize, usize) {
+            let line = self.line_index(offset);
+            (line + 1, offset - self.line_start(line) + 1)
+        }
+
+        /// The 0-based line and UTF-16 column of `offset`, as the language
+        /// server protocol counts them.
+        pub fn utf16_position(&self, offset: usize) -> (usize, usize) {
+            let line = self.line_index(offset);
+            let start = self.line_start(line);
+            let column = self.src[start..offset.min(self.src.len())]
+                .chars()
+                .map(char::len_utf16)
+                .sum();
+
+            (line, column)
+        }
+
+        /// The offset of a 0-based line and UTF-16 column, clamped to the
+        /// end of the line.
+        pub fn utf16_offset(&self, line: usize, column: usize) -> usize {
+            if line >= self.line_count() {
+                return self.src.len();
+            }
+            let start = self.line_start(line);
+            let mut units = 0;
+
+            for (idx, c) in self.line_text(line).char_indices() {
+                if units >= column {
+                    return start + idx;
+                }
+                units += c.len_utf16();
+            }
+            start + self.line_text(line).len()
+        }
+
+        /// The text a span covers, or nothing if it does not fall on
+        /// character boundaries.
+        pub fn text(&self, span: Span) -> &str {
+            self.src.get(span.range()).unwrap_or_default()
+        }
+    }
+
+    /// The source files a program is compiled from, each identified by a
+    /// [`FileId`].
+    #[derive(Default)]
+    pub struct SourceMap {
+        files: Vec<SourceFile>,
+    }
+
+    impl SourceMap {
+        pub fn new() -> SourceMap {
+            SourceMap::default()
+        }
+
+        pub fn add(&mut self, name: impl Into<String>, src: &[u8]) -> FileId {
+            let id = FileId(self.files.len() as u32);
+            self.files.push(SourceFile::new(name, src));
+            id
+        }
+
+        pub fn get(&self, file: FileId) -> &SourceFile {
+            &self.files[file.index()]
+        }
+
+        pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+            self.files
+                .iter()
+                .enumerate()
+                .map(|(idx, file)| (FileId(idx as u32), file))
+        }
+
+        /// The 1-based line a span starts on.
+        pub fn line(&self, span: Span) -> usize {
+            self.get(span.file).line_index(span.start as usize) + 1
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{self, Command, Output},
+};
+
+fn cool(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cool"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Writes a program to a fresh temporary directory.
+fn write(dir: &str, name: &str, src: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("cool-test-cli-{}-{}", dir, process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, src).unwrap();
+    path
+}
+
+#[test]
+fn test_usage() {
+    let output = cool(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stderr(&output),
+        "error: no command given\n\nRun `cool --help` for usage.\n"
+    );
+
+    let output = cool(&["--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Usage: cool <command>"));
+
+    for (args, message) in [
+        (&["frob"][..], "unknown command `frob`"),
+        (&["run"], "`run` takes at least one file"),
+        (
+            &["parse", "-o", "out", "a.cl"],
+            "`-o` only applies to `build`",
+        ),
+        (&["build", "--emit"], "`--emit` takes a value"),
+        (&["build", "--emit=obj", "a.cl"], "unknown emit kind `obj`"),
+        (
+            &["build", "-fno-inline", "a.cl"],
+            "unknown optimization `inline`",
+        ),
+        (
+            &["build", "--emit=sexp", "-fno-dce", "a.cl"],
+            "`-O` only applies to executables, assembly and IR",
+        ),
+        (
+            &["build", "--target=mips", "--emit=exe", "a.cl"],
+            "only x86 programs can be linked into executables",
+        ),
+        (
+            &["run", "--error-format=xml", "a.cl"],
+            "unknown error format `xml`",
+        ),
+    ] {
+        let output = cool(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stderr(&output).starts_with(&format!("error: {}\n", message)));
+    }
+}
+
+#[test]
+fn test_commands() {
+    let output = cool(&["check", "missing.cl"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("error: cannot read `missing.cl`: "));
+
+    let good = write(
+        "commands",
+        "good.cl",
+        "class Main inherits IO { main() : Object { out_int(6 * 7) }; };\n",
+    );
+    let good = good.to_str().unwrap();
+    let bad = write(
+        "commands",
+        "bad.cl",
+        "class Main { main() : Int { \"zero\" }; };\n",
+    );
+    let bad = bad.to_str().unwrap();
+
+    assert_eq!(cool(&["parse", good, bad]).status.code(), Some(0));
+    assert_eq!(cool(&["check", good]).status.code(), Some(0));
+
+    let output = cool(&["check", "--error-format", "json", bad]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("{\"severity\":\"error\",\"code\":\"S0011\""));
+
+    let huge = write(
+        "commands",
+        "huge.cl",
+        "class Main { main() : Int { 99999999999999999999999 }; };\n",
+    );
+    let output = cool(&["check", huge.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error[C0013]: integer literal is too large\n"));
+
+    let output = cool(&["run", good]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42");
+
+    let output = cool(&["build", "--emit=ir", good]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("method Main.main"));
+
+    let wat = write("commands", "good.wat", "");
+    let output = cool(&["build", "--target=wasm", good, "-o", wat.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(fs::read_to_string(&wat).unwrap().starts_with("(module"));
+
+    // The tree is written before any semantic error is found.
+    let output = cool(&["build", "--emit=sexp", bad]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("(program\n  (classes:"));
+
+    let output = cool(&["build", "--emit=ref-ast", bad]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("#1\n_program\n  #1\n  _class\n"));
+
+    let output = cool(&["lex", good]);
+    assert_eq!(output.status.code(), Some(0));
+    let tokens = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(tokens.starts_with(&format!("#name \"{}\"\n#1 CLASS\n#1 TYPEID Main\n", good)));
+
+    let output = cool(&["dump-ast", bad]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Program {"));
+}
+
+#[test]
+fn test_several_files() {
+    let main = write(
+        "files",
+        "main.cl",
+        "class Main inherits IO { main() : Object { out_int(new Answer.get()) }; };\n",
+    );
+    let main = main.to_str().unwrap();
+    let answer = write(
+        "files",
+        "answer.cl",
+        "class Answer {\n    get() : Int { 42 };\n    bad() : Int { false };\n};\n",
+    );
+    let answer = answer.to_str().unwrap();
+
+    // Errors point into the file they are in.
+    let output = cool(&["check", main, answer]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains(&format!(" --> {}:3:19\n", answer)));
+
+    fs::write(answer, "class Answer {\n    get() : Int { 42 };\n};\n").unwrap();
+    let output = cool(&["run", main, answer]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42");
+
+    // Neither file is a program on its own.
+    assert_eq!(cool(&["check", main]).status.code(), Some(1));
+    assert_eq!(cool(&["check", answer]).status.code(), Some(1));
+}
+
+#[test]
+fn test_fmt() {
+    let file = write("fmt", "main.cl", "class Main { main() : Int { 0 }; };\n");
+    let file = file.to_str().unwrap();
+
+    let output = cool(&["fmt", "--check", file]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), format!("`{}` is not formatted\n", file));
+
+    assert_eq!(cool(&["fmt", file]).status.code(), Some(0));
+    assert_eq!(
+        fs::read_to_string(file).unwrap(),
+        "class Main {\n    main() : Int { 0 };\n};\n"
+    );
+    assert_eq!(cool(&["fmt", "--check", file]).status.code(), Some(0));
+}
@@ -0,0 +1,344 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{self, Command, Stdio},
+};
+
+use cool::{
+    codegen::{Layout, llvm, mips, wasm, x86},
+    ir::{self, opt::PassManager},
+    semantic,
+    util::source::SourceMap,
+};
+use regex::Regex;
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+const PROGRAM: &str = r#"
+class A { x : Int <- 3; get() : Int { x }; };
+class B inherits A { y : String; get() : Int { x + 1 }; set(v : Int) : SELF_TYPE { { x <- v; self; } }; };
+class C inherits A { };
+class Main inherits IO {
+  main() : Object {
+    let a : A <- new B in {
+      out_int(a.get());
+      out_int(a@A.get());
+      case a of b : B => out_string("B"); o : Object => out_string("O"); esac;
+    }
+  };
+};
+"#;
+
+#[test]
+fn test_layout() {
+    let (program, mut interner) = parse(PROGRAM);
+    let analysis = semantic::analyze(&program).unwrap();
+    let layout = Layout::new(&analysis.classes);
+
+    let [a, b, c] = ["A", "B", "C"].map(|name| interner.intern(name));
+    let names = |class| {
+        layout
+            .methods(class)
+            .iter()
+            .map(|method| {
+                let class = interner.lookup(&method.class);
+                format!("{}.{}", class, interner.lookup(&method.name))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Descendants of a class have contiguous tags.
+    assert_eq!(layout.tag_range(a), layout.tag(a)..=layout.tag(c));
+    assert_eq!(layout.tag_range(b), layout.tag(b)..=layout.tag(b));
+    assert_eq!(layout.depth(b), 2);
+
+    // Overrides keep the slot of the method they replace.
+    assert_eq!(
+        names(b),
+        [
+            "Object.abort",
+            "Object.type_name",
+            "Object.copy",
+            "B.get",
+            "B.set"
+        ]
+    );
+    assert_eq!(layout.attribute_index(b, interner.intern("y")), Some(1));
+
+    // Only methods no descendant overrides can be called directly.
+    let [get, copy, main, out_int] =
+        ["get", "copy", "Main", "out_int"].map(|name| interner.intern(name));
+    let target = |class, method| {
+        layout
+            .hierarchy()
+            .static_target(class, method)
+            .map(|method| interner.lookup(&method.class))
+    };
+    assert_eq!(target(a, get), None);
+    assert_eq!(target(b, get), Some("B"));
+    assert_eq!(target(c, get), Some("A"));
+    assert_eq!(target(a, copy), Some("Object"));
+    assert_eq!(target(main, out_int), Some("IO"));
+}
+
+/// A source map of a single file, `test.cl`.
+fn sources(src: &str) -> SourceMap {
+    let mut sources = SourceMap::new();
+    sources.add("test.cl", src.as_bytes());
+    sources
+}
+
+#[test]
+fn test_mips() {
+    let (program, interner) = parse(PROGRAM);
+    let analysis = semantic::analyze(&program).unwrap();
+    let lowered = ir::lower(&analysis);
+    let asm = mips::generate(&lowered, &analysis.classes, &interner, &sources(PROGRAM));
+
+    for expected in [
+        "\t.globl\tMain_protObj\n",
+        "_int_tag:\n\t.word\t3\n",
+        "A_dispTab:\n\t.word\tObject.abort\n\t.word\tObject.type_name\n\t.word\tObject.copy\n\t.word\tA.get\n",
+        "B_protObj:\n\t.word\t7\n\t.word\t5\n\t.word\tB_dispTab\n",
+        // Attributes are initialized after those of the parent.
+        "B_init:\n",
+        "\tjal\tA_init\n",
+        // A dispatch on `self`, which is not checked for void.
+        "\tmove\t$a0 $s0\n\tlw\t$t1 8($a0)\n\tlw\t$t1 16($t1)\n\tjalr\t$t1\n",
+        "\t.globl\theap_start\nheap_start:\n",
+    ] {
+        assert!(asm.contains(expected), "missing {:?} in\n{}", expected, asm);
+    }
+
+    // Dynamic and static dispatch to `get`, past a check of the receiver
+    // for void that branches over the error.
+    for call in [
+        r"\tlw\t\$t1 8\(\$a0\)\n\tlw\t\$t1 12\(\$t1\)\n\tjalr\t\$t1\n",
+        r"\tjal\tA\.get\n",
+    ] {
+        let pattern = format!(r"\tbne\t\$a0 \$zero (label\d+)\n(?:\t.*\n)*?(label\d+):\n{call}");
+        let labels = captures(&asm, &pattern);
+        assert_eq!(labels[0], labels[1], "{}", asm);
+    }
+
+    // The arm on `B` is tried before the one on `Object`, each failed range
+    // check falling through to the next.
+    let labels = captures(
+        &asm,
+        r"\tblt\t\$t2 7 (label\d+)\n\tbgt\t\$t2 7 (label\d+)\n\tb\t(label\d+)\n(label\d+):\n\tblt\t\$t2 0 (label\d+)\n\tbgt\t\$t2 8 (label\d+)\n\tb\t(label\d+)\n(label\d+):\n\tjal\t_case_abort\n",
+    );
+    assert!(labels[0] == labels[1] && labels[1] == labels[3], "{}", asm);
+    assert!(labels[4] == labels[5] && labels[5] == labels[7], "{}", asm);
+    assert_ne!(labels[2], labels[6]);
+    for arm in [labels[2], labels[6]] {
+        assert!(asm.contains(&format!("\n{}:\n", arm)), "{}", asm);
+    }
+}
+
+/// The groups `pattern` captures in `asm`, which must match it.
+fn captures<'a>(asm: &'a str, pattern: &str) -> Vec<&'a str> {
+    let captures = Regex::new(pattern)
+        .unwrap()
+        .captures(asm)
+        .unwrap_or_else(|| panic!("missing {:?} in\n{}", pattern, asm));
+    captures
+        .iter()
+        .skip(1)
+        .map(|group| group.unwrap().as_str())
+        .collect()
+}
+
+/// Exercises the runtime of the native backends, reading from stdin.
+const NATIVE_PROGRAM: &str = r#"
+class A {
+  x : Int <- 3;
+  get() : Int { x };
+  sum(a : Int, b : Int, c : Int, d : Int, e : Int, f : Int, g : Int) : Int {
+    a - b + c * d + e - f + g / 2
+  };
+};
+class B inherits A { get() : Int { x + 1 }; };
+
+class Main inherits IO {
+  fact(n : Int) : Int { if n = 0 then 1 else n * fact(n - 1) fi };
+
+  main() : Object {
+    let a : A <- new B, s : String <- in_string() in {
+      out_int(a.get()).out_int(a@A.get()).out_string(" ");
+      case a of b : B => out_string("B"); o : Object => out_string("O"); esac;
+      out_string(if a = a.copy() then "eq " else "ne " fi);
+      out_string(if "ab" = "a".concat("b") then "eq " else "ne " fi);
+      out_int(a.sum(1, 2, 3, 4, 5, 6, 7)).out_string(" ");
+      out_int(fact(10)).out_string(" ");
+      out_int(~5 / 2).out_string(" ");
+      out_int((~2147483647 - 1) / ~1).out_string(" ");
+      out_string((new SELF_TYPE).type_name()).out_string(" ");
+      out_string(s.substr(1, 2)).out_int(s.length() + in_int());
+      (let v : A in v).get();
+    }
+  };
+};
+"#;
+
+/// Runs a program compiled from `NATIVE_PROGRAM`, and checks its output.
+fn check_native(command: &mut Command) {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"hello\n 41\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "43 Bne eq 13 3628800 -2 -2147483648 Main el46"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "test.cl:26: Dispatch to void.\n"
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_x86() {
+    let (program, interner) = parse(NATIVE_PROGRAM);
+    let analysis = semantic::analyze(&program).unwrap();
+    let lowered = ir::lower(&analysis);
+    let asm = x86::generate(
+        &lowered,
+        &analysis.classes,
+        &interner,
+        &sources(NATIVE_PROGRAM),
+    );
+
+    let dir = env::temp_dir().join(format!("cool-test-x86-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    // Each call links in a directory of its own.
+    let executables = [dir.join("test"), dir.join("other")];
+    std::thread::scope(|scope| {
+        for executable in &executables {
+            scope.spawn(|| x86::link(&asm, executable).unwrap());
+        }
+    });
+
+    for executable in &executables {
+        check_native(&mut Command::new(executable));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_optimization_levels() {
+    let src = r#"
+class Main inherits IO {
+  main() : Object {
+    out_int((~2147483647 - 1) / ~1).out_string(" ").out_int(~7 / 2)
+  };
+};
+"#;
+    let (program, interner) = parse(src);
+    let analysis = semantic::analyze(&program).unwrap();
+
+    // Folding the divisions must not change what the program prints.
+    let dir = env::temp_dir().join(format!("cool-test-levels-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for level in [0, 1] {
+        let mut lowered = ir::lower(&analysis);
+        PassManager::with_level(level).run(&mut lowered, &analysis.classes);
+        assert_eq!(ir::print(&lowered, &interner).contains(" div "), level == 0);
+
+        let asm = x86::generate(&lowered, &analysis.classes, &interner, &sources(src));
+        let executable = dir.join(format!("test{}", level));
+        x86::link(&asm, &executable).unwrap();
+        let output = Command::new(&executable).output().unwrap();
+
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "-2147483648 -3");
+        assert!(output.status.success());
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_llvm() {
+    let (program, interner) = parse(NATIVE_PROGRAM);
+    let analysis = semantic::analyze(&program).unwrap();
+    let lowered = ir::lower(&analysis);
+    let ir = llvm::generate(
+        &lowered,
+        &analysis.classes,
+        &interner,
+        &sources(NATIVE_PROGRAM),
+    );
+
+    for expected in [
+        "%class.B = type { i64, i64, ptr, ptr }\n",
+        "@A_dispTab = constant [5 x ptr] [ptr @\"Object.abort\", ptr @\"Object.type_name\", ptr @\"Object.copy\", ptr @\"A.get\", ptr @\"A.sum\"]\n",
+        "define ptr @\"Main.fact\"(ptr %self, ptr %arg0) {\n",
+    ] {
+        assert!(ir.contains(expected), "missing {:?} in\n{}", expected, ir);
+    }
+
+    // Compiling the IR needs `llc`, which older releases only accept with
+    // opaque pointers enabled.
+    let dir = env::temp_dir().join(format!("cool-test-llvm-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (ir_path, asm_path) = (dir.join("test.ll"), dir.join("test.s"));
+    fs::write(&ir_path, &ir).unwrap();
+
+    let compiled = [&[][..], &["-opaque-pointers"][..]]
+        .into_iter()
+        .any(|flags| {
+            Command::new("llc")
+                .arg("-relocation-model=pic")
+                .args(flags)
+                .arg(&ir_path)
+                .arg("-o")
+                .arg(&asm_path)
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        });
+    if compiled {
+        let executable = dir.join("test");
+        x86::link(&fs::read_to_string(&asm_path).unwrap(), &executable).unwrap();
+        check_native(&mut Command::new(&executable));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_wasm() {
+    let (program, interner) = parse(NATIVE_PROGRAM);
+    let analysis = semantic::analyze(&program).unwrap();
+    let lowered = ir::lower(&analysis);
+    let module = wasm::generate(
+        &lowered,
+        &analysis.classes,
+        &interner,
+        &sources(NATIVE_PROGRAM),
+    );
+    let binary = wat::parse_str(&module).unwrap();
+
+    // Running the module needs a WebAssembly engine, here the one of Node.
+    if Command::new("node").arg("--version").output().is_err() {
+        return;
+    }
+    let dir = env::temp_dir().join(format!("cool-test-wasm-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (host, binary_path) = (dir.join("host.mjs"), dir.join("test.wasm"));
+    fs::write(&host, wasm::HOST).unwrap();
+    fs::write(&binary_path, binary).unwrap();
+
+    check_native(Command::new("node").arg(&host).arg(&binary_path));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,207 @@
+use cool::{
+    ast::{bindings::Tree, converter},
+    diagnostics::{
+        Diagnostic, Emitter, ToDiagnostic,
+        explain::{EXPLANATIONS, Explanation},
+        json, sarif,
+    },
+    semantic,
+    util::{
+        interner::Interner,
+        source::SourceMap,
+        span::{FileId, Span},
+    },
+};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+#[test]
+fn test_render() {
+    let src = "class Main {\n\tmain() : Int { \"zero\" };\n};\nclass A inherits Int { };\n";
+    let (program, interner) = parse(src);
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+
+    let diagnostics: Vec<_> = errors
+        .iter()
+        .map(|error| error.to_diagnostic(&interner))
+        .collect();
+    let sources = sources(src);
+    let emitter = Emitter::new(&sources, false);
+
+    // Tabs are expanded, and the carets follow.
+    assert_eq!(
+        emitter.render_all(&diagnostics),
+        r#"error[S0011]: mismatched types
+ --> test.cl:2:17
+  |
+2 |     main() : Int { "zero" };
+  |                    ^^^^^^ expected `Int`, found `String`
+
+error[S0004]: class `A` cannot inherit from `Int`
+ --> test.cl:4:18
+  |
+4 | class A inherits Int { };
+  |                  ^^^
+  |
+  = note: `Int`, `String`, `Bool` and `SELF_TYPE` cannot be inherited from
+"#
+    );
+
+    // Spans over several lines are underlined up to the end of the first,
+    // and color only wraps the styled parts.
+    let diagnostic =
+        Diagnostic::error("oops", Span::new(FileId::default(), 6, 40)).with_help("try again");
+    let emitter = Emitter::new(&sources, true);
+    assert_eq!(
+        emitter.render(&diagnostic),
+        "\x1b[1;31merror\x1b[0m\x1b[1m: oops\x1b[0m\n \
+         \x1b[1;34m-->\x1b[0m test.cl:1:7\n  \
+         \x1b[1;34m|\x1b[0m\n\
+         \x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m class Main {\n  \
+         \x1b[1;34m|\x1b[0m       \x1b[1;31m^^^^^^\x1b[0m\n  \
+         \x1b[1;34m|\x1b[0m\n  \
+         \x1b[1;34m=\x1b[0m \x1b[1mhelp\x1b[0m: try again\n"
+    );
+}
+
+#[test]
+fn test_machine_readable() {
+    let src = "class Main {\n\tmain() : Int { \"z\u{e9}ro\" };\n};\n";
+    let (program, interner) = parse(src);
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+
+    let diagnostics: Vec<_> = errors
+        .iter()
+        .map(|error| error.to_diagnostic(&interner))
+        .collect();
+    let sources = sources(src);
+
+    // Offsets and columns count bytes.
+    assert_eq!(
+        json::render(&sources, &diagnostics),
+        "{\"severity\":\"error\",\"code\":\"S0011\",\"message\":\"mismatched types\",\
+         \"file\":\"test.cl\",\
+         \"start\":{\"offset\":29,\"line\":2,\"column\":17},\
+         \"end\":{\"offset\":36,\"line\":2,\"column\":24},\
+         \"label\":\"expected `Int`, found `String`\",\"notes\":[],\"help\":[]}\n"
+    );
+
+    // SARIF columns count characters.
+    let log = sarif::render(&sources, &diagnostics);
+    assert!(log.starts_with("{\"$schema\":"));
+    assert!(log.contains("\"version\":\"2.1.0\""));
+    assert!(log.contains("\"rules\":[{\"id\":\"S0011\"}]"));
+    assert!(log.contains("\"ruleId\":\"S0011\",\"level\":\"error\""));
+    assert!(
+        log.contains("\"message\":{\"text\":\"mismatched types: expected `Int`, found `String`\"}")
+    );
+    assert!(log.contains(
+        "\"region\":{\"startLine\":2,\"startColumn\":17,\"endLine\":2,\"endColumn\":23,\
+         \"byteOffset\":29,\"byteLength\":7}"
+    ));
+
+    let diagnostic = Diagnostic::error("a \"quoted\"\tname", Span::new(FileId::default(), 0, 5))
+        .with_note("line\nbreak")
+        .with_help("\u{1}");
+    assert_eq!(
+        json::render(&sources, &[diagnostic]),
+        "{\"severity\":\"error\",\"code\":null,\"message\":\"a \\\"quoted\\\"\\tname\",\
+         \"file\":\"test.cl\",\
+         \"start\":{\"offset\":0,\"line\":1,\"column\":1},\
+         \"end\":{\"offset\":5,\"line\":1,\"column\":6},\
+         \"label\":null,\"notes\":[\"line\\nbreak\"],\"help\":[\"\\u0001\"]}\n"
+    );
+}
+
+#[test]
+fn test_several_files() {
+    let mut sources = SourceMap::new();
+    sources.add(
+        "main.cl",
+        b"class Main {\n    main() : Object { new Counter.next() };\n};\n",
+    );
+    sources.add(
+        "counter.cl",
+        b"class Counter {\n    count : Int;\n    next() : Int { \"1\" };\n};\n",
+    );
+
+    let mut interner = Interner::with_capacity(64);
+    let program = converter::convert_all(&sources, &mut interner).unwrap();
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+    let diagnostics: Vec<_> = errors
+        .iter()
+        .map(|error| error.to_diagnostic(&interner))
+        .collect();
+
+    assert_eq!(
+        Emitter::new(&sources, false).render_all(&diagnostics),
+        r#"error[S0011]: mismatched types
+ --> counter.cl:3:20
+  |
+3 |     next() : Int { "1" };
+  |                    ^^^ expected `Int`, found `String`
+"#
+    );
+    assert!(json::render(&sources, &diagnostics).contains("\"file\":\"counter.cl\""));
+    assert!(sarif::render(&sources, &diagnostics).contains("{\"uri\":\"counter.cl\"}"));
+}
+
+/// A source map of a single file, `test.cl`.
+fn sources(src: &str) -> SourceMap {
+    let mut sources = SourceMap::new();
+    sources.add("test.cl", src.as_bytes());
+    sources
+}
+
+/// Codes of the errors a program fails with.
+fn codes(src: &str) -> Vec<&'static str> {
+    let mut interner = Interner::with_capacity(64);
+    let tree = Tree::new(src.as_bytes());
+
+    match converter::convert(src.as_bytes(), &tree, &mut interner) {
+        Ok(program) => match semantic::analyze(&program) {
+            Ok(_) => Vec::new(),
+            Err((_, errors)) => errors.iter().map(|error| error.inner.code()).collect(),
+        },
+        Err((_, errors)) => errors.iter().map(|error| error.inner.code()).collect(),
+    }
+}
+
+#[test]
+fn test_explain() {
+    let mut previous = "";
+    for explanation in EXPLANATIONS {
+        assert!(
+            explanation.code > previous,
+            "{} is out of order",
+            explanation.code
+        );
+        previous = explanation.code;
+
+        // Examples fail with the error they explain, and only with it.
+        if let Some(example) = explanation.example {
+            let codes = codes(example);
+            assert!(!codes.is_empty(), "{} example compiles", explanation.code);
+            assert!(
+                codes.iter().all(|code| *code == explanation.code),
+                "{} example fails with {:?}",
+                explanation.code,
+                codes
+            );
+        }
+    }
+
+    let explanation = Explanation::lookup("c0007").unwrap();
+    assert_eq!(explanation.code, "C0007");
+    assert!(
+        explanation
+            .render()
+            .starts_with("error[C0007]: `if` without `else`\n\nAn `if` has no `else` branch.")
+    );
+    assert!(explanation.render().ends_with(
+        "Erroneous code example:\n\n    class Main {\n        main() : Int { if true then 1 fi };\n    };\n"
+    ));
+    assert!(Explanation::lookup("C9999").is_none());
+}
@@ -0,0 +1,180 @@
+use std::thread;
+
+use cool::{
+    interp::{self, Error},
+    semantic,
+};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+/// Runs a well-typed program, returning what it printed along with the
+/// runtime error that stopped it, if any.
+fn run(src: &str, input: &str) -> (String, Option<Error>) {
+    let (program, interner) = parse(src);
+    let analysis = semantic::analyze(&program).unwrap();
+
+    let mut output = Vec::new();
+    let result = interp::run(&analysis.classes, &interner, input.as_bytes(), &mut output);
+
+    (
+        String::from_utf8(output).unwrap(),
+        result.err().map(|error| error.inner),
+    )
+}
+
+#[test]
+fn test_arithmetic_and_control_flow() {
+    let (output, error) = run(
+        r#"
+class Main inherits IO {
+  fact(n : Int) : Int { if n = 0 then 1 else n * fact(n - 1) fi };
+
+  main() : Object {
+    let i : Int <- 0, sum : Int in {
+      while i < 5 loop { sum <- sum + i; i <- i + 1; } pool;
+      out_int(sum).out_string(" ");
+      out_int(fact(10)).out_string(" ");
+      out_int(~7 / 2).out_string(" ");
+      out_string(if not (i <= 4) then "yes" else "no" fi);
+    }
+  };
+};
+"#,
+        "",
+    );
+
+    assert!(error.is_none());
+    assert_eq!(output, "10 3628800 -3 yes");
+}
+
+#[test]
+fn test_objects_and_dispatch() {
+    let (output, error) = run(
+        r#"
+class Animal {
+  name : String <- "animal";
+  sound() : String { "..." };
+  describe() : String { name.concat(" says ").concat(sound()) };
+  rename(n : String) : SELF_TYPE { { name <- n; self; } };
+};
+
+class Dog inherits Animal {
+  sound() : String { "woof" };
+};
+
+class Main inherits IO {
+  main() : Object {
+    let dog : Animal <- new Dog, copy : Animal <- dog.copy() in {
+      out_string(dog.rename("rex").describe()).out_string(" ");
+      out_string(copy.describe()).out_string(" ");
+      out_string(dog@Animal.sound()).out_string(" ");
+      out_string(dog.type_name()).out_string(" ");
+      out_string(if dog = copy then "same" else "different" fi).out_string(" ");
+      out_string(if isvoid dog.rename("x") then "void" else "object" fi);
+    }
+  };
+};
+"#,
+        "",
+    );
+
+    assert!(error.is_none());
+    assert_eq!(
+        output,
+        "rex says woof animal says woof ... Dog different object"
+    );
+}
+
+#[test]
+fn test_case_and_builtins() {
+    let (output, error) = run(
+        r#"
+class A { };
+class B inherits A { };
+
+class Main inherits IO {
+  classify(x : Object) : String {
+    case x of
+      a : A => "A";
+      b : B => "B";
+      i : Int => "Int";
+      o : Object => "Object";
+    esac
+  };
+
+  main() : Object {
+    let name : String <- in_string(), n : Int <- in_int() in {
+      out_string(classify(new B)).out_string(classify(new A));
+      out_string(classify(3)).out_string(classify(self)).out_string(" ");
+      out_string(name.substr(1, 3)).out_string(" ");
+      out_int(name.length() + n).out_string("\t\\\n");
+      out_int("a\nb".length()).out_string("\"q\"");
+    }
+  };
+};
+"#,
+        "coolest\n  -12 apples\n",
+    );
+
+    assert!(error.is_none());
+    assert_eq!(output, "BAIntObject ool -5\t\\\n3\"q\"");
+}
+
+#[test]
+fn test_non_ascii_strings() {
+    // Strings are sequences of bytes, as in the native backends.
+    let (output, error) = run(
+        r#"
+class Main inherits IO {
+  main() : Object {
+    let s : String <- "héllo" in {
+      out_int(s.length()).out_string(" ");
+      out_string(s.substr(1, 1).concat(s.substr(2, 1))).out_string(" ");
+      out_string(s.substr(3, 3));
+    }
+  };
+};
+"#,
+        "",
+    );
+
+    assert!(error.is_none());
+    assert_eq!(output, "6 é llo");
+}
+
+#[test]
+fn test_runtime_errors() {
+    let program = |body: &str| {
+        format!("class A {{ }}; class Main {{ a : A; main() : Object {{ {body} }}; }};")
+    };
+
+    let errors = [
+        ("a.copy()", "DispatchOnVoid"),
+        ("case a of x : Int => 0; esac", "CaseOnVoid"),
+        ("case new A of x : Int => 0; esac", "CaseNoMatch"),
+        ("\"abc\".substr(2, 2)", "SubstrOutOfRange"),
+        ("1 / 0", "DivisionByZero"),
+        ("new A.abort()", "Abort"),
+    ];
+    for (body, expected) in errors {
+        let (_, error) = run(&program(body), "");
+        let error = error.unwrap();
+
+        assert!(format!("{:?}", error).starts_with(expected), "{:?}", error);
+    }
+}
+
+#[test]
+fn test_stack_overflow() {
+    // The interpreter recurses on the native stack, give it enough room to
+    // reach its own limit.
+    let runner = thread::Builder::new()
+        .stack_size(512 * 1024 * 1024)
+        .spawn(|| run("class Main { main() : Object { main() }; };", ""))
+        .unwrap();
+    let (_, error) = runner.join().unwrap();
+
+    assert!(matches!(error, Some(Error::StackOverflow)));
+}
@@ -0,0 +1,255 @@
+use cool::{
+    diagnostics::ToDiagnostic,
+    ir::{
+        self, Block, BlockId, Function, FunctionKind, Inst, Operand, Program, Temp, Terminator,
+        opt::{Pass, PassManager},
+        verify::{self, Error, ErrorKind},
+    },
+    semantic,
+    util::{interner::sym, span::Span},
+};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+#[test]
+fn test_lower() {
+    let (program, interner) = parse(
+        r#"
+class Main inherits IO {
+  x : Int <- 1;
+  main() : Object {
+    let y : Int <- x in {
+      while y < 10 loop y <- y * 2 pool;
+      case y of i : Int => out_int(i); o : Object => abort(); esac;
+    }
+  };
+};
+"#,
+    );
+    let analysis = semantic::analyze(&program).unwrap();
+    let program = ir::lower(&analysis);
+
+    assert_eq!(ir::verify(&program, &analysis.classes), Ok(()));
+    assert_eq!(
+        ir::print(&program, &interner),
+        r#"init Main {
+bb0:
+  setattr x, 1
+  return self
+}
+
+method Main.main() {
+bb0:
+  %0 = getattr x
+  %1 = %0
+  jump bb1
+bb1:
+  %2 = %1
+  %3 = lt %2, 10
+  branch %3, bb2, bb3
+bb2:
+  %4 = %1
+  %5 = mul %4, 2
+  %1 = %5
+  jump bb1
+bb3:
+  %6 = %1
+  typecase %6 [Int: bb4, Object: bb5]
+bb4:
+  %8 = %6
+  %9 = %8
+  %10 = dispatch self Main.out_int(%9)
+  %7 = %10
+  jump bb6
+bb5:
+  %11 = %6
+  %12 = dispatch self Main.abort()
+  %7 = %12
+  jump bb6
+bb6:
+  return %7
+}
+"#
+    );
+}
+
+#[test]
+fn test_verify() {
+    let src = r#"
+class Main { x : Int; main() : Object { 0 }; };
+"#;
+    let (program, mut interner) = parse(src);
+    let analysis = semantic::analyze(&program).unwrap();
+    let main = sym::MAIN_CLASS;
+    let y = interner.intern("y");
+
+    let program = Program {
+        functions: vec![Function {
+            class: main,
+            kind: FunctionKind::Method(sym::MAIN),
+            params: Vec::new(),
+            temps: 2,
+            blocks: vec![
+                Block {
+                    insts: vec![Inst::GetAttr {
+                        dest: Temp(0),
+                        name: y,
+                    }],
+                    terminator: Terminator::Branch {
+                        condition: Operand::Bool(true),
+                        then: BlockId(1),
+                        otherwise: BlockId(2),
+                    },
+                },
+                Block {
+                    insts: vec![Inst::Dispatch {
+                        dest: Temp(1),
+                        receiver: Operand::SelfObject,
+                        class: main,
+                        method: sym::MAIN,
+                        args: vec![Operand::Temp(Temp(0))],
+                        span: Span::default(),
+                    }],
+                    terminator: Terminator::Jump(BlockId(2)),
+                },
+                // Only assigned on one of the paths leading here.
+                Block {
+                    insts: Vec::new(),
+                    terminator: Terminator::Return(Operand::Temp(Temp(1))),
+                },
+            ],
+        }],
+    };
+    let errors = ir::verify(&program, &analysis.classes).unwrap_err();
+
+    // Errors are reported at the name of the method of their function.
+    let diagnostics: Vec<_> = verify::locate(errors, &program, &analysis.classes)
+        .iter()
+        .map(|error| error.to_diagnostic(&interner))
+        .map(|diagnostic| {
+            let span = diagnostic.span;
+            (
+                diagnostic.message,
+                &src[span.start as usize..span.end as usize],
+            )
+        })
+        .collect();
+    assert_eq!(
+        diagnostics,
+        [
+            ("invalid IR: undefined attribute `y`".to_owned(), "main"),
+            (
+                "invalid IR: method `main` takes 0 arguments but 1 was supplied".to_owned(),
+                "main"
+            ),
+            (
+                "invalid IR: temporary %1 is read before it is assigned".to_owned(),
+                "main"
+            ),
+        ]
+    );
+
+    let errors: Vec<_> = ir::verify(&program, &analysis.classes)
+        .unwrap_err()
+        .into_iter()
+        .map(|Error { block, kind, .. }| (block.0, kind))
+        .collect();
+
+    assert_eq!(
+        errors,
+        [
+            (0, ErrorKind::UndefinedAttribute(y)),
+            (
+                1,
+                ErrorKind::ArgumentCountMismatch {
+                    method: sym::MAIN,
+                    expected: 0,
+                    found: 1
+                }
+            ),
+            (2, ErrorKind::UseBeforeDefinition(Temp(1))),
+        ]
+    );
+}
+
+#[test]
+fn test_optimize() {
+    let (program, interner) = parse(
+        r#"
+class Main inherits IO {
+  main() : Object {
+    let x : Int <- 2 + 3 * 4, unused : Int <- x * 2 in {
+      if x < 20 then out_int(x) else out_string("big") fi;
+      x / 0;
+    }
+  };
+};
+"#,
+    );
+    let analysis = semantic::analyze(&program).unwrap();
+    let mut program = ir::lower(&analysis);
+    PassManager::with_level(1).run(&mut program, &analysis.classes);
+
+    assert_eq!(ir::verify(&program, &analysis.classes), Ok(()));
+    assert_eq!(
+        ir::print(&program, &interner),
+        r#"init Main {
+bb0:
+  return self
+}
+
+method Main.main() {
+bb0:
+  %10 = call self Main.out_int(14)
+  %13 = div 14, 0
+  return %13
+}
+"#
+    );
+
+    // Without dead code elimination, the unused binding and the branch not
+    // taken stay in place.
+    let mut program = ir::lower(&analysis);
+    let mut passes = PassManager::with_level(1);
+    passes.disable(Pass::from_name("dce").unwrap());
+    passes.run(&mut program, &analysis.classes);
+
+    assert_eq!(ir::verify(&program, &analysis.classes), Ok(()));
+    let printed = ir::print(&program, &interner);
+    assert!(printed.contains("  %5 = 28\n"));
+    assert!(printed.contains("Main.out_string(\"big\")"));
+}
+
+#[test]
+fn test_devirtualize() {
+    let (program, interner) = parse(
+        r#"
+class A { get() : Int { 1 }; };
+class B inherits A { get() : Int { 2 }; };
+class Main {
+  main() : Object {
+    let a : A <- new B, b : B <- new B in a.get() + b.get() + a.copy().get()
+  };
+};
+"#,
+    );
+    let analysis = semantic::analyze(&program).unwrap();
+    let mut program = ir::lower(&analysis);
+    let mut passes = PassManager::with_level(0);
+    passes.enable(Pass::from_name("devirt").unwrap());
+    passes.run(&mut program, &analysis.classes);
+
+    assert_eq!(ir::verify(&program, &analysis.classes), Ok(()));
+    let printed = ir::print(&program, &interner);
+    // `B` overrides `get`, but no descendant of `B` does, and none of `A`
+    // overrides `copy`.
+    assert!(
+        printed.contains("%4 = dispatch %5 A.get()\n"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("%6 = call %7 B.get()\n"), "{}", printed);
+    assert!(printed.contains("%10 = call %11 A.copy()\n"), "{}", printed);
+}
@@ -0,0 +1,147 @@
+use cool::{
+    ast::{bindings::Tree, lex},
+    util::source::SourceFile,
+};
+
+fn assert_tokens(src: &str, expected: &str) {
+    let file = SourceFile::new("test.cl", src.as_bytes());
+    let tree = Tree::new(file.src.as_bytes());
+    let tokens = lex::tokens(&file, &tree);
+
+    assert_eq!(lex::print(&file, &tokens), expected);
+}
+
+#[test]
+fn test_tokens() {
+    assert_tokens(
+        r#"class Main inherits IO { -- a comment
+    (* a (* nested *) comment *)
+    x : Int <- 007;
+    main() : SELF_TYPE {
+        if true then self@IO.out_string("hi") else not False fi
+    };
+    f(t : Bool) : Object { case t of b : Bool => isvoid ~1 + 2 * 3 / 4 - 5; esac };
+};
+"#,
+        r#"#name "test.cl"
+#1 CLASS
+#1 TYPEID Main
+#1 INHERITS
+#1 TYPEID IO
+#1 '{'
+#3 OBJECTID x
+#3 ':'
+#3 TYPEID Int
+#3 ASSIGN
+#3 INT_CONST 007
+#3 ';'
+#4 OBJECTID main
+#4 '('
+#4 ')'
+#4 ':'
+#4 TYPEID SELF_TYPE
+#4 '{'
+#5 IF
+#5 BOOL_CONST true
+#5 THEN
+#5 OBJECTID self
+#5 '@'
+#5 TYPEID IO
+#5 '.'
+#5 OBJECTID out_string
+#5 '('
+#5 STR_CONST "hi"
+#5 ')'
+#5 ELSE
+#5 NOT
+#5 TYPEID False
+#5 FI
+#6 '}'
+#6 ';'
+#7 OBJECTID f
+#7 '('
+#7 OBJECTID t
+#7 ':'
+#7 TYPEID Bool
+#7 ')'
+#7 ':'
+#7 TYPEID Object
+#7 '{'
+#7 CASE
+#7 OBJECTID t
+#7 OF
+#7 OBJECTID b
+#7 ':'
+#7 TYPEID Bool
+#7 DARROW
+#7 ISVOID
+#7 '~'
+#7 INT_CONST 1
+#7 '+'
+#7 INT_CONST 2
+#7 '*'
+#7 INT_CONST 3
+#7 '/'
+#7 INT_CONST 4
+#7 '-'
+#7 INT_CONST 5
+#7 ';'
+#7 ESAC
+#7 '}'
+#7 ';'
+#8 '}'
+#8 ';'
+"#,
+    );
+}
+
+#[test]
+fn test_strings() {
+    assert_tokens(
+        "x <- \"tab\\tnew\\nline\\\ncontinued \\\"quoted\\\" \\q\";\n",
+        r#"#name "test.cl"
+#1 OBJECTID x
+#1 ASSIGN
+#2 STR_CONST "tab\tnew\nline\ncontinued \"quoted\" q"
+#2 ';'
+"#,
+    );
+
+    let longest = format!("\"{}\"\n", "a".repeat(1024));
+    let expected = format!("#name \"test.cl\"\n#1 STR_CONST \"{}\"\n", "a".repeat(1024));
+    assert_tokens(&longest, &expected);
+}
+
+#[test]
+fn test_errors() {
+    assert_tokens(
+        "s <- \"open\nx _ ! *) \"a\0b\" \"c\\\0d\" y",
+        r#"#name "test.cl"
+#1 OBJECTID s
+#1 ASSIGN
+#1 ERROR "Unterminated string constant"
+#2 OBJECTID x
+#2 ERROR "_"
+#2 ERROR "!"
+#2 ERROR "Unmatched *)"
+#2 ERROR "String contains null character."
+#2 ERROR "String contains escaped null character."
+#2 OBJECTID y
+"#,
+    );
+
+    let long = format!("\"{}\" z\n", "a".repeat(1025));
+    assert_tokens(
+        &long,
+        "#name \"test.cl\"\n#1 ERROR \"String constant too long\"\n#1 OBJECTID z\n",
+    );
+
+    assert_tokens(
+        "a\n\"eof",
+        "#name \"test.cl\"\n#1 OBJECTID a\n#2 ERROR \"EOF in string constant\"\n",
+    );
+    assert_tokens(
+        "a\n(* (* *)\n",
+        "#name \"test.cl\"\n#1 OBJECTID a\n#3 ERROR \"EOF in comment\"\n",
+    );
+}
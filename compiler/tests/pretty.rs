@@ -0,0 +1,85 @@
+use cool::{
+    ast::{bindings::Tree, converter, pretty},
+    util::interner::Interner,
+};
+
+fn format(src: &str) -> String {
+    let mut interner = Interner::with_capacity(64);
+    let tree = Tree::new(src.as_bytes());
+    let program = converter::convert(src.as_bytes(), &tree, &mut interner).unwrap();
+
+    pretty::format(src.as_bytes(), &tree, &program, &interner)
+}
+
+#[test]
+fn test_format() {
+    let src = r#"-- A list.
+class List inherits IO { item:Int; next : List;   -- the rest
+  init(i:Int,n:List):List{{item<-i;next<-n;self;}};
+  (* Prints every item. *)
+  print():Object{ { out_int(item); out_string("\n\t");
+   if isvoid next then 0 else next.print() fi;
+   -- done
+  } };
+  sum() : Int { let s : Int <- 0, l : List <- self in { while not isvoid l loop { s <- s + l@List.item(); l <- l.next(); } pool; s; } };
+  kind(x : Object) : String { case x of i : Int => "int"; o : Object => if true then "a" else { "b"; } fi; esac };
+};
+class Main { main() : Object { (new List).init(~1 * (2 - 3), new List).print() }; };
+class Empty {};
+"#;
+    let expected = r#"-- A list.
+class List inherits IO {
+    item : Int;
+    next : List; -- the rest
+
+    init(i : Int, n : List) : List {
+        {
+            item <- i;
+            next <- n;
+            self;
+        }
+    };
+
+    (* Prints every item. *)
+    print() : Object {
+        {
+            out_int(item);
+            out_string("\n\t");
+            if isvoid next then 0 else next.print() fi;
+            -- done
+        }
+    };
+
+    sum() : Int {
+        let s : Int <- 0, l : List <- self in {
+            while not isvoid l loop {
+                s <- s + l@List.item();
+                l <- l.next();
+            } pool;
+            s;
+        }
+    };
+
+    kind(x : Object) : String {
+        case x of
+            i : Int => "int";
+            o : Object => if true then
+                "a"
+            else {
+                "b";
+            } fi;
+        esac
+    };
+};
+
+class Main {
+    main() : Object { (new List).init(~1 * (2 - 3), new List).print() };
+};
+
+class Empty {};
+"#;
+
+    assert_eq!(format(src), expected);
+    // Formatted programs stay as they are.
+    assert_eq!(format(expected), expected);
+}
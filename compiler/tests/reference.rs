@@ -0,0 +1,192 @@
+use cool::{ast::reference, util::source::SourceMap};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+fn assert_reference(src: &str, expected: &str) {
+    let (program, interner) = parse(src);
+    let mut sources = SourceMap::new();
+    sources.add("test.cl", src.as_bytes());
+
+    assert_eq!(reference::print(&program, &sources, &interner), expected);
+}
+
+#[test]
+fn test_features() {
+    assert_reference(
+        "class A inherits IO {\n    x : Int;\n    f(a : Int) : A { self };\n};\n",
+        r#"#1
+_program
+  #1
+  _class
+    A
+    IO
+    "test.cl"
+    (
+    #2
+    _attr
+      x
+      Int
+      #2
+      _no_expr
+      : _no_type
+    #3
+    _method
+      f
+      #3
+      _formal
+        a
+        Int
+      A
+      #3
+      _object
+        self
+      : _no_type
+    )
+"#,
+    );
+}
+
+#[test]
+fn test_exprs() {
+    assert_reference(
+        r#"class Main {
+    main() : Object {
+        {
+            out_int((1 + 002) * ~3);
+            if isvoid x then not false else x <= 2 fi;
+            while true loop (new Main)@Object.copy() pool;
+            let y : Int, z : Int <- 1 in case y of s : String => "a"; esac;
+        }
+    };
+};
+"#,
+        r#"#1
+_program
+  #1
+  _class
+    Main
+    Object
+    "test.cl"
+    (
+    #2
+    _method
+      main
+      Object
+      #3
+      _block
+        #4
+        _dispatch
+          #4
+          _object
+            self
+          : _no_type
+          out_int
+          (
+          #4
+          _mul
+            #4
+            _plus
+              #4
+              _int
+                1
+              : _no_type
+              #4
+              _int
+                002
+              : _no_type
+            : _no_type
+            #4
+            _neg
+              #4
+              _int
+                3
+              : _no_type
+            : _no_type
+          : _no_type
+          )
+        : _no_type
+        #5
+        _cond
+          #5
+          _isvoid
+            #5
+            _object
+              x
+            : _no_type
+          : _no_type
+          #5
+          _comp
+            #5
+            _bool
+              0
+            : _no_type
+          : _no_type
+          #5
+          _leq
+            #5
+            _object
+              x
+            : _no_type
+            #5
+            _int
+              2
+            : _no_type
+          : _no_type
+        : _no_type
+        #6
+        _loop
+          #6
+          _bool
+            1
+          : _no_type
+          #6
+          _static_dispatch
+            #6
+            _new
+              Main
+            : _no_type
+            Object
+            copy
+            (
+            )
+          : _no_type
+        : _no_type
+        #7
+        _let
+          y
+          Int
+          #7
+          _no_expr
+          : _no_type
+          #7
+          _let
+            z
+            Int
+            #7
+            _int
+              1
+            : _no_type
+            #7
+            _typcase
+              #7
+              _object
+                y
+              : _no_type
+              #7
+              _branch
+                s
+                String
+                #7
+                _string
+                  "a"
+                : _no_type
+            : _no_type
+          : _no_type
+        : _no_type
+      : _no_type
+    )
+"#,
+    );
+}
@@ -0,0 +1,314 @@
+use cool::{
+    ast,
+    semantic::{self, Binding, Error},
+    util::span::Spanned,
+};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+#[test]
+fn test_class_table() {
+    let (program, mut interner) = parse(
+        r#"
+class Main inherits A { main() : Object { 0 }; };
+class A inherits IO { };
+"#,
+    );
+    let classes = semantic::analyze(&program).unwrap().classes;
+
+    let main = interner.intern("Main");
+    let ancestors: Vec<_> = classes
+        .ancestors(main)
+        .map(|name| interner.lookup(&name).to_owned())
+        .collect();
+
+    assert_eq!(classes.len(), 7);
+    assert_eq!(ancestors, ["Main", "A", "IO", "Object"]);
+}
+
+#[test]
+fn test_invalid_hierarchy() {
+    let (program, _) = parse(
+        r#"
+class A inherits B { };
+class B inherits A { };
+class C inherits Int { };
+class D inherits E { };
+class String { };
+class D { };
+class Main { main() : Object { 0 }; };
+"#,
+    );
+    let (analysis, errors) = semantic::analyze(&program).unwrap_err();
+    let classes = analysis.classes;
+    let errors: Vec<_> = errors.into_iter().map(|error| error.inner).collect();
+
+    assert!(matches!(
+        errors[..],
+        [
+            Error::InheritanceCycle(_),
+            Error::InheritanceCycle(_),
+            Error::ForbiddenInheritance { .. },
+            Error::UndefinedParent { .. },
+            Error::BasicClassRedefinition(_),
+            Error::ClassRedefinition(_),
+        ]
+    ));
+    // Every invalid parent is replaced, leaving a tree rooted at `Object`.
+    assert!(
+        classes
+            .iter()
+            .all(|class| classes.ancestors(class.name).count() <= 2)
+    );
+}
+
+#[test]
+fn test_typing() {
+    let (program, interner) = parse(
+        r#"
+class Main inherits IO {
+  count : Int <- 0;
+
+  main() : SELF_TYPE {
+    let a : A <- new B in {
+      out_int(a@A.id(count));
+      case a of
+        b : B => b;
+        c : C => c;
+      esac;
+      if isvoid a then new B else new C fi;
+      copy();
+    }
+  };
+};
+
+class A {
+  id(x : Int) : Int { x };
+  me() : SELF_TYPE { self };
+};
+class B inherits A { };
+class C inherits A { };
+"#,
+    );
+    let analysis = semantic::analyze(&program).unwrap();
+
+    let ast::Feature::Method(main) = &program.classes[0].features.0[1] else {
+        unreachable!();
+    };
+    let ast::ExprKind::Let { body, .. } = &main.body.kind else {
+        unreachable!();
+    };
+    let ast::ExprKind::Block { body } = &body.kind else {
+        unreachable!();
+    };
+    let types: Vec<_> = body
+        .iter()
+        .map(|expr| interner.lookup(&analysis.types.get(expr).unwrap()))
+        .collect();
+
+    assert_eq!(types, ["SELF_TYPE", "A", "A", "SELF_TYPE"]);
+}
+
+#[test]
+fn test_type_errors() {
+    let (program, _) = parse(
+        r#"
+class Main {
+  a : Int <- "zero";
+
+  f(x : SELF_TYPE) : Object {
+    {
+      if 1 then 2 else 3 fi;
+      undeclared;
+      1 = "one";
+      main(1, 2);
+      (new Object).nope();
+      case 0 of y : Int => y; z : Int => z; esac;
+      new Nope;
+    }
+  };
+
+  main() : Object { 0 };
+};
+"#,
+    );
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+    let errors: Vec<_> = errors.into_iter().map(|error| error.inner).collect();
+
+    assert!(matches!(
+        errors[..],
+        [
+            Error::TypeMismatch { .. },
+            Error::SelfTypeNotAllowed,
+            Error::TypeMismatch { .. },
+            Error::UndeclaredIdentifier(_),
+            Error::InvalidComparison { .. },
+            Error::ArgumentCountMismatch { .. },
+            Error::UndefinedMethod { .. },
+            Error::DuplicateCaseBranch(_),
+            Error::UndefinedType(_),
+        ]
+    ));
+}
+
+#[test]
+fn test_feature_resolution() {
+    let (program, _) = parse(
+        r#"
+class A {
+  a : Int;
+  f(x : Int, y : Int) : Int { x };
+  g() : Object { self };
+};
+
+class B inherits A {
+  a : Int;
+  b : Int;
+  b : String;
+  f(x : Int, x : Int) : Int { x };
+  f() : Int { 0 };
+  g() : Int { 0 };
+  out_string() : Object { self };
+};
+
+class C inherits IO {
+  out_string(s : Int) : SELF_TYPE { self };
+};
+
+class Main { main() : Object { 0 }; };
+"#,
+    );
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+    let errors: Vec<_> = errors.into_iter().map(|error| error.inner).collect();
+
+    assert!(matches!(
+        errors[..],
+        [
+            Error::InheritedAttributeRedefinition { .. },
+            Error::AttributeRedefinition(_),
+            Error::FormalRedefinition(_),
+            Error::MethodRedefinition(_),
+            Error::OverrideTypeMismatch { .. },
+            Error::OverrideTypeMismatch { .. },
+        ]
+    ));
+}
+
+#[test]
+fn test_scope_resolution() {
+    let (program, interner) = parse(
+        r#"
+class A {
+  x : Int;
+};
+
+class B inherits A {
+  f(y : Int) : Object {
+    let z : Int <- x in {
+      y;
+      case z of x : Int => x; esac;
+      z <- y;
+      self;
+    }
+  };
+};
+
+class Main { main() : Object { 0 }; };
+"#,
+    );
+    let analysis = semantic::analyze(&program).unwrap();
+
+    let ast::Feature::Method(main) = &program.classes[1].features.0[0] else {
+        unreachable!();
+    };
+    let ast::ExprKind::Let { bindings, body } = &main.body.kind else {
+        unreachable!();
+    };
+    let ast::ExprKind::Block { body } = &body.kind else {
+        unreachable!();
+    };
+    let ast::ExprKind::Case { body: arms, .. } = &body[1].kind else {
+        unreachable!();
+    };
+
+    let initializer = bindings.0[0].right.as_ref().unwrap();
+    assert!(matches!(
+        analysis.bindings.get(initializer),
+        Some(Binding::Attribute { class, .. }) if interner.lookup(&class) == "A"
+    ));
+    assert!(matches!(
+        analysis.bindings.get(&body[0]),
+        Some(Binding::Param(_))
+    ));
+    assert!(matches!(
+        analysis.bindings.get(&arms[0].value),
+        Some(Binding::Case(_))
+    ));
+    assert!(matches!(
+        analysis.bindings.get(&body[2]),
+        Some(Binding::Let(_))
+    ));
+    assert!(matches!(
+        analysis.bindings.get(&body[3]),
+        Some(Binding::SelfObject)
+    ));
+}
+
+#[test]
+fn test_scope_errors() {
+    let (program, _) = parse(
+        r#"
+class Main {
+  f(a : Int, a : Int) : Object {
+    {
+      let self : Int in 0;
+      case 0 of self : Int => 0; esac;
+      let b : Int in b;
+      b;
+    }
+  };
+
+  main() : Object { 0 };
+};
+"#,
+    );
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+    let errors: Vec<_> = errors.into_iter().map(|error| error.inner).collect();
+
+    assert!(matches!(
+        errors[..],
+        [
+            Error::FormalRedefinition(_),
+            Error::SelfBinding,
+            Error::SelfBinding,
+            Error::UndeclaredIdentifier(_),
+        ]
+    ));
+}
+
+#[test]
+fn test_entry_point() {
+    let (program, _) = parse("class Main { main(argc : Int) : Object { argc }; };");
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+
+    assert!(matches!(
+        errors[..],
+        [Spanned {
+            inner: Error::MissingMainMethod,
+            ..
+        }]
+    ));
+
+    let (program, _) = parse("class A { };");
+    let (_, errors) = semantic::analyze(&program).unwrap_err();
+
+    assert!(matches!(
+        errors[..],
+        [Spanned {
+            inner: Error::MissingMainClass,
+            ..
+        }]
+    ));
+}
@@ -0,0 +1,130 @@
+use cool::ast::sexp;
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+fn assert_sexp(src: &str, expected: &str) {
+    let (program, interner) = parse(src);
+    assert_eq!(sexp::print(&program, &interner), expected);
+}
+
+#[test]
+fn test_features() {
+    assert_sexp(
+        "class A inherits IO { x : Int; f(a : Int, b : String) : A { self }; };",
+        r#"(program
+  (classes: 
+    (class
+      (name: A)
+      (inherits: IO)
+      (features: 
+        (attribute
+          (name: x)
+          (ty: Int)
+          (initializer: ))
+        (method
+          (name: f)
+          (params: 
+            (param
+              (name: a)
+              (ty: Int))
+            (param
+              (name: b)
+              (ty: String)))
+          (return_ty: A)
+          (body: 
+            (ident self)))))))
+"#,
+    );
+}
+
+#[test]
+fn test_exprs() {
+    assert_sexp(
+        r#"
+class Main {
+    main() : Object {
+        {
+            x <- not 1 <= ~2;
+            if isvoid x then "a" else true fi;
+            while false loop (new Main)@Object.copy() pool;
+            let y : Int in case y of z : Int => z; esac;
+        }
+    };
+};
+"#,
+        r#"(program
+  (classes: 
+    (class
+      (name: Main)
+      (inherits: )
+      (features: 
+        (method
+          (name: main)
+          (params: )
+          (return_ty: Object)
+          (body: 
+            (block
+              (body: 
+                (assignment
+                  (name: x)
+                  (right: 
+                    (unary
+                      (op: not)
+                      (right: 
+                        (binary
+                          (op: lte)
+                          (left: 
+                            (int 1))
+                          (right: 
+                            (unary
+                              (op: complement)
+                              (right: 
+                                (int 2)))))))))
+                (conditional
+                  (condition: 
+                    (unary
+                      (op: is_void)
+                      (right: 
+                        (ident x))))
+                  (consequence: 
+                    (string "a"))
+                  (alternative: 
+                    (bool true)))
+                (repeat
+                  (condition: 
+                    (bool false))
+                  (body: 
+                    (dispatch
+                      (qualifier: 
+                        (qualifier
+                          (value: 
+                            (paren
+                              (value: 
+                                (new
+                                  (ty: Main)))))
+                          (parent: Object)))
+                      (method: copy)
+                      (args: ))))
+                (let
+                  (bindings: 
+                    (let_binding
+                      (name: y)
+                      (ty: Int)
+                      (right: )))
+                  (body: 
+                    (case
+                      (value: 
+                        (ident y))
+                      (body: 
+                        (case_arm
+                          (pat: 
+                            (case_pattern
+                              (name: z)
+                              (ty: Int)))
+                          (value: 
+                            (ident z)))))))))))))))
+"#,
+    );
+}
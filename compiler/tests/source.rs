@@ -0,0 +1,63 @@
+use cool::util::{
+    source::{SourceFile, SourceMap},
+    span::{FileId, Span},
+};
+
+#[test]
+fn test_lines() {
+    let file = SourceFile::new("dir/main.cl", b"class Main {\r\n\tx : Int;\n};");
+    assert_eq!(file.file_name(), "main.cl");
+    assert_eq!(file.line_count(), 3);
+
+    assert_eq!(file.line_col(0), (1, 1));
+    assert_eq!(file.line_col(12), (1, 13));
+    assert_eq!(file.line_col(14), (2, 1));
+    assert_eq!(file.line_col(16), (2, 3));
+    assert_eq!(file.line_col(25), (3, 2));
+    assert_eq!(file.line_text(0), "class Main {");
+    assert_eq!(file.line_text(1), "\tx : Int;");
+    assert_eq!(file.line_text(2), "};");
+
+    let span = Span::new(FileId::default(), 15, 16);
+    assert_eq!(file.text(span), "x");
+    assert_eq!(
+        file.text(span.to(Span::new(FileId::default(), 19, 22))),
+        "x : Int"
+    );
+}
+
+#[test]
+fn test_utf16() {
+    // `é` is 2 bytes and 1 UTF-16 unit, `𝄞` 4 bytes and 2 units.
+    let file = SourceFile::new("main.cl", "-- é𝄞\n\"𝄞\" x".as_bytes());
+
+    assert_eq!(file.utf16_position(3), (0, 3));
+    assert_eq!(file.utf16_position(5), (0, 4));
+    assert_eq!(file.utf16_position(9), (0, 6));
+    assert_eq!(file.utf16_position(17), (1, 5));
+
+    assert_eq!(file.utf16_offset(0, 4), 5);
+    assert_eq!(file.utf16_offset(1, 3), 15);
+    // Past the end of a line, and of the file.
+    assert_eq!(file.utf16_offset(0, 99), 9);
+    assert_eq!(file.utf16_offset(5, 0), file.src.len());
+}
+
+#[test]
+fn test_source_map() {
+    let mut sources = SourceMap::new();
+    let first = sources.add("a.cl", b"class A { };\n");
+    let second = sources.add("b.cl", b"\n\nclass B { };\n");
+
+    assert_eq!(first, FileId(0));
+    assert_eq!(second, FileId(1));
+    assert_eq!(sources.get(second).name, "b.cl");
+    assert_eq!(sources.line(Span::new(second, 2, 7)), 3);
+    assert_eq!(
+        sources
+            .files()
+            .map(|(_, file)| file.name.as_str())
+            .collect::<Vec<_>>(),
+        ["a.cl", "b.cl"]
+    );
+}
@@ -0,0 +1,257 @@
+use cool::{
+    ast::{ExprKind, Feature, bindings::Tree, converter},
+    diagnostics::ToDiagnostic,
+    util::{interner::Interner, source::SourceFile},
+};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+/// The messages of the syntax errors of a program, with their line and
+/// column.
+fn errors(src: &str) -> Vec<(String, usize, usize)> {
+    let mut interner = Interner::with_capacity(64);
+    let tree = Tree::new(src.as_bytes());
+    let (_, errors) = converter::convert(src.as_bytes(), &tree, &mut interner).unwrap_err();
+    let file = SourceFile::new("test.cl", src.as_bytes());
+
+    errors
+        .iter()
+        .map(|error| {
+            let (line, col) = file.line_col(error.span.start as usize);
+            (error.to_diagnostic(&interner).message, line, col)
+        })
+        .collect()
+}
+
+#[test]
+fn test_missing_tokens() {
+    for (src, message, col) in [
+        (
+            "class Main { main() : Int { 0 } };",
+            "expected `;` after method declaration",
+            32,
+        ),
+        (
+            "class Main { };\nclass A { }",
+            "expected `;` after class",
+            12,
+        ),
+        (
+            "class Main { main() : Int { if true then 1 else 2 }; };",
+            "missing `fi` to close `if`",
+            50,
+        ),
+        (
+            "class Main { main() : Int { while true loop 1 }; };",
+            "missing `pool` to close `while`",
+            46,
+        ),
+        (
+            "class Main { main( : Int { 0 }; };",
+            "missing `)` to close parameter list",
+            19,
+        ),
+        (
+            "class Main { main() : Int { 1 + }; };",
+            "expected expression after `+`",
+            32,
+        ),
+        ("class Main { x : ; };", "expected type after `:`", 17),
+    ] {
+        let line = if src.contains('\n') { 2 } else { 1 };
+        assert_eq!(errors(src), [(message.to_owned(), line, col)], "{}", src);
+    }
+}
+
+#[test]
+fn test_skipped_text() {
+    for (src, message, col) in [
+        (
+            "class Main { x : Int main() : Int { 0 }; };",
+            "expected `;` after attribute declaration",
+            16,
+        ),
+        (
+            "class Main { main() : Int { { 1; 2 } }; };",
+            "expected `;` after expression",
+            34,
+        ),
+        (
+            "class Main { main() : Int { foo(1,) }; };",
+            "unexpected `,` in argument list",
+            34,
+        ),
+        (
+            "class Main inherits { };",
+            "expected type after `inherits`",
+            12,
+        ),
+        (
+            "class Main { main() : Int { let x : Int <- 1 2 }; };",
+            "expected `in` in `let`",
+            46,
+        ),
+        (
+            "class main { };",
+            "class name `main` must start with an uppercase letter",
+            1,
+        ),
+    ] {
+        assert_eq!(errors(src), [(message.to_owned(), 1, col)], "{}", src);
+    }
+}
+
+#[test]
+fn test_missing_else() {
+    let src = r#"
+class Main {
+  main() : Int { if true then 1 fi };
+};
+"#;
+    assert_eq!(errors(src), [("`if` without `else`".to_owned(), 3, 18)]);
+}
+
+#[test]
+fn test_spans() {
+    let src = r#"
+class Main {
+  x : Int <- 1;
+  main(a : Int) : Object {
+    let y : Int <- a in case y of z : Int => z; esac
+  };
+};
+"#;
+    let (program, _) = parse(src);
+    let file = SourceFile::new("test.cl", src.as_bytes());
+
+    let class = &program.classes[0];
+    assert!(file.text(class.span).starts_with("class Main {"));
+    assert!(file.text(class.span).ends_with("  };\n};"));
+
+    let [Feature::Attribute(attribute), Feature::Method(method)] = &class.features.0[..] else {
+        panic!("unexpected features {:#?}", class.features);
+    };
+    assert_eq!(file.text(attribute.span), "x : Int <- 1");
+    assert_eq!(
+        file.text(class.features.0[1].span()),
+        file.text(method.span)
+    );
+    assert!(
+        file.text(method.span)
+            .starts_with("main(a : Int) : Object {")
+    );
+    assert_eq!(file.text(method.params.0[0].span), "a : Int");
+
+    let ExprKind::Let { bindings, body } = &method.body.kind else {
+        panic!("unexpected body {:#?}", method.body);
+    };
+    assert_eq!(file.text(bindings.0[0].span), "y : Int <- a");
+    let ExprKind::Case { body: arms, .. } = &body.kind else {
+        panic!("unexpected body {:#?}", body);
+    };
+    assert_eq!(file.text(arms[0].span), "z : Int => z");
+    assert_eq!(file.text(arms[0].pat.span), "z : Int");
+}
+
+#[test]
+fn test_strings() {
+    let src = "class Main { s : String <- \"a\\tb\\nc\\\nd\\\\e\\q\"; };";
+    let (program, _) = parse(src);
+    let Feature::Attribute(attribute) = &program.classes[0].features.0[0] else {
+        panic!("unexpected features {:#?}", program.classes[0].features);
+    };
+    let Some(ExprKind::String(value)) = attribute.initializer.as_ref().map(|expr| &expr.kind)
+    else {
+        panic!("unexpected initializer {:#?}", attribute.initializer);
+    };
+    assert_eq!(&**value, "a\tb\nc\nd\\eq");
+
+    // The grammar ends these strings at the escaped quote.
+    for (literal, expected) in [
+        (r#""q\"z""#, "q\"z"),
+        (r#""a\"b\"c""#, "a\"b\"c"),
+        (r#""\"\"""#, "\"\""),
+        (r#""a\\\"b\n""#, "a\\\"b\n"),
+    ] {
+        let src = format!("class Main {{ s : String <- {} + 1; }};", literal);
+        let (program, _) = parse(&src);
+        let file = SourceFile::new("test.cl", src.as_bytes());
+        let Feature::Attribute(attribute) = &program.classes[0].features.0[0] else {
+            panic!("unexpected features {:#?}", program.classes[0].features);
+        };
+        let Some(ExprKind::Binary { left, .. }) =
+            attribute.initializer.as_ref().map(|expr| &expr.kind)
+        else {
+            panic!("unexpected initializer {:#?}", attribute.initializer);
+        };
+        let ExprKind::String(value) = &left.kind else {
+            panic!("unexpected operand {:#?}", left);
+        };
+        assert_eq!(&**value, expected);
+        assert_eq!(file.text(left.span), literal);
+    }
+
+    let longest = format!("class Main {{ s : String <- \"{}\"; }};", "é".repeat(512));
+    parse(&longest);
+
+    let long = format!("class Main {{ s : String <- \"{}a\"; }};", "é".repeat(512));
+    for (src, message, line, col) in [
+        (
+            "class Main { s : String <- \"a\nb\"; };",
+            "unterminated string",
+            1,
+            28,
+        ),
+        (
+            "class Main { s : String <- \"open\n};",
+            "unterminated string",
+            1,
+            28,
+        ),
+        (
+            "class Main { s : String <- \"a\0b\"; };",
+            "string contains a null character",
+            1,
+            28,
+        ),
+        (
+            "class Main { s : String <- \"a\\\0b\"; };",
+            "string contains an escaped null character",
+            1,
+            28,
+        ),
+        (&long, "string is longer than 1024 bytes", 1, 28),
+        (
+            "class Main { s : String <- \"a\\\"\n; };",
+            "unterminated string",
+            1,
+            28,
+        ),
+    ] {
+        assert_eq!(errors(src), [(message.to_owned(), line, col)], "{}", src);
+    }
+}
+
+#[test]
+fn test_integers() {
+    let (program, _) = parse("class Main { i : Int <- 2147483647; };");
+    let Feature::Attribute(attribute) = &program.classes[0].features.0[0] else {
+        panic!("unexpected features {:#?}", program.classes[0].features);
+    };
+    let Some(ExprKind::Int(value)) = attribute.initializer.as_ref().map(|expr| &expr.kind) else {
+        panic!("unexpected initializer {:#?}", attribute.initializer);
+    };
+    assert_eq!(*value, i32::MAX);
+
+    for value in ["2147483648", "99999999999999999999999"] {
+        let src = format!("class Main {{ i : Int <- {}; }};", value);
+        assert_eq!(
+            errors(&src),
+            [("integer literal is too large".to_owned(), 1, 25)],
+            "{}",
+            value
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use cool::{
+    ast::{self, bindings::Tree, converter},
+    util::interner::Interner,
+};
+
+pub fn parse(src: &str) -> (ast::Program, Interner) {
+    let mut interner = Interner::with_capacity(64);
+    let tree = Tree::new(src.as_bytes());
+
+    match converter::convert(src.as_bytes(), &tree, &mut interner) {
+        Ok(program) => (program, interner),
+        Err((_, errors)) => panic!("failed converting test program: {:#?}", errors),
+    }
+}
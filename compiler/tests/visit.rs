@@ -0,0 +1,115 @@
+use cool::{
+    ast::{
+        Expr, ExprKind, Ident,
+        visit::{self, Visitor, VisitorMut},
+    },
+    util::interner::Interner,
+};
+
+use crate::test_utils::parse;
+
+mod test_utils;
+
+const PROGRAM: &str = r#"
+class Main inherits IO {
+    count : Int <- 0;
+    main() : Object {
+        let n : Int <- in_int() in {
+            while count < n loop count <- count + 1 pool;
+            case n of
+                x : Int => out_int(x);
+                o : Object => abort();
+            esac;
+        }
+    };
+};
+"#;
+
+/// Collects the names of identifiers and the values of integers.
+struct Collect<'i> {
+    interner: &'i Interner,
+    names: Vec<&'i str>,
+    ints: Vec<i32>,
+}
+
+impl<'i> Collect<'i> {
+    fn new(interner: &'i Interner) -> Collect<'i> {
+        Collect {
+            interner,
+            names: Vec::new(),
+            ints: Vec::new(),
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Collect<'_> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let ExprKind::Int(value) = expr.kind {
+            self.ints.push(value);
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_ident(&mut self, ident: &'ast Ident) {
+        self.names.push(self.interner.lookup(&ident.name));
+    }
+}
+
+/// Renames every identifier `count` to `total`, and doubles integers.
+struct Rename<'i> {
+    interner: &'i mut Interner,
+}
+
+impl VisitorMut for Rename<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let ExprKind::Int(value) = &mut expr.kind {
+            *value *= 2;
+        }
+        visit::walk_expr_mut(self, expr);
+    }
+
+    fn visit_ident_mut(&mut self, ident: &mut Ident) {
+        if self.interner.lookup(&ident.name) == "count" {
+            ident.name = self.interner.intern("total");
+        }
+    }
+}
+
+#[test]
+fn test_visitor() {
+    let (program, interner) = parse(PROGRAM);
+    let mut collect = Collect::new(&interner);
+    collect.visit_program(&program);
+
+    // Identifiers come in source order, types included.
+    assert_eq!(
+        collect.names,
+        [
+            "Main", "IO", "count", "Int", "main", "Object", "n", "Int", "in_int", "count", "n",
+            "count", "count", "n", "x", "Int", "out_int", "x", "o", "Object", "abort",
+        ]
+    );
+    assert_eq!(collect.ints, [0, 1]);
+}
+
+#[test]
+fn test_visitor_mut() {
+    let (mut program, mut interner) = parse(PROGRAM);
+    Rename {
+        interner: &mut interner,
+    }
+    .visit_program_mut(&mut program);
+
+    let mut collect = Collect::new(&interner);
+    collect.visit_program(&program);
+    assert_eq!(
+        collect
+            .names
+            .iter()
+            .filter(|name| **name == "total")
+            .count(),
+        4
+    );
+    assert!(!collect.names.contains(&"count"));
+    assert_eq!(collect.ints, [0, 2]);
+}
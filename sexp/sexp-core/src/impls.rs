@@ -57,8 +57,7 @@ where
     T: ToSexp,
 {
     fn to_sexp<S: SexpSerializer>(&self, serializer: &mut S) -> Result<()> {
-        self.into_iter()
-            .try_for_each(|item| item.to_sexp(serializer))
+        self.iter().try_for_each(|item| item.to_sexp(serializer))
     }
 }
 
@@ -70,7 +69,7 @@ macro_rules! seq_impls {
                 T: ToSexp,
             {
                 fn to_sexp<S: SexpSerializer>(&self, serializer: &mut S) -> Result<()> {
-                    self.into_iter()
+                    self.iter()
                         .try_for_each(|item| item.to_sexp(serializer))
                 }
             }
@@ -96,13 +95,13 @@ macro_rules! deref_impl {
 }
 
 deref_impl! {
-    <'a, T> ToSexp for &'a T where T: ToSexp
+    <'a, T> ToSexp for &'a T where T: ?Sized + ToSexp
 }
 
 deref_impl! {
-    <'a, T> ToSexp for &'a mut T where T: ToSexp
+    <'a, T> ToSexp for &'a mut T where T: ?Sized + ToSexp
 }
 
 deref_impl! {
-    <T> ToSexp for Box<T> where T: ToSexp
+    <T> ToSexp for Box<T> where T: ?Sized + ToSexp
 }
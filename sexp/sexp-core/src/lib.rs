@@ -1,4 +1,7 @@
-use std::io::{self};
+use std::{
+    any::Any,
+    io::{self},
+};
 
 mod impls;
 
@@ -23,6 +26,9 @@ pub trait SexpSerializer {
 
     fn serialize_str(&mut self, value: &str) -> Result<()>;
 
+    /// Writes a bare atom, like a name, without quoting or escaping it.
+    fn serialize_symbol(&mut self, value: &str) -> Result<()>;
+
     fn serialize_none(&mut self) -> Result<()>;
 
     fn serialize_some<T>(&mut self, value: &T) -> Result<()>
@@ -50,15 +56,22 @@ pub trait SexpSerializer {
     fn begin_value(&mut self) -> Result<()>;
 
     fn end_value(&mut self) -> Result<()>;
+
+    /// Data values need to be written but do not hold, like the table their
+    /// names are interned in.
+    fn context(&self) -> Option<&dyn Any>;
 }
 
-pub struct SexpSerializerImpl<W> {
+pub struct SexpSerializerImpl<'c, W> {
     writer: W,
     indent_depth: usize,
     has_value: bool,
+    /// Whether the last thing written is the `: ` after a field name.
+    after_field: bool,
+    context: Option<&'c dyn Any>,
 }
 
-impl<W> SexpSerializerImpl<W>
+impl<'c, W> SexpSerializerImpl<'c, W>
 where
     W: io::Write,
 {
@@ -67,11 +80,32 @@ where
             writer,
             indent_depth: 0,
             has_value: false,
+            after_field: false,
+            context: None,
+        }
+    }
+
+    pub fn with_context(writer: W, context: &'c dyn Any) -> Self {
+        SexpSerializerImpl {
+            writer,
+            indent_depth: 0,
+            has_value: false,
+            after_field: false,
+            context: Some(context),
         }
     }
+
+    /// Separates an atom from the name of its cell or the atom before it.
+    fn begin_atom(&mut self) -> Result<()> {
+        if self.indent_depth > 0 && !self.after_field {
+            self.writer.write_all(b" ")?;
+        }
+        self.after_field = false;
+        Ok(())
+    }
 }
 
-impl<W> SexpSerializer for SexpSerializerImpl<W>
+impl<W> SexpSerializer for SexpSerializerImpl<'_, W>
 where
     W: io::Write,
 {
@@ -81,6 +115,7 @@ where
         } else {
             b"false" as &[u8]
         };
+        self.begin_atom()?;
         self.writer.write_all(s)
     }
 
@@ -90,6 +125,7 @@ where
     {
         let mut buf = itoa::Buffer::new();
         let s = buf.format(value);
+        self.begin_atom()?;
         self.writer.write_all(s.as_bytes())
     }
 
@@ -99,6 +135,7 @@ where
     {
         let mut buf = ryu::Buffer::new();
         let s = buf.format_finite(value);
+        self.begin_atom()?;
         self.writer.write_all(s.as_bytes())
     }
 
@@ -108,9 +145,15 @@ where
     }
 
     fn serialize_str(&mut self, value: &str) -> Result<()> {
+        self.begin_atom()?;
         format_escaped_str(&mut self.writer, value)
     }
 
+    fn serialize_symbol(&mut self, value: &str) -> Result<()> {
+        self.begin_atom()?;
+        write_string_fragment(&mut self.writer, value)
+    }
+
     fn serialize_none(&mut self) -> Result<()> {
         self.serialize_unit()
     }
@@ -157,6 +200,7 @@ where
     }
 
     fn begin_cell(&mut self, name: &'static str) -> Result<()> {
+        self.after_field = false;
         if self.has_value {
             self.writer.write_all(b"\n")?;
             indent(&mut self.writer, self.indent_depth)?;
@@ -173,12 +217,17 @@ where
     }
 
     fn begin_value(&mut self) -> Result<()> {
+        self.after_field = true;
         self.writer.write_all(b": ")
     }
 
     fn end_value(&mut self) -> Result<()> {
         Ok(())
     }
+
+    fn context(&self) -> Option<&dyn Any> {
+        self.context
+    }
 }
 
 fn format_escaped_str<W>(writer: &mut W, value: &str) -> Result<()>
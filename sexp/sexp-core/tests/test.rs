@@ -10,7 +10,35 @@ struct Unit(Vec<u8>);
 
 #[test]
 fn test_unit_struct() {
-    assert_sexp(&Unit(vec![1, 2, 3]), "unit");
+    assert_sexp(&Unit(vec![1, 2, 3]), "(unit 1 2 3)");
+}
+
+#[test]
+fn test_transparent() {
+    #[derive(Sexp)]
+    #[sexp(transparent)]
+    struct Name(String);
+
+    #[derive(Sexp)]
+    #[sexp(rename = "pair")]
+    struct Pair {
+        left: Name,
+        #[sexp(skip)]
+        #[allow(dead_code)]
+        hidden: u8,
+        right: Name,
+    }
+
+    assert_sexp(
+        &Pair {
+            left: Name("a".to_owned()),
+            hidden: 0,
+            right: Name("b".to_owned()),
+        },
+        r#"(pair
+  (left: "a")
+  (right: "b"))"#,
+    );
 }
 
 #[test]
@@ -33,11 +61,11 @@ fn test_unit_variant() {
             my_enum: vec![
                 MyEnum::Struct { foo: 4, bar: 5.3 },
                 MyEnum::Struct { foo: 80, bar: 10.0 },
+                MyEnum::NewType(false),
             ],
             my_bool: false,
         },
-        r#"
-(test
+        r#"(test
   (my_enum: 
     (struct
       (foo: 4)
@@ -45,9 +73,7 @@ fn test_unit_variant() {
     (struct
       (foo: 80)
       (bar: 10.0))
-    (newtype
-        false))
-  (my_bool: false))
-"#,
+    (newtype false))
+  (my_bool: false))"#,
     );
 }
@@ -19,13 +19,11 @@ pub struct Variant<'a> {
     pub attrs: attr::Variant,
     pub style: Style,
     pub fields: Vec<Field<'a>>,
-    pub original: &'a syn::Variant,
 }
 
 pub struct Field<'a> {
     pub member: syn::Member,
     pub attrs: attr::Field,
-    pub ty: &'a syn::Type,
     pub original: &'a syn::Field,
 }
 
@@ -76,6 +74,26 @@ impl<'a> Container<'a> {
             }
         }
 
+        // A transparent container is written as its only field.
+        if attrs.transparent() {
+            let mut fields = match &mut data {
+                Data::Struct(_, fields) => fields.iter_mut().filter(|field| !field.attrs.skip()),
+                Data::Enum(_) => {
+                    ctx.error_spanned_by(item, "#[sexp(transparent)] cannot be used on enums");
+                    return None;
+                }
+            };
+
+            match (fields.next(), fields.next()) {
+                (Some(field), None) => field.attrs.mark_transparent(),
+                _ => {
+                    let msg = "#[sexp(transparent)] requires exactly one field that is not skipped";
+                    ctx.error_spanned_by(item, msg);
+                    return None;
+                }
+            }
+        }
+
         Some(Container {
             ident: item.ident.clone(),
             attrs,
@@ -100,7 +118,6 @@ fn enum_from_ast<'a>(
                 attrs,
                 style,
                 fields,
-                original: variant,
             }
         })
         .collect()
@@ -130,7 +147,6 @@ fn fields_from_ast<'a>(ctx: &Ctx, fields: &'a Punctuated<syn::Field, Token![,]>)
             Field {
                 member,
                 attrs: attr::Field::from_ast(ctx, idx, field),
-                ty: &field.ty,
                 original: field,
             }
         })
@@ -87,10 +87,10 @@ impl Container {
                 continue;
             }
 
-            if let syn::Meta::List(meta) = &attr.meta {
-                if meta.tokens.is_empty() {
-                    continue;
-                }
+            if let syn::Meta::List(meta) = &attr.meta
+                && meta.tokens.is_empty()
+            {
+                continue;
             }
 
             if let Err(err) = attr.parse_nested_meta(|meta| {
@@ -163,6 +163,7 @@ pub struct Variant {
     name: Name,
     rename_all: RenameRule,
     skip: bool,
+    transparent: bool,
 }
 
 impl Variant {
@@ -170,16 +171,17 @@ impl Variant {
         let mut name = Attr::none(ctx, RENAME);
         let mut rename_all = Attr::none(ctx, RENAME_ALL);
         let mut skip = BoolAttr::none(ctx, SKIP);
+        let mut transparent = BoolAttr::none(ctx, TRANSPARENT);
 
         for attr in &variant.attrs {
             if attr.path() != SEXP {
                 continue;
             }
 
-            if let syn::Meta::List(meta) = &attr.meta {
-                if meta.tokens.is_empty() {
-                    continue;
-                }
+            if let syn::Meta::List(meta) = &attr.meta
+                && meta.tokens.is_empty()
+            {
+                continue;
             }
 
             if let Err(err) = attr.parse_nested_meta(|meta| {
@@ -195,6 +197,16 @@ impl Variant {
                     }
                 } else if meta.path == SKIP {
                     skip.set_true(&meta.path);
+                } else if meta.path == TRANSPARENT {
+                    match &variant.fields {
+                        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                            transparent.set_true(&meta.path);
+                        }
+                        _ => {
+                            let msg = "#[sexp(transparent)] can only be used on newtype variants";
+                            ctx.syn_error(meta.error(msg));
+                        }
+                    }
                 } else {
                     let path = meta.path.to_token_stream().to_string().replace(' ', "");
                     return Err(
@@ -211,6 +223,7 @@ impl Variant {
             name: name.unwrap_or(Name::from(&unraw(&variant.ident))),
             rename_all: rename_all.get().unwrap_or_default(),
             skip: skip.get(),
+            transparent: transparent.get(),
         }
     }
 
@@ -229,6 +242,11 @@ impl Variant {
     pub fn skip(&self) -> bool {
         self.skip
     }
+
+    /// Whether the field of the variant is written in place of the variant.
+    pub fn transparent(&self) -> bool {
+        self.transparent
+    }
 }
 
 pub struct Field {
@@ -258,10 +276,10 @@ impl Field {
                 continue;
             }
 
-            if let syn::Meta::List(meta) = &attr.meta {
-                if meta.tokens.is_empty() {
-                    continue;
-                }
+            if let syn::Meta::List(meta) = &attr.meta
+                && meta.tokens.is_empty()
+            {
+                continue;
             }
 
             if let Err(err) = attr.parse_nested_meta(|meta| {
@@ -309,6 +327,10 @@ impl Field {
     pub fn transparent(&self) -> bool {
         self.transparent
     }
+
+    pub fn mark_transparent(&mut self) {
+        self.transparent = true;
+    }
 }
 
 fn get_rename(
@@ -321,7 +343,7 @@ fn get_rename(
     if !lookahead.peek(Token![=]) {
         return Err(lookahead.error());
     }
-    let literal = {
+    {
         let expr: syn::Expr = meta.value()?.parse()?;
         let mut value = &expr;
 
@@ -348,8 +370,7 @@ fn get_rename(
             );
             Ok(None)
         }
-    };
-    literal
+    }
 }
 
 fn unraw(ident: &Ident) -> Ident {
@@ -21,13 +21,14 @@ static RENAME_RULES: &[(&str, RenameRule)] = &[
     ("UPPERCASE", UpperCase),
     ("PascalCase", PascalCase),
     ("camelCase", CamelCase),
+    ("snake_case", SnakeCase),
     ("SCREAMING_SNAKE_CASE", ScreamingSnakeCase),
     ("kebab-case", KebabCase),
     ("SCREAMING-KEBAB-CASE", ScreamingKebabCase),
 ];
 
 impl RenameRule {
-    pub fn from_str(s: &str) -> Result<Self, ParseError> {
+    pub fn from_str(s: &str) -> Result<Self, ParseError<'_>> {
         for (name, rule) in RENAME_RULES {
             if s == *name {
                 return Ok(*rule);
@@ -100,7 +101,7 @@ impl RenameRule {
 
 pub struct ParseError<'a>(&'a str);
 
-impl<'a> Display for ParseError<'a> {
+impl Display for ParseError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
@@ -36,9 +36,11 @@ fn expand_derive_sexp(input: &mut syn::DeriveInput) -> syn::Result<TokenStream>
         let ident = &container.ident;
         let this = syn::Path::from(container.ident.clone());
         let body = get_body(&container, &this);
+        let generics = with_bounds(&container.original.generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
         quote! {
-            impl sexp_core::ToSexp for #ident {
+            impl #impl_generics sexp_core::ToSexp for #ident #ty_generics #where_clause {
                 fn to_sexp<S>(&self, s: &mut S) -> sexp_core::Result<()>
                 where
                     S: sexp_core::SexpSerializer,
@@ -56,7 +58,7 @@ fn get_body(container: &Container, this: &syn::Path) -> TokenStream {
         get_transparent(container)
     } else {
         match &container.data {
-            Data::Enum(variants) => get_enum(this, variants, &container.attrs),
+            Data::Enum(variants) => get_enum(this, variants),
             Data::Struct(Style::Struct, fields) => get_struct(fields, &container.attrs),
             Data::Struct(Style::Tuple, fields) => get_tuple_struct(fields, &container.attrs),
             Data::Struct(Style::NewType, _) => get_newtype_struct(&container.attrs),
@@ -81,17 +83,24 @@ fn get_transparent(container: &Container) -> TokenStream {
     let member = &transparent.member;
 
     quote_block! {
-        #path(&self.#member)
+        #path(&self.#member, s)
     }
 }
 
-fn get_enum(this: &syn::Path, variants: &[Variant], attrs: &attr::Container) -> TokenStream {
-    assert!(variants.len() as u64 <= u64::from(u32::MAX));
+/// Requires every type parameter to implement `ToSexp`.
+fn with_bounds(generics: &syn::Generics) -> syn::Generics {
+    let mut generics = generics.clone();
 
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(sexp_core::ToSexp));
+    }
+    generics
+}
+
+fn get_enum(this: &syn::Path, variants: &[Variant]) -> TokenStream {
     let arms: Vec<_> = variants
         .iter()
-        .enumerate()
-        .map(|(idx, variant)| get_variant(this, variant, idx as u32, attrs))
+        .map(|variant| get_variant(this, variant))
         .collect();
 
     quote! {
@@ -101,12 +110,7 @@ fn get_enum(this: &syn::Path, variants: &[Variant], attrs: &attr::Container) ->
     }
 }
 
-fn get_variant(
-    this: &syn::Path,
-    variant: &Variant,
-    idx: u32,
-    attrs: &attr::Container,
-) -> TokenStream {
+fn get_variant(this: &syn::Path, variant: &Variant) -> TokenStream {
     let variant_ident = &variant.ident;
 
     if variant.attrs.skip() {
@@ -157,13 +161,14 @@ fn get_variant(
 
             match filtered_variant_style(variant) {
                 Style::Unit => {
-                    quote_block!(s.cell(#variant_name, |_| Ok(())))
+                    quote_block!(s.serialize_symbol(#variant_name))
+                }
+                Style::NewType if variant.attrs.transparent() => {
+                    quote_block!(sexp_core::ToSexp::to_sexp(field, s))
                 }
                 Style::NewType => {
                     quote_block! {
-                        s.cell(#variant_name, |s| {
-                            s.serialize_tuple_field(field)?;
-                        })
+                        s.cell(#variant_name, |s| s.serialize_tuple_field(field))
                     }
                 }
                 Style::Tuple => get_tuple_variant(variant_name, &variant.fields),
@@ -182,6 +187,7 @@ fn get_tuple_variant(variant_name: &Name, fields: &[Field]) -> TokenStream {
     quote_block! {
         s.cell(#variant_name, |s| {
             #(#stmts)*
+            Ok(())
         })
     }
 }
@@ -192,6 +198,7 @@ fn get_struct_variant(variant_name: &Name, fields: &[Field]) -> TokenStream {
     quote_block! {
         s.cell(#variant_name, |s| {
             #(#stmts)*
+            Ok(())
         })
     }
 }
@@ -203,6 +210,7 @@ fn get_struct(fields: &[Field], attrs: &attr::Container) -> TokenStream {
     quote_block! {
         s.cell(#type_name, |s| {
             #(#stmts)*
+            Ok(())
         })
     }
 }
@@ -214,6 +222,7 @@ fn get_tuple_struct(fields: &[Field], attrs: &attr::Container) -> TokenStream {
     quote_block! {
         s.cell(#type_name, |s| {
             #(#stmts)*
+            Ok(())
         })
     }
 }
@@ -280,7 +289,13 @@ fn get_struct_fields(fields: &[Field], is_enum: bool) -> Vec<TokenStream> {
             let key = field.attrs.name();
             let span = field.original.span();
 
-            quote_spanned!(span=> s.serialize_struct_field(#key, #field_expr)?;)
+            // Flattened fields are written among those of their container,
+            // without a cell of their own.
+            if field.attrs.flatten() {
+                quote_spanned!(span=> s.serialize_tuple_field(#field_expr)?;)
+            } else {
+                quote_spanned!(span=> s.serialize_struct_field(#key, #field_expr)?;)
+            }
         })
         .collect()
 }